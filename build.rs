@@ -14,7 +14,7 @@ fn setup_opengl()
     let mut file_gl = File::create(&Path::new(&out_dir).join("bindings.rs")).unwrap();
 
     use gl_generator::{StructGenerator, DebugStructGenerator, Registry, Fallbacks, Api, Profile};
-    let registry = Registry::new(Api::Gl, (4, 3), Profile::Core, Fallbacks::All, []);
+    let registry = Registry::new(Api::Gl, (4, 3), Profile::Core, Fallbacks::All, ["GL_ARB_clip_control"]);
 
     if env::var("CARGO_FEATURE_DEBUG").is_ok() {
         registry.write_bindings(
@@ -95,7 +95,7 @@ fn main() {
 
                 RenderTarget::write_to_color(&gl, 0, 0, width, height, Some(&vec4(0.0, 0.0, 0.0, 0.0)), Some(&color_texture), || {
                     skybox.apply(&camera)?;
-                    renderer.light_pass(&camera, Some(&ambient_light), &[&directional_light], &[], &[])?;
+                    renderer.light_pass(&camera, &Lights {ambient: Some(Ambient::Light(&ambient_light)), directional_lights: &[&directional_light], ..Default::default()})?;
                     if fog_enabled {
                         fog_effect.apply(time as f32, &camera, renderer.geometry_pass_depth_texture())?;
                     }
@@ -107,7 +107,7 @@ fn main() {
                     Ok(())
                 }).unwrap();
             } else {
-                renderer.render_to_screen_with_forward_pass(&camera, Some(&ambient_light), &[&directional_light], &[], &[], width, height, || {
+                renderer.render_to_screen_with_forward_pass(&camera, &Lights {ambient: Some(Ambient::Light(&ambient_light)), directional_lights: &[&directional_light], ..Default::default()}, width, height, || {
                     skybox.apply(&camera)?;
                     if fog_enabled {
                         fog_effect.apply(time as f32, &camera, renderer.geometry_pass_depth_texture())?;
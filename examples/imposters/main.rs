@@ -116,7 +116,7 @@ fn main() {
 
             // Light pass
             Screen::write(&gl, 0, 0, width, height, Some(&vec4(0.8, 0.8, 0.8, 1.0)), Some(1.0), &|| {
-                renderer.light_pass(&camera, Some(&ambient_light), &[&directional_light], &[], &[])?;
+                renderer.light_pass(&camera, &Lights {ambient: Some(Ambient::Light(&ambient_light)), directional_lights: &[&directional_light], ..Default::default()})?;
 
                 state::cull(&gl, state::CullType::None);
                 state::blend(&gl, state::BlendType::SrcAlphaOneMinusSrcAlpha);
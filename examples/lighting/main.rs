@@ -115,8 +115,8 @@ fn main() {
                 }).unwrap();
 
             // Light pass
-            renderer.render_to_screen(&camera, None, &[&directional_light0, &directional_light1],
-                                    &[&spot_light], &[&point_light0, &point_light1], width, height).unwrap();
+            renderer.render_to_screen(&camera, &Lights {directional_lights: &[&directional_light0, &directional_light1],
+                                    spot_lights: &[&spot_light], point_lights: &[&point_light0, &point_light1], ..Default::default()}, width, height).unwrap();
 
             #[cfg(target_arch = "x86_64")]
             if let Some(ref path) = screenshot_path {
@@ -85,7 +85,7 @@ fn main() {
                 Ok(())
             }).unwrap();
 
-            renderer.render_to_screen_with_forward_pass(&camera, Some(&ambient_light), &[&directional_light], &[], &[], width, height, || {
+            renderer.render_to_screen_with_forward_pass(&camera, &Lights {ambient: Some(Ambient::Light(&ambient_light)), directional_lights: &[&directional_light], ..Default::default()}, width, height, || {
                 let transformation = Mat4::from_translation(vec3(0.5, 1.0, 0.0));
                 state::cull(&gl, state::CullType::Back);
                 penguin.mesh().render_with_ambient_and_directional(&transformation, &camera, &ambient_light, &directional_light)?;
@@ -31,7 +31,8 @@ fn main() {
             diffuse_intensity: 0.8,
             specular_intensity: 0.2,
             specular_power: 5.0,
-            color_source: ColorSource::Color(vec4(0.9, 0.2, 0.2, 1.0))
+            color_source: ColorSource::Color(vec4(0.9, 0.2, 0.2, 1.0)),
+            ..Default::default()
         };
         let edges = renderer.new_cylinder_instances(cpu_mesh.indices.as_ref().unwrap(), &cpu_mesh.positions, 0.007, &wireframe_material).unwrap();
         let vertices = renderer.new_sphere_instances(&cpu_mesh.positions, 0.015, &wireframe_material).unwrap();
@@ -109,7 +110,7 @@ fn main() {
                 }).unwrap();
 
                 // Light pass
-                renderer.render_to_screen(&camera, None, &[], &[&spot_light0, &spot_light1, &spot_light2, &spot_light3], &[], width, height).unwrap();
+                renderer.render_to_screen(&camera, &Lights {spot_lights: &[&spot_light0, &spot_light1, &spot_light2, &spot_light3], ..Default::default()}, width, height).unwrap();
                 
                 #[cfg(target_arch = "x86_64")]
                 if let Some(ref path) = screenshot_path {
@@ -1,5 +1,7 @@
 pub mod buffer;
 pub mod program;
+#[macro_use]
+pub mod shader_source;
 pub mod rendertarget;
 pub mod state;
 pub mod texture;
@@ -7,12 +9,16 @@ pub mod types;
 pub mod camera;
 pub mod image_effect;
 pub mod aabb;
+pub mod compressed_image;
+pub mod reflection_probe;
+pub mod planar_reflection;
 
 pub use crate::gl::Gl;
 pub use crate::gl::consts;
 
 pub use buffer::*;
 pub use program::*;
+pub use shader_source::*;
 pub use rendertarget::*;
 pub use state::*;
 pub use texture::*;
@@ -20,6 +26,9 @@ pub use types::*;
 pub use camera::*;
 pub use image_effect::*;
 pub use aabb::*;
+pub use compressed_image::*;
+pub use reflection_probe::*;
+pub use planar_reflection::*;
 
 pub mod cpu_mesh;
 pub use crate::cpu_mesh::*;
@@ -37,8 +46,10 @@ pub enum Error {
     FailedToLinkProgram {message: String},
     FailedToFindAttribute {message: String},
     FailedToFindUniform {message: String},
+    FailedToSetUniform {message: String},
     FailedToCreateFramebuffer {message: String},
     FailedToCreateTexture {message: String},
     FailedToUpdateBuffer {message: String},
-    FailedToCreateMesh {message: String}
+    FailedToCreateMesh {message: String},
+    FailedToRender {message: String}
 }
\ No newline at end of file
@@ -2,54 +2,234 @@ use crate::core::Error;
 use crate::gl::Gl;
 use crate::gl::consts;
 
+///
+/// The data type of each component of a vertex attribute.
+/// Half floats are uploaded as the raw 16 bit patterns in a u16 buffer.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ComponentType {
+    F32,
+    F16,
+    I32,
+    U32,
+    I16,
+    U16,
+    I8,
+    U8
+}
+
+impl ComponentType {
+    pub(crate) fn gl_type(&self) -> u32
+    {
+        match self {
+            ComponentType::F32 => consts::FLOAT,
+            ComponentType::F16 => consts::HALF_FLOAT,
+            ComponentType::I32 => consts::INT,
+            ComponentType::U32 => consts::UNSIGNED_INT,
+            ComponentType::I16 => consts::SHORT,
+            ComponentType::U16 => consts::UNSIGNED_SHORT,
+            ComponentType::I8 => consts::BYTE,
+            ComponentType::U8 => consts::UNSIGNED_BYTE
+        }
+    }
+
+    pub fn size_in_bytes(&self) -> u32
+    {
+        match self {
+            ComponentType::F32 | ComponentType::I32 | ComponentType::U32 => 4,
+            ComponentType::F16 | ComponentType::I16 | ComponentType::U16 => 2,
+            ComponentType::I8 | ComponentType::U8 => 1
+        }
+    }
+
+    pub fn is_integer(&self) -> bool
+    {
+        !matches!(self, ComponentType::F32 | ComponentType::F16)
+    }
+}
+
+///
+/// Describes where a vertex attribute is located in a vertex buffer and how it is read.
+/// The stride and offset are in bytes and a stride of 0 means that the attribute values are tightly packed.
+/// Integer components are converted to floating point in the range [0, 1] (unsigned) or [-1, 1] (signed) if normalized is true,
+/// otherwise they are converted directly or, if the attribute is an integer type in the shader, passed on as integers.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct AttributeLayout {
+    pub component_type: ComponentType,
+    pub component_count: u32,
+    pub normalized: bool,
+    pub stride: u32,
+    pub offset: u32,
+    pub divisor: u32
+}
+
+impl AttributeLayout {
+    pub fn new(component_type: ComponentType, component_count: u32) -> Self
+    {
+        AttributeLayout {component_type, component_count, normalized: false, stride: 0, offset: 0, divisor: 0}
+    }
+
+    ///
+    /// An attribute in an interleaved buffer where each vertex takes up stride bytes and the attribute starts at offset bytes into the vertex.
+    ///
+    pub fn interleaved(component_type: ComponentType, component_count: u32, stride: u32, offset: u32) -> Self
+    {
+        AttributeLayout {component_type, component_count, normalized: false, stride, offset, divisor: 0}
+    }
+
+    pub fn size_in_bytes(&self) -> u32
+    {
+        self.component_type.size_in_bytes() * self.component_count
+    }
+}
+
+///
+/// A type which can be uploaded to a vertex buffer.
+///
+pub trait BufferDataType: Copy {
+    const COMPONENT_TYPE: ComponentType;
+}
+
+impl BufferDataType for f32 { const COMPONENT_TYPE: ComponentType = ComponentType::F32; }
+impl BufferDataType for i32 { const COMPONENT_TYPE: ComponentType = ComponentType::I32; }
+impl BufferDataType for u32 { const COMPONENT_TYPE: ComponentType = ComponentType::U32; }
+impl BufferDataType for i16 { const COMPONENT_TYPE: ComponentType = ComponentType::I16; }
+impl BufferDataType for u16 { const COMPONENT_TYPE: ComponentType = ComponentType::U16; }
+impl BufferDataType for i8 { const COMPONENT_TYPE: ComponentType = ComponentType::I8; }
+impl BufferDataType for u8 { const COMPONENT_TYPE: ComponentType = ComponentType::U8; }
+
+fn as_bytes<T: BufferDataType>(data: &[T]) -> &[u8]
+{
+    // Safe since all the buffer data types are plain numbers without padding
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data)) }
+}
+
 pub struct VertexBuffer {
     gl: Gl,
     id: crate::gl::Buffer,
-    count: usize
+    count: usize,
+    size_in_bytes: usize
 }
 
 impl VertexBuffer
 {
     pub fn new_with_static_f32(gl: &Gl, data: &[f32]) -> Result<VertexBuffer, Error>
+    {
+        Self::new_with_static(gl, data)
+    }
+
+    pub fn fill_with_static_f32(&mut self, data: &[f32])
+    {
+        self.fill_with_static(data);
+    }
+
+    pub fn new_with_dynamic_f32(gl: &Gl, data: &[f32]) -> Result<VertexBuffer, Error>
+    {
+        Self::new_with_dynamic(gl, data)
+    }
+
+    pub fn fill_with_dynamic_f32(&mut self, data: &[f32])
+    {
+        self.fill_with_dynamic(data);
+    }
+
+    pub fn new_with_static<T: BufferDataType>(gl: &Gl, data: &[T]) -> Result<VertexBuffer, Error>
     {
         let id = gl.create_buffer().unwrap();
-        let mut buffer = VertexBuffer { gl: gl.clone(), id, count: 0 };
-        if data.len() > 0 {
-            buffer.fill_with_static_f32(data);
+        let mut buffer = VertexBuffer { gl: gl.clone(), id, count: 0, size_in_bytes: 0 };
+        if !data.is_empty() {
+            buffer.fill_with_static(data);
         }
         Ok(buffer)
     }
 
-    pub fn fill_with_static_f32(&mut self, data: &[f32])
+    pub fn fill_with_static<T: BufferDataType>(&mut self, data: &[T])
     {
-        self.bind();
-        self.gl.buffer_data_f32(consts::ARRAY_BUFFER, data, consts::STATIC_DRAW);
-        self.gl.unbind_buffer(consts::ARRAY_BUFFER);
-        self.count = data.len();
+        self.fill(as_bytes(data), data.len(), consts::STATIC_DRAW);
     }
 
-    pub fn new_with_dynamic_f32(gl: &Gl, data: &[f32]) -> Result<VertexBuffer, Error>
+    pub fn new_with_dynamic<T: BufferDataType>(gl: &Gl, data: &[T]) -> Result<VertexBuffer, Error>
     {
         let id = gl.create_buffer().unwrap();
-        let mut buffer = VertexBuffer { gl: gl.clone(), id, count: 0 };
-        if data.len() > 0 {
-            buffer.fill_with_dynamic_f32(data);
+        let mut buffer = VertexBuffer { gl: gl.clone(), id, count: 0, size_in_bytes: 0 };
+        if !data.is_empty() {
+            buffer.fill_with_dynamic(data);
         }
         Ok(buffer)
     }
 
-    pub fn fill_with_dynamic_f32(&mut self, data: &[f32])
+    pub fn fill_with_dynamic<T: BufferDataType>(&mut self, data: &[T])
+    {
+        self.fill(as_bytes(data), data.len(), consts::DYNAMIC_DRAW);
+    }
+
+    ///
+    /// Creates a buffer for data which is rewritten every frame, see [fill_with_stream](#method.fill_with_stream).
+    ///
+    pub fn new_with_stream<T: BufferDataType>(gl: &Gl, data: &[T]) -> Result<VertexBuffer, Error>
+    {
+        let id = gl.create_buffer().unwrap();
+        let mut buffer = VertexBuffer { gl: gl.clone(), id, count: 0, size_in_bytes: 0 };
+        if !data.is_empty() {
+            buffer.fill_with_stream(data);
+        }
+        Ok(buffer)
+    }
+
+    ///
+    /// Fills the buffer with data which is rewritten every frame.
+    /// The old storage is orphaned (reallocated without data) before the new data is uploaded,
+    /// so the driver does not have to wait for draw calls which are still using the old data.
+    ///
+    pub fn fill_with_stream<T: BufferDataType>(&mut self, data: &[T])
     {
+        let bytes = as_bytes(data);
         self.bind();
-        self.gl.buffer_data_f32(consts::ARRAY_BUFFER, data, consts::DYNAMIC_DRAW);
+        self.gl.buffer_data(consts::ARRAY_BUFFER, bytes.len() as u32, consts::STREAM_DRAW);
+        self.gl.buffer_sub_data_u8(consts::ARRAY_BUFFER, 0, bytes);
         self.gl.unbind_buffer(consts::ARRAY_BUFFER);
         self.count = data.len();
+        self.size_in_bytes = bytes.len();
     }
 
+    ///
+    /// Updates part of the buffer without reallocating it. The offset is the number of values of type T (not bytes) before the first value to update.
+    ///
+    pub fn fill_subset<T: BufferDataType>(&mut self, offset: usize, data: &[T]) -> Result<(), Error>
+    {
+        let offset_in_bytes = offset * std::mem::size_of::<T>();
+        let bytes = as_bytes(data);
+        if offset_in_bytes + bytes.len() > self.size_in_bytes {
+            return Err(Error::FailedToUpdateBuffer {message: format!("Cannot update bytes {}-{} of a vertex buffer of {} bytes",
+                                                                       offset_in_bytes, offset_in_bytes + bytes.len(), self.size_in_bytes)});
+        }
+        self.bind();
+        self.gl.buffer_sub_data_u8(consts::ARRAY_BUFFER, offset_in_bytes as u32, bytes);
+        self.gl.unbind_buffer(consts::ARRAY_BUFFER);
+        Ok(())
+    }
+
+    fn fill(&mut self, data: &[u8], count: usize, usage: u32)
+    {
+        self.bind();
+        self.gl.buffer_data_u8(consts::ARRAY_BUFFER, data, usage);
+        self.gl.unbind_buffer(consts::ARRAY_BUFFER);
+        self.count = count;
+        self.size_in_bytes = data.len();
+    }
+
+    ///
+    /// The number of values (not vertices) in the buffer.
+    ///
     pub fn count(&self) -> usize {
         self.count
     }
 
+    pub fn size_in_bytes(&self) -> usize {
+        self.size_in_bytes
+    }
+
     pub(crate) fn bind(&self)
     {
         self.gl.bind_buffer(consts::ARRAY_BUFFER, &self.id);
@@ -67,16 +247,16 @@ impl Drop for VertexBuffer
 pub struct ElementBuffer {
     gl: Gl,
     id: crate::gl::Buffer,
-    count: usize
+    count: usize,
+    data_type: u32
 }
 
 impl ElementBuffer
 {
     pub fn new_with_u32(gl: &Gl, data: &[u32]) -> Result<ElementBuffer, Error>
     {
-        let id = gl.create_buffer().unwrap();
-        let mut buffer = ElementBuffer{ gl: gl.clone(), id, count: 0 };
-        if data.len() > 0 {
+        let mut buffer = Self::new(gl);
+        if !data.is_empty() {
             buffer.fill_with_u32(data);
         }
         Ok(buffer)
@@ -84,16 +264,94 @@ impl ElementBuffer
 
     pub fn fill_with_u32(&mut self, data: &[u32])
     {
+        self.fill(as_bytes(data), data.len(), consts::UNSIGNED_INT);
+    }
+
+    pub fn new_with_u16(gl: &Gl, data: &[u16]) -> Result<ElementBuffer, Error>
+    {
+        let mut buffer = Self::new(gl);
+        if !data.is_empty() {
+            buffer.fill_with_u16(data);
+        }
+        Ok(buffer)
+    }
+
+    pub fn fill_with_u16(&mut self, data: &[u16])
+    {
+        self.fill(as_bytes(data), data.len(), consts::UNSIGNED_SHORT);
+    }
+
+    pub fn new_with_u8(gl: &Gl, data: &[u8]) -> Result<ElementBuffer, Error>
+    {
+        let mut buffer = Self::new(gl);
+        if !data.is_empty() {
+            buffer.fill_with_u8(data);
+        }
+        Ok(buffer)
+    }
+
+    pub fn fill_with_u8(&mut self, data: &[u8])
+    {
+        self.fill(data, data.len(), consts::UNSIGNED_BYTE);
+    }
+
+    ///
+    /// Updates part of the buffer without reallocating it. The offset is the number of indices before the first index to update.
+    ///
+    pub fn fill_subset_with_u32(&mut self, offset: usize, data: &[u32]) -> Result<(), Error>
+    {
+        self.fill_subset(offset, as_bytes(data), consts::UNSIGNED_INT)
+    }
+
+    pub fn fill_subset_with_u16(&mut self, offset: usize, data: &[u16]) -> Result<(), Error>
+    {
+        self.fill_subset(offset, as_bytes(data), consts::UNSIGNED_SHORT)
+    }
+
+    pub fn fill_subset_with_u8(&mut self, offset: usize, data: &[u8]) -> Result<(), Error>
+    {
+        self.fill_subset(offset, data, consts::UNSIGNED_BYTE)
+    }
+
+    fn fill_subset(&mut self, offset: usize, data: &[u8], data_type: u32) -> Result<(), Error>
+    {
+        if data_type != self.data_type {
+            return Err(Error::FailedToUpdateBuffer {message: "The index type does not match the type the element buffer was created with".to_string()});
+        }
+        let index_size = crate::gl::byte_size_for_type(data_type, 1) as usize;
+        let count = data.len() / index_size;
+        if offset + count > self.count {
+            return Err(Error::FailedToUpdateBuffer {message: format!("Cannot update indices {}-{} of an element buffer with {} indices", offset, offset + count, self.count)});
+        }
         self.bind();
-        self.gl.buffer_data_u32(consts::ELEMENT_ARRAY_BUFFER, data, consts::STATIC_DRAW);
+        self.gl.buffer_sub_data_u8(consts::ELEMENT_ARRAY_BUFFER, (offset * index_size) as u32, data);
         self.gl.unbind_buffer(consts::ELEMENT_ARRAY_BUFFER);
-        self.count = data.len();
+        Ok(())
+    }
+
+    fn new(gl: &Gl) -> ElementBuffer
+    {
+        let id = gl.create_buffer().unwrap();
+        ElementBuffer{ gl: gl.clone(), id, count: 0, data_type: consts::UNSIGNED_INT }
+    }
+
+    fn fill(&mut self, data: &[u8], count: usize, data_type: u32)
+    {
+        self.bind();
+        self.gl.buffer_data_u8(consts::ELEMENT_ARRAY_BUFFER, data, consts::STATIC_DRAW);
+        self.gl.unbind_buffer(consts::ELEMENT_ARRAY_BUFFER);
+        self.count = count;
+        self.data_type = data_type;
     }
 
     pub fn count(&self) -> usize {
         self.count
     }
 
+    pub(crate) fn data_type(&self) -> u32 {
+        self.data_type
+    }
+
     pub(crate) fn bind(&self)
     {
         self.gl.bind_buffer(consts::ELEMENT_ARRAY_BUFFER, &self.id);
@@ -1,6 +1,118 @@
 
 use crate::core::*;
 
+///
+/// A rectangle in pixels, where (x, y) is the lower left corner, which defines the part of the screen or render target that is rendered to.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: i32,
+    pub y: i32,
+    pub width: usize,
+    pub height: usize
+}
+
+impl Viewport {
+    pub fn new_at_origo(width: usize, height: usize) -> Self
+    {
+        Self {x: 0, y: 0, width, height}
+    }
+
+    pub fn aspect(&self) -> f32
+    {
+        self.width as f32 / self.height as f32
+    }
+}
+
+///
+/// A ray starting at the origin and going in the given direction, which is normalized.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3
+}
+
+impl Ray {
+    ///
+    /// The point at the given distance from the origin along the ray.
+    ///
+    pub fn at(&self, distance: f32) -> Vec3
+    {
+        self.origin + self.direction * distance
+    }
+}
+
+///
+/// The position and orientation of a camera together with the near and far planes and, for an orthographic camera, the height of the view,
+/// see [Camera::pose] and [Camera::set_pose]. The orthographic height is not used by a perspective camera.
+/// The position and target are relative to the [origin](Camera::origin) of the camera.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CameraPose {
+    pub position: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    pub z_near: f32,
+    pub z_far: f32,
+    pub orthographic_height: f32
+}
+
+///
+/// A smooth transition from one camera pose to another, for example to frame a selected object with [Camera::pose_fitted_to].
+/// The camera orbits around the moving target, so that it keeps looking at the target during the transition.
+///
+pub struct CameraTransition {
+    from: CameraPose,
+    to: CameraPose,
+    duration: f64,
+    time: f64
+}
+
+impl CameraTransition {
+    ///
+    /// Creates a transition which takes the given duration in milliseconds, the same unit as [FrameInput::elapsed_time](crate::FrameInput::elapsed_time).
+    ///
+    pub fn new(from: CameraPose, to: CameraPose, duration: f64) -> Self
+    {
+        Self {from, to, duration, time: 0.0}
+    }
+
+    ///
+    /// Advances the transition by the given time in milliseconds and sets the resulting pose on the camera.
+    ///
+    pub fn update(&mut self, camera: &mut Camera, elapsed_time: f64)
+    {
+        self.time = (self.time + elapsed_time).min(self.duration);
+        camera.set_pose(&self.pose());
+    }
+
+    ///
+    /// The pose at the current time of the transition.
+    ///
+    pub fn pose(&self) -> CameraPose
+    {
+        let t = if self.duration > 0.0 { (self.time / self.duration) as f32 } else { 1.0 };
+        // Smooth start and stop
+        let t = t * t * (3.0 - 2.0 * t);
+        let lerp = |a: f32, b: f32| if a == b { a } else { a + (b - a) * t };
+        let from_offset = self.from.position - self.from.target;
+        let to_offset = self.to.position - self.to.target;
+        let target = self.from.target + (self.to.target - self.from.target) * t;
+        let offset = slerp(from_offset.normalize(), to_offset.normalize(), t) * lerp(from_offset.magnitude(), to_offset.magnitude());
+        CameraPose {position: target + offset, target, up: slerp(self.from.up.normalize(), self.to.up.normalize(), t),
+            // An infinite far plane cannot be interpolated, so it is kept until the end of the transition
+            z_near: lerp(self.from.z_near, self.to.z_near),
+            z_far: if self.from.z_far.is_infinite() || self.to.z_far.is_infinite() { if t < 1.0 { self.from.z_far.max(self.to.z_far) } else { self.to.z_far } } else { lerp(self.from.z_far, self.to.z_far) },
+            orthographic_height: lerp(self.from.orthographic_height, self.to.orthographic_height)}
+    }
+
+    pub fn is_finished(&self) -> bool
+    {
+        self.time >= self.duration
+    }
+}
+
 pub struct Camera {
     position: Vec3,
     target: Vec3,
@@ -10,9 +122,13 @@ pub struct Camera {
     z_far: f32,
     view: Mat4,
     projection: Mat4,
-    screen2ray: Mat4,
+    depth_range: DepthRange,
     matrix_buffer: UniformBuffer,
-    frustrum: [Vec4; 6]
+    frustrum: [Vec4; 6],
+    previous_view_projection: Option<Mat4>,
+    viewport: Option<Viewport>,
+    origin: Vec3d,
+    previous_origin: Vec3d
 }
 
 impl Camera
@@ -21,7 +137,8 @@ impl Camera
     {
         Camera {matrix_buffer: UniformBuffer::new(gl, &vec![16, 16, 16, 3, 1]).unwrap(), frustrum: [vec4(0.0, 0.0, 0.0, 0.0); 6], fov: degrees(0.0), z_near: 0.0, z_far: 0.0,
             position: vec3(0.0, 0.0, 5.0), target: vec3(0.0, 0.0, 0.0), up: vec3(0.0, 1.0, 0.0),
-            view: Mat4::identity(), projection: Mat4::identity(), screen2ray: Mat4::identity()}
+            view: Mat4::identity(), projection: Mat4::identity(), depth_range: DepthRange::current(), previous_view_projection: None, viewport: None,
+            origin: vec3d(0.0, 0.0, 0.0), previous_origin: vec3d(0.0, 0.0, 0.0)}
     }
 
     pub fn new_orthographic(gl: &Gl, position: Vec3, target: Vec3, up: Vec3, width: f32, height: f32, depth: f32) -> Camera
@@ -40,14 +157,18 @@ impl Camera
         camera
     }
 
+    ///
+    /// Sets a perspective projection with the given vertical field of view. The far plane can be at infinity, that is, z_far can be [f32::INFINITY],
+    /// which is especially useful together with a reversed depth, see [state::reversed_depth].
+    ///
     pub fn set_perspective_projection(&mut self, fovy: Degrees, aspect: f32, z_near: f32, z_far: f32)
     {
         if z_near < 0.0 || z_near > z_far { panic!("Wrong perspective camera parameters") };
         self.fov = fovy;
         self.z_near = z_near;
         self.z_far = z_far;
-        self.projection = perspective(fovy, aspect, z_near, z_far);
-        self.update_screen2ray();
+        self.depth_range = DepthRange::current();
+        self.projection = perspective_projection(fovy, aspect, z_near, z_far, &self.depth_range);
         self.update_matrix_buffer();
         self.update_frustrum();
     }
@@ -57,8 +178,8 @@ impl Camera
         self.fov = degrees(0.0);
         self.z_near = 0.0;
         self.z_far = depth;
-        self.projection = ortho(-0.5 * width, 0.5 * width, -0.5 * height, 0.5 * height, 0.0, depth);
-        self.update_screen2ray();
+        self.depth_range = DepthRange::current();
+        self.projection = self.depth_range.remap() * ortho(-0.5 * width, 0.5 * width, -0.5 * height, 0.5 * height, 0.0, depth);
         self.update_matrix_buffer();
         self.update_frustrum();
     }
@@ -72,6 +193,35 @@ impl Camera
         }
     }
 
+    ///
+    /// Sets the part of the screen or render target that the camera renders to, which is used when rendering several cameras into one frame,
+    /// see for example [PhongDeferredPipeline::render_cameras_to_screen](crate::PhongDeferredPipeline::render_cameras_to_screen).
+    /// The aspect ratio of the projection is updated to match the viewport, where an orthographic projection keeps its height.
+    ///
+    pub fn set_viewport(&mut self, viewport: Viewport)
+    {
+        self.viewport = Some(viewport);
+        if self.is_perspective() {
+            self.set_perspective_projection(self.fov, viewport.aspect(), self.z_near, self.z_far);
+        }
+        else {
+            let height = 2.0 / self.projection.y.y;
+            self.set_orthographic_projection(height * viewport.aspect(), height, self.z_far);
+        }
+    }
+
+    ///
+    /// The viewport set by [set_viewport](Camera::set_viewport), if any.
+    ///
+    pub fn viewport(&self) -> Option<Viewport>
+    {
+        self.viewport
+    }
+
+    ///
+    /// Sets the view, where the position and target are relative to the [origin](Camera::origin) of the camera, which is zero unless
+    /// the view has been set by [set_view_f64](Camera::set_view_f64).
+    ///
     pub fn set_view(&mut self, position: Vec3, target: Vec3, up: Vec3)
     {
         self.position = position;
@@ -79,25 +229,182 @@ impl Camera
         let dir = (target - position).normalize();
         self.up = dir.cross(up.normalize().cross(dir));
         self.view = Mat4::look_at(Point::from_vec(self.position), Point::from_vec(self.target), self.up);
-        self.update_screen2ray();
         self.update_matrix_buffer();
         self.update_frustrum();
     }
 
+    ///
+    /// Sets the view from a position and target in world space with double precision, which is needed for scenes with coordinates far from the origin,
+    /// for example in the millions for geospatial data, where single precision is only accurate to within a metre or so.
+    /// The camera origin is moved to the position, so that the camera is at the origin of the single precision render space.
+    /// Meshes with an [origin](crate::PhongForwardMesh::origin) in world space are then transformed relative to the camera on the CPU, see [render_space_transformation](Camera::render_space_transformation),
+    /// so that the precision is high close to the camera. Light positions and everything else given to the camera in single precision are in render space, see [to_render_space](Camera::to_render_space).
+    ///
+    pub fn set_view_f64(&mut self, position: Vec3d, target: Vec3d, up: Vec3)
+    {
+        self.origin = position;
+        let target = target - position;
+        self.set_view(vec3(0.0, 0.0, 0.0), vec3(target.x as f32, target.y as f32, target.z as f32), up);
+    }
+
+    ///
+    /// The origin of the render space in world space, which is where the camera was placed by the last call to [set_view_f64](Camera::set_view_f64).
+    /// The [position](Camera::position) and [target](Camera::target) of the camera are relative to this origin.
+    ///
+    pub fn origin(&self) -> &Vec3d
+    {
+        &self.origin
+    }
+
+    ///
+    /// Moves the origin of the render space without changing the position and target, which are relative to the origin.
+    ///
+    pub(crate) fn set_origin(&mut self, origin: &Vec3d)
+    {
+        self.origin = *origin;
+    }
+
+    ///
+    /// The position of the camera in world space with double precision, that is, the [origin](Camera::origin) plus the [position](Camera::position).
+    ///
+    pub fn world_position(&self) -> Vec3d
+    {
+        self.origin + self.position.cast::<f64>().unwrap()
+    }
+
+    ///
+    /// Converts a point in world space to render space, that is, relative to the [origin](Camera::origin) of the camera.
+    /// The subtraction is done in double precision, so the result is accurate close to the camera.
+    ///
+    pub fn to_render_space(&self, point: &Vec3d) -> Vec3
+    {
+        relative_position(point, &self.origin)
+    }
+
+    ///
+    /// The transformation from the local space of an object placed at the given origin in world space to render space,
+    /// that is, the translation from the origin to the camera origin computed in double precision, followed by the given transformation in single precision.
+    ///
+    pub fn render_space_transformation(&self, origin: &Vec3d, transformation: &Mat4) -> Mat4
+    {
+        Mat4::from_translation(self.to_render_space(origin)) * transformation
+    }
+
+    ///
+    /// Mirrors the camera in the plane y = 0, so that it sees the scene as reflected in that plane.
+    /// Note that the mirroring reverses the winding order of triangles.
+    ///
     pub fn mirror_in_xz_plane(&mut self)
     {
         self.view[1][0] = -self.view[1][0];
         self.view[1][1] = -self.view[1][1];
         self.view[1][2] = -self.view[1][2];
-        self.update_screen2ray();
+        self.position.y = -self.position.y;
+        self.target.y = -self.target.y;
+        self.up.y = -self.up.y;
+        self.update_matrix_buffer();
+        self.update_frustrum();
+    }
+
+    ///
+    /// Replaces the near plane of the perspective projection with the given plane (a, b, c, d) in world space, that is, everything where ax + by + cz + d < 0 is clipped.
+    /// This is for example useful for clipping away everything below a reflecting plane.
+    /// The plane is reset when the view or projection is changed.
+    ///
+    pub fn set_oblique_clip_plane(&mut self, plane: &Vec4)
+    {
+        // The near plane is replaced in the standard depth range and then mapped back to the depth range of the camera
+        let mut projection = self.depth_range.remap().invert().unwrap() * self.projection;
+        let c = self.view.invert().unwrap().transpose() * plane;
+        let q = projection.invert().unwrap() * vec4(c.x.signum(), c.y.signum(), 1.0, 1.0);
+        let c = c * (2.0 / c.dot(q));
+        projection.x.z = c.x - projection.x.w;
+        projection.y.z = c.y - projection.y.w;
+        projection.z.z = c.z - projection.z.w;
+        projection.w.z = c.w - projection.w.w;
+        self.projection = self.depth_range.remap() * projection;
         self.update_matrix_buffer();
         self.update_frustrum();
     }
 
+    ///
+    /// Copies the view and projection of the given camera.
+    ///
+    pub fn copy_from(&mut self, camera: &Camera)
+    {
+        self.position = camera.position;
+        self.target = camera.target;
+        self.up = camera.up;
+        self.fov = camera.fov;
+        self.z_near = camera.z_near;
+        self.z_far = camera.z_far;
+        self.view = camera.view;
+        self.projection = camera.projection;
+        self.depth_range = camera.depth_range;
+        self.origin = camera.origin;
+        self.update_matrix_buffer();
+        self.update_frustrum();
+    }
+
+    ///
+    /// Stores the current view and projection, so that the motion of the camera since then can be computed, for example for the motion vectors
+    /// in the G-buffer of the deferred pipeline. Call this once per frame before the camera is moved.
+    ///
+    pub fn save_previous_view_projection(&mut self)
+    {
+        self.previous_view_projection = Some(self.projection * self.view);
+        self.previous_origin = self.origin;
+    }
+
+    ///
+    /// The view projection matrix stored by [save_previous_view_projection](Camera::save_previous_view_projection) or the current view projection matrix if it has not been called.
+    /// The matrix maps from the current render space, so it also accounts for a change of the [origin](Camera::origin) since then.
+    ///
+    pub fn previous_view_projection(&self) -> Mat4
+    {
+        match self.previous_view_projection {
+            Some(view_projection) => view_projection * Mat4::from_translation(relative_position(&self.origin, &self.previous_origin)),
+            None => self.projection * self.view
+        }
+    }
+
+    ///
+    /// The direction of the view at the given screen coordinates, where (0, 0) is the top left corner and (1, 1) the bottom right corner of the screen.
+    /// For an orthographic projection, this is the same direction everywhere, see [ray_at](Camera::ray_at) for the ray including the origin.
+    ///
     pub fn view_direction_at(&self, screen_coordinates: (f64, f64)) -> Vec3
     {
-        let screen_pos = vec4(2. * screen_coordinates.0 as f32 - 1., 1. - 2. * screen_coordinates.1 as f32, 0., 1.);
-        (self.screen2ray * screen_pos).truncate().normalize()
+        let pixel = vec2(screen_coordinates.0 as f32, 1.0 - screen_coordinates.1 as f32);
+        ray_through_pixel(&(self.projection * self.view).invert().unwrap(), &Viewport::new_at_origo(1, 1), &self.depth_range, &pixel).direction
+    }
+
+    ///
+    /// Projects the point in world space to window coordinates, that is, the pixel coordinates (x, y) in the given viewport,
+    /// with (0, 0) in the lower left corner of the screen or render target like the viewport itself, and the depth z in the range [0, 1] as written to the depth buffer,
+    /// which is 1 at the near plane if the depth is reversed, see [state::reversed_depth].
+    /// Note that the y coordinate of a mouse position usually starts at the top and therefore has to be flipped.
+    ///
+    pub fn project(&self, point: &Vec3, viewport: &Viewport) -> Vec3
+    {
+        project_point(&(self.projection * self.view), viewport, &self.depth_range, point)
+    }
+
+    ///
+    /// The inverse of [project](Camera::project), that is, finds the point in world space from the pixel coordinates (x, y) in the given viewport and the depth z in the range [0, 1],
+    /// for example as read from the depth buffer with [Screen::read_depth].
+    ///
+    pub fn unproject(&self, window_coordinates: &Vec3, viewport: &Viewport) -> Vec3
+    {
+        unproject_point(&(self.projection * self.view).invert().unwrap(), viewport, &self.depth_range, window_coordinates)
+    }
+
+    ///
+    /// The ray through the given pixel coordinates in the given viewport, see [project](Camera::project) for the coordinate system.
+    /// The ray starts at the near plane, so for an orthographic projection the origin varies over the viewport while the direction is the same.
+    ///
+    pub fn ray_at(&self, pixel: &Vec2, viewport: &Viewport) -> Ray
+    {
+        ray_through_pixel(&(self.projection * self.view).invert().unwrap(), viewport, &self.depth_range, pixel)
     }
 
     pub fn get_view(&self) -> &Mat4
@@ -125,16 +432,50 @@ impl Camera
         &self.up
     }
 
-    pub fn matrix_buffer(&self) -> &UniformBuffer
+    pub fn z_near(&self) -> f32
     {
-        &self.matrix_buffer
+        self.z_near
     }
 
-    fn update_screen2ray(&mut self)
+    pub fn z_far(&self) -> f32
     {
-        let mut v = self.view.clone();
-        v[3] = vec4(0.0, 0.0, 0.0, 1.0);
-        self.screen2ray = (self.projection * v).invert().unwrap();
+        self.z_far
+    }
+
+    pub fn is_perspective(&self) -> bool
+    {
+        self.fov != degrees(0.0)
+    }
+
+    ///
+    /// Whether the projection maps the near plane to depth 1 and the far plane to depth 0, which is decided by [state::reversed_depth] when the projection is set.
+    ///
+    pub fn is_depth_reversed(&self) -> bool
+    {
+        self.depth_range.reversed
+    }
+
+    ///
+    /// The inverse of the view projection matrix, which maps the point (x, y, 2 * depth - 1) in normalized device coordinates back to world space,
+    /// where depth is the value in the depth buffer. This also takes a clip space depth range of [0, 1] into account, see [state::reversed_depth].
+    ///
+    pub fn view_projection_inverse(&self) -> Mat4
+    {
+        let depth_to_clip_space = if self.depth_range.zero_to_one { depth_remap(0.5, 0.5) } else { Mat4::identity() };
+        (self.projection * self.view).invert().unwrap() * depth_to_clip_space
+    }
+
+    ///
+    /// The depth in clip space divided by w at the far plane, that is, 1, or 0 or -1 if the depth is reversed.
+    ///
+    pub(crate) fn far_clip_depth(&self) -> f32
+    {
+        self.depth_range.far_clip_depth()
+    }
+
+    pub fn matrix_buffer(&self) -> &UniformBuffer
+    {
+        &self.matrix_buffer
     }
 
     fn update_matrix_buffer(&mut self)
@@ -143,6 +484,7 @@ impl Camera
         self.matrix_buffer.update(1, &self.view.to_slice()).unwrap();
         self.matrix_buffer.update(2, &self.projection.to_slice()).unwrap();
         self.matrix_buffer.update(3, &self.position.to_slice()).unwrap();
+        self.matrix_buffer.update(4, &[self.far_clip_depth()]).unwrap();
     }
 
     fn update_frustrum(&mut self)
@@ -152,7 +494,8 @@ impl Camera
          vec4(m.x.w - m.x.x, m.y.w - m.y.x, m.z.w - m.z.x, m.w.w - m.w.x),
          vec4(m.x.w + m.x.y, m.y.w + m.y.y,m.z.w + m.z.y, m.w.w + m.w.y),
          vec4(m.x.w - m.x.y, m.y.w - m.y.y,m.z.w - m.z.y, m.w.w - m.w.y),
-         vec4(m.x.w + m.x.z,m.y.w + m.y.z,m.z.w + m.z.z, m.w.w + m.w.z),
+         // With a clip space depth range of [0, 1], one of the near and far planes is at z = 0 instead of z = -w
+         if self.depth_range.zero_to_one { vec4(m.x.z, m.y.z, m.z.z, m.w.z) } else { vec4(m.x.w + m.x.z,m.y.w + m.y.z,m.z.w + m.z.z, m.w.w + m.w.z) },
          vec4(m.x.w - m.x.z,m.y.w - m.y.z,m.z.w - m.z.z, m.w.w - m.w.z)];
     }
 
@@ -178,6 +521,55 @@ impl Camera
         return true;
     }
 
+    ///
+    /// The current pose of the camera, see [CameraPose].
+    ///
+    pub fn pose(&self) -> CameraPose
+    {
+        CameraPose {position: self.position, target: self.target, up: self.up, z_near: self.z_near, z_far: self.z_far,
+            orthographic_height: if self.is_perspective() { 0.0 } else { 2.0 / self.projection.y.y }}
+    }
+
+    ///
+    /// Sets the view, the near and far planes and, for an orthographic camera, the height of the view from the given pose.
+    /// The field of view and the aspect ratio are unchanged.
+    ///
+    pub fn set_pose(&mut self, pose: &CameraPose)
+    {
+        let aspect = self.aspect();
+        self.set_view(pose.position, pose.target, pose.up);
+        if self.is_perspective() {
+            self.set_perspective_projection(self.fov, aspect, pose.z_near, pose.z_far);
+        }
+        else {
+            self.set_orthographic_projection(pose.orthographic_height * aspect, pose.orthographic_height, pose.z_far);
+        }
+    }
+
+    ///
+    /// The pose where the camera looks at the center of the given box in the current view direction and from a distance where the whole box is visible,
+    /// with the near and far planes as close to the box as possible. Use [fit_to](Camera::fit_to) to move the camera immediately
+    /// or a [CameraTransition] to move it smoothly.
+    ///
+    pub fn pose_fitted_to(&self, aabb: &AxisAlignedBoundingBox) -> CameraPose
+    {
+        fit_pose(&self.pose(), if self.is_perspective() { Some(self.fov) } else { None }, self.aspect(), aabb)
+    }
+
+    ///
+    /// Moves the camera so that the whole box is visible, see [pose_fitted_to](Camera::pose_fitted_to).
+    ///
+    pub fn fit_to(&mut self, aabb: &AxisAlignedBoundingBox)
+    {
+        let pose = self.pose_fitted_to(aabb);
+        self.set_pose(&pose);
+    }
+
+    fn aspect(&self) -> f32
+    {
+        self.projection.y.y / self.projection.x.x
+    }
+
     pub fn translate(&mut self, change: &Vec3)
     {
         self.set_view(*self.position() + change, *self.target() + change, *self.up());
@@ -209,4 +601,346 @@ impl Camera
         position = target + (*self.position() - *self.target()).normalize() * zoom;
         self.set_view(position, target, up);
     }
-}
\ No newline at end of file
+}
+
+// The mapping of the depth used by a projection, which is decided by the global state when the projection is set
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct DepthRange {
+    reversed: bool,
+    zero_to_one: bool
+}
+
+impl DepthRange {
+    fn current() -> Self
+    {
+        Self {reversed: state::is_depth_reversed(), zero_to_one: state::is_clip_depth_zero_to_one()}
+    }
+
+    // Maps the standard clip space depth, where the near plane is at -1 and the far plane at 1, to this depth range
+    fn remap(&self) -> Mat4
+    {
+        match (self.reversed, self.zero_to_one) {
+            (true, true) => depth_remap(-0.5, 0.5),
+            (true, false) => depth_remap(-1.0, 0.0),
+            _ => Mat4::identity()
+        }
+    }
+
+    fn far_clip_depth(&self) -> f32
+    {
+        match (self.reversed, self.zero_to_one) {
+            (true, true) => 0.0,
+            (true, false) => -1.0,
+            _ => 1.0
+        }
+    }
+
+    fn near_window_depth(&self) -> f32
+    {
+        if self.reversed { 1.0 } else { 0.0 }
+    }
+
+    fn window_depth(&self, clip_depth: f32) -> f32
+    {
+        if self.zero_to_one { clip_depth } else { 0.5 * (clip_depth + 1.0) }
+    }
+
+    fn clip_depth(&self, window_depth: f32) -> f32
+    {
+        if self.zero_to_one { window_depth } else { 2.0 * window_depth - 1.0 }
+    }
+}
+
+// The matrix which maps the depth z in clip space to a * z + b * w
+fn depth_remap(a: f32, b: f32) -> Mat4
+{
+    Mat4::new(1.0, 0.0, 0.0, 0.0,
+              0.0, 1.0, 0.0, 0.0,
+              0.0, 0.0, a, 0.0,
+              0.0, 0.0, b, 1.0)
+}
+
+// The depth is computed directly for each depth range, instead of remapping the standard projection, to avoid cancellation when the far plane is far away
+fn perspective_projection(fovy: Degrees, aspect: f32, z_near: f32, z_far: f32, depth_range: &DepthRange) -> Mat4
+{
+    let f = 1.0 / (0.5 * Radians::from(fovy).0).tan();
+    let (a, b) = match (depth_range.reversed, depth_range.zero_to_one, z_far.is_infinite()) {
+        (false, _, false) => ((z_far + z_near) / (z_near - z_far), 2.0 * z_far * z_near / (z_near - z_far)),
+        (false, _, true) => (-1.0, -2.0 * z_near),
+        (true, false, false) => ((z_far + z_near) / (z_far - z_near), 2.0 * z_far * z_near / (z_far - z_near)),
+        (true, false, true) => (1.0, 2.0 * z_near),
+        (true, true, false) => (z_near / (z_far - z_near), z_far * z_near / (z_far - z_near)),
+        (true, true, true) => (0.0, z_near)
+    };
+    Mat4::new(f / aspect, 0.0, 0.0, 0.0,
+              0.0, f, 0.0, 0.0,
+              0.0, 0.0, a, -1.0,
+              0.0, 0.0, b, 0.0)
+}
+
+// The position of the point relative to the origin, which is subtracted in double precision before converting to single precision
+fn relative_position(point: &Vec3d, origin: &Vec3d) -> Vec3
+{
+    (point - origin).cast::<f32>().unwrap()
+}
+
+fn project_point(view_projection: &Mat4, viewport: &Viewport, depth_range: &DepthRange, point: &Vec3) -> Vec3
+{
+    let clip_position = view_projection * point.extend(1.0);
+    let ndc = clip_position.truncate() / clip_position.w;
+    vec3(viewport.x as f32 + 0.5 * (ndc.x + 1.0) * viewport.width as f32,
+         viewport.y as f32 + 0.5 * (ndc.y + 1.0) * viewport.height as f32,
+         depth_range.window_depth(ndc.z))
+}
+
+fn unproject_point(view_projection_inverse: &Mat4, viewport: &Viewport, depth_range: &DepthRange, window_coordinates: &Vec3) -> Vec3
+{
+    let ndc = vec4(2.0 * (window_coordinates.x - viewport.x as f32) / viewport.width as f32 - 1.0,
+                   2.0 * (window_coordinates.y - viewport.y as f32) / viewport.height as f32 - 1.0,
+                   depth_range.clip_depth(window_coordinates.z), 1.0);
+    let position = view_projection_inverse * ndc;
+    position.truncate() / position.w
+}
+
+fn ray_through_pixel(view_projection_inverse: &Mat4, viewport: &Viewport, depth_range: &DepthRange, pixel: &Vec2) -> Ray
+{
+    let origin = unproject_point(view_projection_inverse, viewport, depth_range, &pixel.extend(depth_range.near_window_depth()));
+    // A point halfway into the depth range, rather than on the far plane, also works when the far plane is at infinity
+    let other = unproject_point(view_projection_inverse, viewport, depth_range, &pixel.extend(0.5));
+    Ray {origin, direction: (other - origin).normalize()}
+}
+
+// Fits the bounding sphere of the box, which is independent of the view direction, and keeps a small margin to the near and far planes
+fn fit_pose(pose: &CameraPose, fovy: Option<Degrees>, aspect: f32, aabb: &AxisAlignedBoundingBox) -> CameraPose
+{
+    let center = 0.5 * (aabb.min + aabb.max);
+    let radius = 0.5 * (aabb.max - aabb.min).magnitude();
+    if !radius.is_finite() || !center.x.is_finite() || !center.y.is_finite() || !center.z.is_finite() {
+        // The box is empty
+        return *pose;
+    }
+    let radius = radius.max(0.001);
+    let direction = (pose.target - pose.position).normalize();
+    match fovy {
+        Some(fovy) => {
+            let half_fov_y = 0.5 * Radians::from(fovy).0;
+            let half_fov_x = (half_fov_y.tan() * aspect).atan();
+            let distance = radius / half_fov_y.min(half_fov_x).sin();
+            CameraPose {position: center - direction * distance, target: center, up: pose.up,
+                z_near: 0.99 * (distance - radius), z_far: if pose.z_far.is_infinite() { pose.z_far } else { 1.01 * (distance + radius) },
+                orthographic_height: pose.orthographic_height}
+        },
+        None => {
+            // The near plane of an orthographic camera is at the camera position
+            let distance = 1.01 * radius;
+            CameraPose {position: center - direction * distance, target: center, up: pose.up,
+                z_near: 0.0, z_far: distance + 1.01 * radius, orthographic_height: 2.0 * radius * (1.0 / aspect).max(1.0)}
+        }
+    }
+}
+
+// Spherical interpolation between the unit vectors a and b
+fn slerp(a: Vec3, b: Vec3, t: f32) -> Vec3
+{
+    let cos_angle = a.dot(b).clamp(-1.0, 1.0);
+    if cos_angle > 0.9999 {
+        return (a + (b - a) * t).normalize();
+    }
+    let perpendicular = if cos_angle < -0.9999 {
+        // Opposite directions, so any perpendicular direction can be used
+        a.cross(if a.x.abs() < 0.9 { vec3(1.0, 0.0, 0.0) } else { vec3(0.0, 1.0, 0.0) }).normalize()
+    } else {
+        (b - a * cos_angle).normalize()
+    };
+    let angle = cos_angle.acos() * t;
+    a * angle.cos() + perpendicular * angle.sin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 0.001;
+    const STANDARD: DepthRange = DepthRange {reversed: false, zero_to_one: false};
+    const REVERSED: [DepthRange; 2] = [DepthRange {reversed: true, zero_to_one: false}, DepthRange {reversed: true, zero_to_one: true}];
+
+    fn assert_close(a: Vec3, b: Vec3)
+    {
+        assert!((a - b).magnitude() < EPSILON, "{:?} is not close to {:?}", a, b);
+    }
+
+    fn perspective_view_projection() -> Mat4
+    {
+        perspective(degrees(60.0), 2.0, 0.1, 100.0) * Mat4::look_at(Point::new(1.0, 2.0, 5.0), Point::new(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0))
+    }
+
+    fn orthographic_view_projection() -> Mat4
+    {
+        ortho(-4.0, 4.0, -2.0, 2.0, 0.0, 20.0) * Mat4::look_at(Point::new(0.0, 0.0, 10.0), Point::new(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0))
+    }
+
+    #[test]
+    fn project_target_to_viewport_center()
+    {
+        let viewport = Viewport {x: 100, y: 50, width: 800, height: 400};
+        for view_projection in &[perspective_view_projection(), orthographic_view_projection()] {
+            let window_coordinates = project_point(view_projection, &viewport, &STANDARD, &vec3(0.0, 0.0, 0.0));
+            assert!((window_coordinates.x - 500.0).abs() < EPSILON);
+            assert!((window_coordinates.y - 250.0).abs() < EPSILON);
+            assert!(window_coordinates.z > 0.0 && window_coordinates.z < 1.0);
+        }
+    }
+
+    #[test]
+    fn unproject_is_inverse_of_project()
+    {
+        let viewport = Viewport {x: 0, y: 0, width: 640, height: 320};
+        for view_projection in &[perspective_view_projection(), orthographic_view_projection()] {
+            for point in &[vec3(0.5, -0.3, 1.0), vec3(-1.0, 0.7, -2.0)] {
+                let window_coordinates = project_point(view_projection, &viewport, &STANDARD, point);
+                assert_close(unproject_point(&view_projection.invert().unwrap(), &viewport, &STANDARD, &window_coordinates), *point);
+            }
+        }
+    }
+
+    #[test]
+    fn perspective_rays_pass_through_eye()
+    {
+        let viewport = Viewport::new_at_origo(640, 320);
+        let eye = vec3(1.0, 2.0, 5.0);
+        for pixel in &[vec2(0.0, 0.0), vec2(320.0, 160.0), vec2(600.0, 10.0)] {
+            let ray = ray_through_pixel(&perspective_view_projection().invert().unwrap(), &viewport, &STANDARD, pixel);
+            let to_eye = eye - ray.origin;
+            assert_close(to_eye.normalize(), -ray.direction);
+        }
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel_with_different_origins()
+    {
+        let viewport = Viewport::new_at_origo(800, 400);
+        let view_projection_inverse = orthographic_view_projection().invert().unwrap();
+        let center = ray_through_pixel(&view_projection_inverse, &viewport, &STANDARD, &vec2(400.0, 200.0));
+        let corner = ray_through_pixel(&view_projection_inverse, &viewport, &STANDARD, &vec2(800.0, 400.0));
+        assert_close(center.direction, vec3(0.0, 0.0, -1.0));
+        assert_close(corner.direction, vec3(0.0, 0.0, -1.0));
+        assert_close(center.origin, vec3(0.0, 0.0, 10.0));
+        assert_close(corner.origin, vec3(4.0, 2.0, 10.0));
+        assert_close(corner.at(10.0), vec3(4.0, 2.0, 0.0));
+    }
+
+    fn box_corners(aabb: &AxisAlignedBoundingBox) -> Vec<Vec3>
+    {
+        (0..8).map(|i| vec3(if i & 1 == 0 { aabb.min.x } else { aabb.max.x },
+                            if i & 2 == 0 { aabb.min.y } else { aabb.max.y },
+                            if i & 4 == 0 { aabb.min.z } else { aabb.max.z })).collect()
+    }
+
+    fn start_pose() -> CameraPose
+    {
+        CameraPose {position: vec3(3.0, 1.0, 4.0), target: vec3(0.0, 0.5, 0.0), up: vec3(0.0, 1.0, 0.0), z_near: 0.1, z_far: 10.0, orthographic_height: 2.0}
+    }
+
+    #[test]
+    fn fitted_box_is_inside_frustum()
+    {
+        let aabb = AxisAlignedBoundingBox {min: vec3(10.0, -2.0, 5.0), max: vec3(30.0, 4.0, 7.0)};
+        let fovy = degrees(45.0);
+        for &aspect in &[0.5, 1.0, 2.0] {
+            let perspective_pose = fit_pose(&start_pose(), Some(fovy), aspect, &aabb);
+            let orthographic_pose = fit_pose(&start_pose(), None, aspect, &aabb);
+            let height = orthographic_pose.orthographic_height;
+            let view_projections = [
+                perspective(fovy, aspect, perspective_pose.z_near, perspective_pose.z_far)
+                    * Mat4::look_at(Point::from_vec(perspective_pose.position), Point::from_vec(perspective_pose.target), perspective_pose.up),
+                ortho(-0.5 * height * aspect, 0.5 * height * aspect, -0.5 * height, 0.5 * height, 0.0, orthographic_pose.z_far)
+                    * Mat4::look_at(Point::from_vec(orthographic_pose.position), Point::from_vec(orthographic_pose.target), orthographic_pose.up)];
+            for view_projection in &view_projections {
+                for corner in box_corners(&aabb) {
+                    let window_coordinates = project_point(view_projection, &Viewport::new_at_origo(1, 1), &STANDARD, &corner);
+                    assert!(window_coordinates.x >= 0.0 && window_coordinates.x <= 1.0, "{:?}", window_coordinates);
+                    assert!(window_coordinates.y >= 0.0 && window_coordinates.y <= 1.0, "{:?}", window_coordinates);
+                    assert!(window_coordinates.z >= 0.0 && window_coordinates.z <= 1.0, "{:?}", window_coordinates);
+                }
+            }
+            let view_direction = (start_pose().target - start_pose().position).normalize();
+            assert_close((perspective_pose.target - perspective_pose.position).normalize(), view_direction);
+            assert_close((orthographic_pose.target - orthographic_pose.position).normalize(), view_direction);
+        }
+    }
+
+    #[test]
+    fn transition_goes_from_start_to_end_pose()
+    {
+        let to = CameraPose {position: vec3(-5.0, 2.0, -1.0), target: vec3(1.0, 1.0, 1.0), up: vec3(0.0, 1.0, 0.0), z_near: 1.0, z_far: 100.0, orthographic_height: 5.0};
+        let mut transition = CameraTransition::new(start_pose(), to, 1000.0);
+        assert_close(transition.pose().position, start_pose().position);
+        transition.time = 500.0;
+        let halfway = transition.pose();
+        let distance = (halfway.position - halfway.target).magnitude();
+        let from_distance = (start_pose().position - start_pose().target).magnitude();
+        let to_distance = (to.position - to.target).magnitude();
+        assert!((distance - 0.5 * (from_distance + to_distance)).abs() < EPSILON);
+        transition.time = 1000.0;
+        assert!(transition.is_finished());
+        assert_close(transition.pose().position, to.position);
+        assert_close(transition.pose().target, to.target);
+        assert!((transition.pose().z_far - to.z_far).abs() < EPSILON);
+    }
+
+    #[test]
+    fn standard_perspective_matches_cgmath()
+    {
+        let projection = perspective_projection(degrees(60.0), 1.5, 0.1, 100.0, &STANDARD);
+        let expected = perspective(degrees(60.0), 1.5, 0.1, 100.0);
+        for i in 0..4 {
+            assert!((projection[i] - expected[i]).magnitude() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn reversed_depth_is_one_at_near_plane_and_zero_at_far_plane()
+    {
+        let viewport = Viewport::new_at_origo(100, 100);
+        let view = Mat4::look_at(Point::new(0.0, 0.0, 0.0), Point::new(0.0, 0.0, -1.0), vec3(0.0, 1.0, 0.0));
+        for depth_range in &REVERSED {
+            let view_projection = perspective_projection(degrees(45.0), 1.0, 0.5, 1000.0, depth_range) * view;
+            assert!((project_point(&view_projection, &viewport, depth_range, &vec3(0.0, 0.0, -0.5)).z - 1.0).abs() < EPSILON);
+            assert!(project_point(&view_projection, &viewport, depth_range, &vec3(0.0, 0.0, -1000.0)).z.abs() < EPSILON);
+            let point = vec3(3.0, -2.0, -50.0);
+            let window_coordinates = project_point(&view_projection, &viewport, depth_range, &point);
+            assert_close(unproject_point(&view_projection.invert().unwrap(), &viewport, depth_range, &window_coordinates), point);
+        }
+    }
+
+    #[test]
+    fn infinite_far_plane()
+    {
+        let viewport = Viewport::new_at_origo(100, 100);
+        let view = Mat4::look_at(Point::new(0.0, 0.0, 0.0), Point::new(0.0, 0.0, -1.0), vec3(0.0, 1.0, 0.0));
+        for depth_range in [STANDARD].iter().chain(REVERSED.iter()) {
+            let view_projection = perspective_projection(degrees(45.0), 1.0, 0.5, f32::INFINITY, depth_range) * view;
+            let mut previous_depth = depth_range.near_window_depth();
+            for &distance in &[1.0, 10.0, 1000.0, 100000.0] {
+                let depth = project_point(&view_projection, &viewport, depth_range, &vec3(0.0, 0.0, -distance)).z;
+                assert!((0.0..=1.0).contains(&depth));
+                assert!(if depth_range.reversed { depth < previous_depth } else { depth > previous_depth });
+                previous_depth = depth;
+            }
+            let ray = ray_through_pixel(&view_projection.invert().unwrap(), &viewport, depth_range, &vec2(50.0, 50.0));
+            assert_close(ray.origin, vec3(0.0, 0.0, -0.5));
+            assert_close(ray.direction, vec3(0.0, 0.0, -1.0));
+        }
+    }
+
+    #[test]
+    fn relative_position_is_accurate_far_from_origin()
+    {
+        let origin = vec3d(6378137.0, 1234567.0, -2345678.0);
+        let point = origin + vec3d(0.3, -0.3, 0.3);
+        assert_close(relative_position(&point, &origin), vec3(0.3, -0.3, 0.3));
+        // The same subtraction in single precision is off by more than a decimetre
+        let single = point.cast::<f32>().unwrap() - origin.cast::<f32>().unwrap();
+        assert!((single - vec3(0.3, -0.3, 0.3)).magnitude() > 0.1);
+    }
+}
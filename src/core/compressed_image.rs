@@ -0,0 +1,522 @@
+use crate::core::Error;
+use crate::gl::Gl;
+use crate::texture::Format;
+use crate::Image;
+
+///
+/// An image in a block compressed GPU format together with its mip chain, for example loaded from a KTX2 or DDS file with
+/// [Loader::get_compressed_image](struct.Loader.html#method.get_compressed_image).
+/// All the supported formats consists of blocks of 4x4 pixels and the rows are in the same order as for [Image](struct.Image.html).
+///
+pub struct CompressedImage {
+    pub format: Format,
+    pub width: u32,
+    pub height: u32,
+    /// The data of each mip level, starting with the full size image
+    pub levels: Vec<Vec<u8>>
+}
+
+impl CompressedImage
+{
+    ///
+    /// Returns the first image in the list whose format is supported by the GPU, which makes it possible to ship the same texture in several formats
+    /// (for example S3TC for desktop and ETC2 or ASTC for mobile) and pick one at runtime.
+    ///
+    pub fn choose_supported<'a>(gl: &Gl, images: &'a [CompressedImage]) -> Option<&'a CompressedImage>
+    {
+        images.iter().find(|image| image.is_supported(gl))
+    }
+
+    ///
+    /// Returns whether the GPU can sample the format directly, otherwise the image has to be decoded on the CPU,
+    /// which [Texture2D::new_with_compressed](struct.Texture2D.html#method.new_with_compressed) does automatically.
+    ///
+    pub fn is_supported(&self, gl: &Gl) -> bool
+    {
+        is_format_supported(gl, self.format)
+    }
+
+    pub fn is_srgb(&self) -> bool
+    {
+        matches!(self.format, Format::SRGBDXT1 | Format::SRGBAlphaDXT1 | Format::SRGBAlphaDXT3 | Format::SRGBAlphaDXT5 |
+            Format::SRGB8ETC2 | Format::SRGB8A1ETC2 | Format::SRGB8Alpha8ETC2 | Format::SRGB8Alpha8ASTC4x4)
+    }
+
+    ///
+    /// Decodes the given mip level to an image with four 8 bit channels.
+    /// The S3TC (DXT1, DXT3 and DXT5) and ETC2 formats can be decoded, ASTC cannot.
+    ///
+    pub fn decode(&self, level: usize) -> Result<Image, Error>
+    {
+        let (width, height) = self.level_size(level)?;
+        let data = &self.levels[level];
+        let block_size = block_size_in_bytes(self.format)?;
+        let blocks_x = (width as usize).div_ceil(4);
+        let blocks_y = (height as usize).div_ceil(4);
+        if data.len() != blocks_x * blocks_y * block_size {
+            Err(Error::FailedToCreateTexture {message: format!("Wrong size of data for mip level {} ({} != {})", level, data.len(), blocks_x * blocks_y * block_size)})?
+        }
+
+        let mut bytes = vec![0u8; width as usize * height as usize * 4];
+        for (block_index, block) in data.chunks(block_size).enumerate() {
+            let pixels = decode_block(self.format, block)?;
+            let (bx, by) = (block_index % blocks_x * 4, block_index / blocks_x * 4);
+            for y in 0..4 {
+                for x in 0..4 {
+                    if bx + x < width as usize && by + y < height as usize {
+                        let i = ((by + y) * width as usize + bx + x) * 4;
+                        bytes[i..i + 4].copy_from_slice(&pixels[y * 4 + x]);
+                    }
+                }
+            }
+        }
+        Ok(Image {bytes, width, height})
+    }
+
+    ///
+    /// Returns the width and height of the given mip level.
+    ///
+    pub fn level_size(&self, level: usize) -> Result<(u32, u32), Error>
+    {
+        if level >= self.levels.len() {
+            Err(Error::FailedToCreateTexture {message: format!("The image has {} mip levels, not {}", self.levels.len(), level + 1)})?
+        }
+        // Shifting by 32 or more bits overflows, but all levels from 32 and up are 1x1 anyway
+        let shift = level.min(31) as u32;
+        Ok(((self.width >> shift).max(1), (self.height >> shift).max(1)))
+    }
+}
+
+///
+/// Returns the size in bytes of a 4x4 block of the compressed format.
+///
+pub(crate) fn block_size_in_bytes(format: Format) -> Result<usize, Error>
+{
+    match format {
+        Format::RGBDXT1 | Format::RGBADXT1 | Format::SRGBDXT1 | Format::SRGBAlphaDXT1 |
+        Format::RGB8ETC2 | Format::SRGB8ETC2 | Format::RGB8A1ETC2 | Format::SRGB8A1ETC2 => Ok(8),
+        Format::RGBADXT3 | Format::RGBADXT5 | Format::SRGBAlphaDXT3 | Format::SRGBAlphaDXT5 |
+        Format::RGBA8ETC2 | Format::SRGB8Alpha8ETC2 | Format::RGBAASTC4x4 | Format::SRGB8Alpha8ASTC4x4 => Ok(16),
+        _ => Err(Error::FailedToCreateTexture {message: format!("The format {:?} is not a compressed format", format)})
+    }
+}
+
+// The extension names are the WebGL names on web and the names without the GL_ prefix on desktop
+#[cfg(not(target_arch = "wasm32"))]
+fn is_format_supported(gl: &Gl, format: Format) -> bool
+{
+    match format {
+        Format::RGBDXT1 | Format::RGBADXT1 | Format::RGBADXT3 | Format::RGBADXT5 => gl.supports_extension("EXT_texture_compression_s3tc"),
+        Format::SRGBDXT1 | Format::SRGBAlphaDXT1 | Format::SRGBAlphaDXT3 | Format::SRGBAlphaDXT5 =>
+            gl.supports_extension("EXT_texture_compression_s3tc") && gl.supports_extension("EXT_texture_sRGB"),
+        Format::RGB8ETC2 | Format::SRGB8ETC2 | Format::RGB8A1ETC2 | Format::SRGB8A1ETC2 | Format::RGBA8ETC2 | Format::SRGB8Alpha8ETC2 =>
+            gl.supports_extension("ARB_ES3_compatibility"),
+        Format::RGBAASTC4x4 | Format::SRGB8Alpha8ASTC4x4 => gl.supports_extension("KHR_texture_compression_astc_ldr"),
+        _ => true
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn is_format_supported(gl: &Gl, format: Format) -> bool
+{
+    match format {
+        Format::RGBDXT1 | Format::RGBADXT1 | Format::RGBADXT3 | Format::RGBADXT5 => gl.supports_extension("WEBGL_compressed_texture_s3tc"),
+        Format::SRGBDXT1 | Format::SRGBAlphaDXT1 | Format::SRGBAlphaDXT3 | Format::SRGBAlphaDXT5 => gl.supports_extension("WEBGL_compressed_texture_s3tc_srgb"),
+        Format::RGB8ETC2 | Format::SRGB8ETC2 | Format::RGB8A1ETC2 | Format::SRGB8A1ETC2 | Format::RGBA8ETC2 | Format::SRGB8Alpha8ETC2 =>
+            gl.supports_extension("WEBGL_compressed_texture_etc"),
+        Format::RGBAASTC4x4 | Format::SRGB8Alpha8ASTC4x4 => gl.supports_extension("WEBGL_compressed_texture_astc"),
+        _ => true
+    }
+}
+
+// Decodes a 4x4 block to RGBA pixels in row order
+fn decode_block(format: Format, block: &[u8]) -> Result<[[u8; 4]; 16], Error>
+{
+    Ok(match format {
+        Format::RGBDXT1 | Format::SRGBDXT1 => decode_bc1(block, false, false),
+        Format::RGBADXT1 | Format::SRGBAlphaDXT1 => decode_bc1(block, true, false),
+        Format::RGBADXT3 | Format::SRGBAlphaDXT3 => {
+            let mut pixels = decode_bc1(&block[8..16], false, true);
+            let alpha = u64::from_le_bytes([block[0], block[1], block[2], block[3], block[4], block[5], block[6], block[7]]);
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                pixel[3] = ((alpha >> (4 * i)) & 15) as u8 * 17;
+            }
+            pixels
+        },
+        Format::RGBADXT5 | Format::SRGBAlphaDXT5 => {
+            let mut pixels = decode_bc1(&block[8..16], false, true);
+            let alphas = bc3_alphas(block[0], block[1]);
+            let indices = block[2..8].iter().rev().fold(0u64, |bits, byte| (bits << 8) | *byte as u64);
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                pixel[3] = alphas[((indices >> (3 * i)) & 7) as usize];
+            }
+            pixels
+        },
+        Format::RGB8ETC2 | Format::SRGB8ETC2 => decode_etc2(block, false),
+        Format::RGB8A1ETC2 | Format::SRGB8A1ETC2 => decode_etc2(block, true),
+        Format::RGBA8ETC2 | Format::SRGB8Alpha8ETC2 => {
+            let mut pixels = decode_etc2(&block[8..16], false);
+            let alphas = decode_eac(&block[0..8]);
+            for (pixel, alpha) in pixels.iter_mut().zip(alphas.iter()) {
+                pixel[3] = *alpha;
+            }
+            pixels
+        },
+        _ => Err(Error::FailedToCreateTexture {message: format!("Decoding the format {:?} on the CPU is not supported", format)})?
+    })
+}
+
+fn rgb565(color: u16) -> [i32; 3]
+{
+    let r = ((color >> 11) & 31) as i32;
+    let g = ((color >> 5) & 63) as i32;
+    let b = (color & 31) as i32;
+    [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2)]
+}
+
+// Decodes the color part of a S3TC block. The color block of DXT3 and DXT5 always uses four colors.
+fn decode_bc1(block: &[u8], has_alpha: bool, always_four_colors: bool) -> [[u8; 4]; 16]
+{
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let (a, b) = (rgb565(c0), rgb565(c1));
+    let mix = |wa: i32, wb: i32| -> [u8; 4] {
+        let mut color = [0, 0, 0, 255];
+        for i in 0..3 {
+            color[i] = ((wa * a[i] + wb * b[i]) / (wa + wb)) as u8;
+        }
+        color
+    };
+    let colors = if c0 > c1 || always_four_colors {
+        [mix(1, 0), mix(0, 1), mix(2, 1), mix(1, 2)]
+    } else {
+        [mix(1, 0), mix(0, 1), mix(1, 1), if has_alpha {[0, 0, 0, 0]} else {[0, 0, 0, 255]}]
+    };
+
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    let mut pixels = [[0u8; 4]; 16];
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        *pixel = colors[((indices >> (2 * i)) & 3) as usize];
+    }
+    pixels
+}
+
+fn bc3_alphas(a0: u8, a1: u8) -> [u8; 8]
+{
+    let (a0, a1) = (a0 as usize, a1 as usize);
+    let mut alphas = [a0 as u8, a1 as u8, 0, 0, 0, 0, 0, 255];
+    if a0 > a1 {
+        for i in 1..7 {
+            alphas[i + 1] = (((7 - i) * a0 + i * a1) / 7) as u8;
+        }
+    } else {
+        for i in 1..5 {
+            alphas[i + 1] = (((5 - i) * a0 + i * a1) / 5) as u8;
+        }
+    }
+    alphas
+}
+
+const ETC_MODIFIERS: [[i32; 2]; 8] = [[2, 8], [5, 17], [9, 29], [13, 42], [18, 60], [24, 80], [33, 106], [47, 183]];
+const ETC_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+fn bits(block: u64, high: u32, low: u32) -> i32
+{
+    ((block >> low) & ((1 << (high - low + 1)) - 1)) as i32
+}
+
+fn extend_4(value: i32) -> i32 { (value << 4) | value }
+fn extend_5(value: i32) -> i32 { (value << 3) | (value >> 2) }
+fn extend_6(value: i32) -> i32 { (value << 2) | (value >> 4) }
+fn extend_7(value: i32) -> i32 { (value << 1) | (value >> 6) }
+
+fn to_color(rgb: [i32; 3]) -> [u8; 4]
+{
+    [rgb[0].clamp(0, 255) as u8, rgb[1].clamp(0, 255) as u8, rgb[2].clamp(0, 255) as u8, 255]
+}
+
+fn offset(rgb: [i32; 3], d: i32) -> [u8; 4]
+{
+    to_color([rgb[0] + d, rgb[1] + d, rgb[2] + d])
+}
+
+// Decodes an ETC2 RGB block, or an ETC2 RGB block with punch-through alpha where the differential bit is used as an opaque bit.
+// The pixel indices are stored column by column, while the returned pixels are in row order.
+fn decode_etc2(block: &[u8], punch_through: bool) -> [[u8; 4]; 16]
+{
+    let block = u64::from_be_bytes([block[0], block[1], block[2], block[3], block[4], block[5], block[6], block[7]]);
+    let differential = bits(block, 33, 33) == 1;
+    let opaque = !punch_through || differential;
+    let index = |x: usize, y: usize| -> usize {
+        let p = (x * 4 + y) as u32;
+        (((block >> (p + 16)) & 1) << 1 | ((block >> p) & 1)) as usize
+    };
+    let transparent = |x: usize, y: usize| !opaque && index(x, y) == 2;
+
+    let mut pixels = [[0u8; 4]; 16];
+    if punch_through || differential {
+        let r = bits(block, 63, 59) + ((bits(block, 58, 56) << 29) >> 29);
+        let g = bits(block, 55, 51) + ((bits(block, 50, 48) << 29) >> 29);
+        let b = bits(block, 47, 43) + ((bits(block, 42, 40) << 29) >> 29);
+        if !(0..32).contains(&r) {
+            // T mode
+            let c1 = [extend_4(bits(block, 60, 59) << 2 | bits(block, 57, 56)), extend_4(bits(block, 55, 52)), extend_4(bits(block, 51, 48))];
+            let c2 = [extend_4(bits(block, 47, 44)), extend_4(bits(block, 43, 40)), extend_4(bits(block, 39, 36))];
+            let d = ETC_DISTANCES[(bits(block, 35, 34) << 1 | bits(block, 32, 32)) as usize];
+            let paint = [to_color(c1), offset(c2, d), to_color(c2), offset(c2, -d)];
+            fill_etc_pixels(&mut pixels, |x, y| if transparent(x, y) {[0; 4]} else {paint[index(x, y)]});
+        } else if !(0..32).contains(&g) {
+            // H mode
+            let r1 = bits(block, 62, 59);
+            let g1 = bits(block, 58, 56) << 1 | bits(block, 52, 52);
+            let b1 = bits(block, 51, 51) << 3 | bits(block, 49, 47);
+            let (r2, g2, b2) = (bits(block, 46, 43), bits(block, 42, 39), bits(block, 38, 35));
+            let ordered = ((r1 << 8) | (g1 << 4) | b1) >= ((r2 << 8) | (g2 << 4) | b2);
+            let d = ETC_DISTANCES[(bits(block, 34, 34) << 2 | bits(block, 32, 32) << 1 | ordered as i32) as usize];
+            let c1 = [extend_4(r1), extend_4(g1), extend_4(b1)];
+            let c2 = [extend_4(r2), extend_4(g2), extend_4(b2)];
+            let paint = [offset(c1, d), offset(c1, -d), offset(c2, d), offset(c2, -d)];
+            fill_etc_pixels(&mut pixels, |x, y| if transparent(x, y) {[0; 4]} else {paint[index(x, y)]});
+        } else if !(0..32).contains(&b) {
+            // Planar mode
+            let o = [extend_6(bits(block, 62, 57)), extend_7(bits(block, 56, 56) << 6 | bits(block, 54, 49)),
+                extend_6(bits(block, 48, 48) << 5 | bits(block, 44, 43) << 3 | bits(block, 41, 39))];
+            let h = [extend_6(bits(block, 38, 34) << 1 | bits(block, 32, 32)), extend_7(bits(block, 31, 25)), extend_6(bits(block, 24, 19))];
+            let v = [extend_6(bits(block, 18, 13)), extend_7(bits(block, 12, 6)), extend_6(bits(block, 5, 0))];
+            fill_etc_pixels(&mut pixels, |x, y| {
+                let (x, y) = (x as i32, y as i32);
+                let channel = |i: usize| (x * (h[i] - o[i]) + y * (v[i] - o[i]) + 4 * o[i] + 2) >> 2;
+                to_color([channel(0), channel(1), channel(2)])
+            });
+        } else {
+            let c1 = [extend_5(bits(block, 63, 59)), extend_5(bits(block, 55, 51)), extend_5(bits(block, 47, 43))];
+            let c2 = [extend_5(r), extend_5(g), extend_5(b)];
+            decode_etc_subblocks(&mut pixels, block, c1, c2, opaque);
+        }
+    } else {
+        let c1 = [extend_4(bits(block, 63, 60)), extend_4(bits(block, 55, 52)), extend_4(bits(block, 47, 44))];
+        let c2 = [extend_4(bits(block, 59, 56)), extend_4(bits(block, 51, 48)), extend_4(bits(block, 43, 40))];
+        decode_etc_subblocks(&mut pixels, block, c1, c2, true);
+    }
+    pixels
+}
+
+fn fill_etc_pixels<F: Fn(usize, usize) -> [u8; 4]>(pixels: &mut [[u8; 4]; 16], color: F)
+{
+    for y in 0..4 {
+        for x in 0..4 {
+            pixels[y * 4 + x] = color(x, y);
+        }
+    }
+}
+
+// The individual and differential modes which splits the block in two 2x4 or 4x2 sub blocks, each with a base color and a modifier table.
+// Without the opaque bit, the small modifiers are zero and the index 2 is transparent.
+fn decode_etc_subblocks(pixels: &mut [[u8; 4]; 16], block: u64, c1: [i32; 3], c2: [i32; 3], opaque: bool)
+{
+    let flip = bits(block, 32, 32) == 1;
+    let tables = [ETC_MODIFIERS[bits(block, 39, 37) as usize], ETC_MODIFIERS[bits(block, 36, 34) as usize]];
+    fill_etc_pixels(pixels, |x, y| {
+        let p = (x * 4 + y) as u32;
+        let index = (((block >> (p + 16)) & 1) << 1 | ((block >> p) & 1)) as usize;
+        if !opaque && index == 2 {
+            return [0; 4];
+        }
+        let subblock = if flip {y >= 2} else {x >= 2} as usize;
+        let table = tables[subblock];
+        let modifier = match index {
+            0 => if opaque {table[0]} else {0},
+            1 => table[1],
+            2 => -table[0],
+            _ => -table[1]
+        };
+        offset(if subblock == 0 {c1} else {c2}, modifier)
+    });
+}
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14], [-3, -7, -10, -13, 2, 6, 9, 12], [-2, -5, -8, -13, 1, 4, 7, 12], [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11], [-3, -7, -9, -11, 2, 6, 8, 10], [-4, -7, -8, -11, 3, 6, 7, 10], [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9], [-2, -5, -8, -10, 1, 4, 7, 9], [-2, -4, -8, -10, 1, 3, 7, 9], [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9], [-1, -2, -3, -10, 0, 1, 2, 9], [-4, -6, -8, -9, 3, 5, 7, 8], [-3, -5, -7, -9, 2, 4, 6, 8]];
+
+// Decodes the alpha part of an ETC2 RGBA block to alpha values in row order
+fn decode_eac(block: &[u8]) -> [u8; 16]
+{
+    let block = u64::from_be_bytes([block[0], block[1], block[2], block[3], block[4], block[5], block[6], block[7]]);
+    let base = bits(block, 63, 56);
+    let multiplier = bits(block, 55, 52);
+    let table = EAC_MODIFIERS[bits(block, 51, 48) as usize];
+    let mut alphas = [0u8; 16];
+    for y in 0..4 {
+        for x in 0..4 {
+            let p = (x * 4 + y) as u32;
+            let index = bits(block, 47 - 3 * p, 45 - 3 * p) as usize;
+            alphas[y * 4 + x] = (base + table[index] * multiplier).clamp(0, 255) as u8;
+        }
+    }
+    alphas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Builds an ETC block from (high bit, low bit, value) fields, where bit 63 is the most significant bit of the first byte
+    fn etc_block(fields: &[(u32, u32, u64)]) -> [u8; 8]
+    {
+        let block = fields.iter().fold(0u64, |block, &(high, low, value)| {
+            assert!(value < 1 << (high - low + 1));
+            block | value << low
+        });
+        block.to_be_bytes()
+    }
+
+    // The index of the pixel at (x, y) is stored with the most significant bit at 16 + x * 4 + y and the least significant bit at x * 4 + y
+    fn etc_index(x: u32, y: u32, index: u64) -> [(u32, u32, u64); 2]
+    {
+        let p = x * 4 + y;
+        [(p + 16, p + 16, index >> 1), (p, p, index & 1)]
+    }
+
+    fn rgb(pixel: [u8; 4]) -> [u8; 3]
+    {
+        [pixel[0], pixel[1], pixel[2]]
+    }
+
+    #[test]
+    fn bc1_four_colors()
+    {
+        let block = [0x00, 0xF8, 0x1F, 0x00, 0xE4, 0, 0, 0];
+        let pixels = decode_block(Format::RGBDXT1, &block).unwrap();
+        assert_eq!(pixels[0], [255, 0, 0, 255]);
+        assert_eq!(pixels[1], [0, 0, 255, 255]);
+        assert_eq!(pixels[2], [170, 0, 85, 255]);
+        assert_eq!(pixels[3], [85, 0, 170, 255]);
+        assert_eq!(pixels[15], [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn bc1_three_colors_and_transparent()
+    {
+        let block = [0x1F, 0x00, 0x00, 0xF8, 0xE4, 0, 0, 0];
+        let pixels = decode_block(Format::RGBADXT1, &block).unwrap();
+        assert_eq!(pixels[2], [127, 0, 127, 255]);
+        assert_eq!(pixels[3], [0, 0, 0, 0]);
+        assert_eq!(decode_block(Format::RGBDXT1, &block).unwrap()[3], [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn bc2_explicit_alpha()
+    {
+        let block = [0x8F, 0, 0, 0, 0, 0, 0, 0x10, 0x00, 0xF8, 0x1F, 0x00, 0xE4, 0, 0, 0];
+        let pixels = decode_block(Format::RGBADXT3, &block).unwrap();
+        assert_eq!(pixels[0], [255, 0, 0, 255]);
+        assert_eq!(pixels[1], [0, 0, 255, 136]);
+        assert_eq!(pixels[2][3], 0);
+        assert_eq!(pixels[15][3], 17);
+    }
+
+    #[test]
+    fn bc3_interpolated_alpha()
+    {
+        let block = [255, 0, 0x88, 0x0E, 0, 0, 0, 0, 0x00, 0xF8, 0x1F, 0x00, 0xE4, 0, 0, 0];
+        let pixels = decode_block(Format::RGBADXT5, &block).unwrap();
+        assert_eq!([pixels[0][3], pixels[1][3], pixels[2][3], pixels[3][3], pixels[4][3]], [255, 0, 218, 36, 255]);
+        assert_eq!(rgb(pixels[3]), [85, 0, 170]);
+    }
+
+    #[test]
+    fn etc2_individual_mode()
+    {
+        let mut fields = vec![(63, 60, 8), (59, 56, 4), (55, 52, 8), (51, 48, 4), (47, 44, 8), (43, 40, 4)];
+        fields.extend_from_slice(&etc_index(3, 3, 3));
+        let pixels = decode_etc2(&etc_block(&fields), false);
+        assert_eq!(pixels[0], [138, 138, 138, 255]);
+        assert_eq!(pixels[2], [70, 70, 70, 255]);
+        assert_eq!(pixels[15], [60, 60, 60, 255]);
+    }
+
+    #[test]
+    fn etc2_differential_mode()
+    {
+        let fields = [(63, 59, 16), (58, 56, 3), (55, 51, 16), (50, 48, 4), (47, 43, 16), (33, 33, 1), (32, 32, 1)];
+        let pixels = decode_etc2(&etc_block(&fields), false);
+        assert_eq!(pixels[0], [134, 134, 134, 255]);
+        assert_eq!(pixels[12], [158, 101, 134, 255]);
+    }
+
+    #[test]
+    fn etc2_t_mode()
+    {
+        let mut fields = vec![(63, 61, 7), (60, 59, 2), (57, 56, 2), (55, 52, 5), (47, 44, 8), (43, 40, 8), (39, 36, 8), (35, 34, 3), (33, 33, 1), (32, 32, 1)];
+        fields.extend_from_slice(&etc_index(1, 0, 1));
+        fields.extend_from_slice(&etc_index(2, 0, 2));
+        fields.extend_from_slice(&etc_index(3, 0, 3));
+        let pixels = decode_etc2(&etc_block(&fields), false);
+        assert_eq!(pixels[0..4], [[170, 85, 0, 255], [200, 200, 200, 255], [136, 136, 136, 255], [72, 72, 72, 255]]);
+    }
+
+    #[test]
+    fn etc2_h_mode()
+    {
+        let mut fields = vec![(62, 59, 4), (58, 56, 3), (50, 50, 1), (49, 47, 3), (46, 43, 8), (42, 39, 8), (38, 35, 8), (34, 34, 1), (33, 33, 1)];
+        fields.extend_from_slice(&etc_index(1, 0, 1));
+        fields.extend_from_slice(&etc_index(2, 0, 2));
+        fields.extend_from_slice(&etc_index(3, 0, 3));
+        let pixels = decode_etc2(&etc_block(&fields), false);
+        assert_eq!(pixels[0..4], [[91, 125, 74, 255], [45, 79, 28, 255], [159, 159, 159, 255], [113, 113, 113, 255]]);
+    }
+
+    #[test]
+    fn etc2_planar_mode()
+    {
+        let fields = [(42, 42, 1), (38, 34, 31), (33, 33, 1), (32, 32, 1)];
+        let pixels = decode_etc2(&etc_block(&fields), false);
+        for y in 0..4 {
+            assert_eq!(pixels[y * 4..y * 4 + 4], [[0, 0, 0, 255], [64, 0, 0, 255], [128, 0, 0, 255], [191, 0, 0, 255]]);
+        }
+    }
+
+    #[test]
+    fn etc2_punch_through_alpha()
+    {
+        // Without the opaque bit, index 2 is transparent and the modifier of index 0 is zero
+        let mut fields = vec![(63, 59, 16), (58, 56, 3), (55, 51, 16), (50, 48, 4), (47, 43, 16), (32, 32, 1)];
+        fields.extend_from_slice(&etc_index(1, 0, 2));
+        let pixels = decode_block(Format::RGB8A1ETC2, &etc_block(&fields)).unwrap();
+        assert_eq!(pixels[0], [132, 132, 132, 255]);
+        assert_eq!(pixels[1], [0, 0, 0, 0]);
+
+        let mut fields = vec![(63, 61, 7), (60, 59, 2), (57, 56, 2), (55, 52, 5), (47, 44, 8), (43, 40, 8), (39, 36, 8), (35, 34, 3), (32, 32, 1)];
+        fields.extend_from_slice(&etc_index(1, 0, 1));
+        fields.extend_from_slice(&etc_index(2, 0, 2));
+        let pixels = decode_block(Format::RGB8A1ETC2, &etc_block(&fields)).unwrap();
+        assert_eq!(pixels[0..3], [[170, 85, 0, 255], [200, 200, 200, 255], [0, 0, 0, 0]]);
+    }
+
+    #[test]
+    fn eac_alpha()
+    {
+        let fields = [(63, 56, 128), (55, 52, 2), (51, 48, 13), (47, 45, 7), (35, 33, 3)];
+        let alphas = decode_eac(&etc_block(&fields));
+        assert_eq!(alphas[0..3], [146, 108, 126]);
+        assert_eq!(alphas[15], 126);
+    }
+
+    #[test]
+    fn decode_image_smaller_than_a_block()
+    {
+        let image = CompressedImage {format: Format::RGBDXT1, width: 2, height: 1, levels: vec![vec![0x00, 0xF8, 0x1F, 0x00, 0xE4, 0, 0, 0]]};
+        assert_eq!(image.decode(0).unwrap().bytes, vec![255, 0, 0, 255, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn level_size_of_deep_levels()
+    {
+        let image = CompressedImage {format: Format::RGBDXT1, width: u32::MAX, height: 8, levels: vec![vec![]; 40]};
+        assert_eq!(image.level_size(1).unwrap(), (u32::MAX >> 1, 4));
+        assert_eq!(image.level_size(35).unwrap(), (1, 1));
+        assert!(image.level_size(40).is_err());
+    }
+}
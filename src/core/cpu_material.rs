@@ -5,9 +5,19 @@ pub struct Image {
     pub height: u32
 }
 
+///
+/// An image with high dynamic range, for example loaded from a .hdr file, with three float values (red, green and blue) per pixel.
+///
+pub struct HDRImage {
+    pub pixels: Vec<f32>,
+    pub width: u32,
+    pub height: u32
+}
+
 pub struct CPUMaterial {
     pub name: String,
     pub color: Option<(f32, f32, f32, f32)>,
+    // The color texture, which is assumed to be in the sRGB color space
     pub texture_image: Option<Image>,
     pub diffuse_intensity: Option<f32>,
     pub specular_intensity: Option<f32>,
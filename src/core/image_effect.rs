@@ -10,15 +10,20 @@ pub struct ImageEffect {
 impl ImageEffect {
     pub fn new(gl: &Gl, fragment_shader: &str) -> Result<Self, Error>
     {
-        let program = program::Program::from_source(&gl,
-                                                    "in vec3 position;
+        Self::from_shader_source(gl, &ShaderSource::new(&[("fragment shader", fragment_shader)]))
+    }
+
+    pub fn from_shader_source(gl: &Gl, fragment_shader: &ShaderSource) -> Result<Self, Error>
+    {
+        let program = program::Program::from_shader_source(&gl,
+                                                    &ShaderSource::new(&[("image effect vertex shader", "in vec3 position;
                                                     in vec2 uv_coordinate;
                                                     out vec2 uv;
                                                     void main()
                                                     {
                                                         uv = uv_coordinate;
                                                         gl_Position = vec4(position, 1.0);
-                                                    }",
+                                                    }")]),
                                                     fragment_shader)?;
 
         let positions = vec![
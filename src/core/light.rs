@@ -36,11 +36,296 @@ impl AmbientLight
     }
 }
 
+///
+/// An ambient light which interpolates between a sky color and a ground color depending on how much the surface normal points up or down,
+/// which gives a simple approximation of light coming from the sky and light bounced off the ground.
+///
+pub struct HemisphereLight
+{
+    sky_color: Vec3,
+    ground_color: Vec3,
+    up: Vec3,
+    intensity: f32
+}
+
+impl HemisphereLight
+{
+    pub fn new(_: &Gl, intensity: f32, sky_color: &Vec3, ground_color: &Vec3, up: &Vec3) -> Result<HemisphereLight, Error>
+    {
+        Ok(HemisphereLight { sky_color: *sky_color, ground_color: *ground_color, up: up.normalize(), intensity })
+    }
+
+    pub fn sky_color(&self) -> Vec3
+    {
+        self.sky_color
+    }
+
+    pub fn set_sky_color(&mut self, color: &Vec3)
+    {
+        self.sky_color = *color;
+    }
+
+    pub fn ground_color(&self) -> Vec3
+    {
+        self.ground_color
+    }
+
+    pub fn set_ground_color(&mut self, color: &Vec3)
+    {
+        self.ground_color = *color;
+    }
+
+    pub fn up(&self) -> Vec3
+    {
+        self.up
+    }
+
+    pub fn set_up(&mut self, up: &Vec3)
+    {
+        self.up = up.normalize();
+    }
+
+    pub fn intensity(&self) -> f32
+    {
+        self.intensity
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32)
+    {
+        self.intensity = intensity;
+    }
+
+    pub(crate) fn use_uniforms(&self, program: &Program) -> Result<(), Error>
+    {
+        program.add_uniform_vec3("hemisphereLight.skyColor", &self.sky_color)?;
+        program.add_uniform_vec3("hemisphereLight.groundColor", &self.ground_color)?;
+        program.add_uniform_vec3("hemisphereLight.up", &self.up)?;
+        program.add_uniform_float("hemisphereLight.intensity", &self.intensity)?;
+        Ok(())
+    }
+}
+
+///
+/// An ambient light given by the incoming light projected onto the first nine spherical harmonics (bands 0 to 2),
+/// which captures the low frequency variation of the light, see Ramamoorthi and Hanrahan 2001, "An Efficient Representation for Irradiance Environment Maps".
+/// The projection is done on the CPU when the light is created from a cube map or an image.
+///
+pub struct SHLight
+{
+    coefficients: [Vec3; 9],
+    intensity: f32
+}
+
+impl SHLight
+{
+    ///
+    /// Creates a light from the given coefficients, in the order L00, L1-1, L10, L11, L2-2, L2-1, L20, L21 and L22.
+    ///
+    pub fn new(_: &Gl, intensity: f32, coefficients: &[Vec3; 9]) -> Result<SHLight, Error>
+    {
+        Ok(SHLight { coefficients: *coefficients, intensity })
+    }
+
+    ///
+    /// Creates a light by projecting the given cube map onto the spherical harmonics. The cube map is read back from the GPU,
+    /// so this should not be done every frame for large cube maps.
+    ///
+    pub fn new_from_cube_map(gl: &Gl, intensity: f32, cube_map: &TextureCubeMap) -> Result<SHLight, Error>
+    {
+        let size = cube_map.width;
+        let mut samples = Vec::with_capacity(6 * size * size);
+        for side in 0..6 {
+            let pixels = cube_map.read_side_linear_rgb(side)?;
+            for y in 0..size {
+                for x in 0..size {
+                    let s = 2.0 * (x as f32 + 0.5) / size as f32 - 1.0;
+                    let t = 2.0 * (y as f32 + 0.5) / size as f32 - 1.0;
+                    // The same directions as in cube_map_shared.frag
+                    let direction = match side {
+                        0 => vec3(1.0, -t, -s),
+                        1 => vec3(-1.0, -t, s),
+                        2 => vec3(s, 1.0, t),
+                        3 => vec3(s, -1.0, -t),
+                        4 => vec3(s, -t, 1.0),
+                        _ => vec3(-s, -t, -1.0)
+                    };
+                    let solid_angle = (1.0 + s * s + t * t).powf(-1.5);
+                    let i = 3 * (y * size + x);
+                    samples.push((direction.normalize(), vec3(pixels[i], pixels[i + 1], pixels[i + 2]), solid_angle));
+                }
+            }
+        }
+        Self::new(gl, intensity, &Self::project(&samples))
+    }
+
+    ///
+    /// Creates a light by projecting the given equirectangular panorama, for example loaded from a .hdr file, onto the spherical harmonics.
+    /// The first row of the panorama is the top.
+    ///
+    pub fn new_from_equirectangular(gl: &Gl, intensity: f32, image: &HDRImage) -> Result<SHLight, Error>
+    {
+        let (width, height) = (image.width as usize, image.height as usize);
+        let mut samples = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let latitude = (0.5 - (y as f32 + 0.5) / height as f32) * std::f32::consts::PI;
+            for x in 0..width {
+                // The inverse of the mapping in equirectangular.frag
+                let longitude = ((x as f32 + 0.5) / width as f32 - 0.5) * 2.0 * std::f32::consts::PI;
+                let direction = vec3(longitude.cos() * latitude.cos(), latitude.sin(), longitude.sin() * latitude.cos());
+                let i = 3 * (y * width + x);
+                samples.push((direction, vec3(image.pixels[i], image.pixels[i + 1], image.pixels[i + 2]), latitude.cos()));
+            }
+        }
+        Self::new(gl, intensity, &Self::project(&samples))
+    }
+
+    pub fn coefficients(&self) -> &[Vec3; 9]
+    {
+        &self.coefficients
+    }
+
+    pub fn set_coefficients(&mut self, coefficients: &[Vec3; 9])
+    {
+        self.coefficients = *coefficients;
+    }
+
+    pub fn intensity(&self) -> f32
+    {
+        self.intensity
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32)
+    {
+        self.intensity = intensity;
+    }
+
+    pub(crate) fn use_uniforms(&self, program: &Program) -> Result<(), Error>
+    {
+        program.add_uniform_vec3_array("shLight.coefficients", &self.coefficients)?;
+        program.add_uniform_float("shLight.intensity", &self.intensity)?;
+        Ok(())
+    }
+
+    // Projects the samples (direction, color, relative solid angle) onto the spherical harmonics.
+    // The solid angles are normalized so they sum to the area of the sphere, which compensates for the discretization.
+    fn project(samples: &[(Vec3, Vec3, f32)]) -> [Vec3; 9]
+    {
+        let total_weight: f32 = samples.iter().map(|(_, _, weight)| weight).sum();
+        let scale = 4.0 * std::f32::consts::PI / total_weight;
+        let mut coefficients = [vec3(0.0, 0.0, 0.0); 9];
+        for (d, color, weight) in samples {
+            let basis = [0.282095,
+                0.488603 * d.y, 0.488603 * d.z, 0.488603 * d.x,
+                1.092548 * d.x * d.y, 1.092548 * d.y * d.z, 0.315392 * (3.0 * d.z * d.z - 1.0), 1.092548 * d.x * d.z, 0.546274 * (d.x * d.x - d.y * d.y)];
+            for (coefficient, b) in coefficients.iter_mut().zip(basis.iter()) {
+                *coefficient += color * (b * weight * scale);
+            }
+        }
+        coefficients
+    }
+}
+
+///
+/// Image-based lighting from an environment cube map. The diffuse lighting is looked up in an irradiance map and
+/// the specular lighting in a prefiltered environment map, where each mip level corresponds to an increasing roughness.
+/// Both maps are precomputed on the GPU when the light is created.
+///
+pub struct EnvironmentLight
+{
+    irradiance_map: TextureCubeMap,
+    prefiltered_map: TextureCubeMap,
+    irradiance_effect: ImageEffect,
+    prefilter_effect: ImageEffect,
+    intensity: f32
+}
+
+impl EnvironmentLight
+{
+    const IRRADIANCE_MAP_SIZE: usize = 32;
+    const PREFILTERED_MAP_SIZE: usize = 128;
+    const PREFILTERED_MAX_LEVEL: usize = 4;
+
+    pub fn new(gl: &Gl, intensity: f32, environment_map: &TextureCubeMap) -> Result<EnvironmentLight, Error>
+    {
+        let irradiance_map = TextureCubeMap::new(gl, Self::IRRADIANCE_MAP_SIZE, Self::IRRADIANCE_MAP_SIZE,
+                                                 Interpolation::Linear, Interpolation::Linear, None,
+                                                 Wrapping::ClampToEdge, Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::RGBA16F)?;
+        let mut prefiltered_map = TextureCubeMap::new(gl, Self::PREFILTERED_MAP_SIZE, Self::PREFILTERED_MAP_SIZE,
+                                                      Interpolation::Linear, Interpolation::Linear, Some(Interpolation::Linear),
+                                                      Wrapping::ClampToEdge, Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::RGBA16F)?;
+        prefiltered_map.set_mip_levels(0, Self::PREFILTERED_MAX_LEVEL as u32)?;
+        let irradiance_effect = ImageEffect::from_shader_source(gl, &ShaderSource::from_segments(vec![
+            shader_segment!("shaders/cube_map_shared.frag"),
+            shader_segment!("shaders/irradiance.frag")]))?;
+        let prefilter_effect = ImageEffect::from_shader_source(gl, &ShaderSource::from_segments(vec![
+            shader_segment!("shaders/cube_map_shared.frag"),
+            shader_segment!("shaders/prefilter.frag")]))?;
+
+        let light = EnvironmentLight { irradiance_map, prefiltered_map, irradiance_effect, prefilter_effect, intensity };
+        light.update(environment_map)?;
+        Ok(light)
+    }
+
+    ///
+    /// Recomputes the irradiance map and the prefiltered map from the given environment map,
+    /// for example when the environment map has been rendered again.
+    ///
+    pub fn update(&self, environment_map: &TextureCubeMap) -> Result<(), Error>
+    {
+        self.irradiance_effect.program().use_texture(environment_map, "environmentMap")?;
+        self.irradiance_map.write_sides(0, &self.irradiance_effect)?;
+
+        self.prefilter_effect.program().use_texture(environment_map, "environmentMap")?;
+        for mip_level in 0..=Self::PREFILTERED_MAX_LEVEL {
+            let roughness = mip_level as f32 / Self::PREFILTERED_MAX_LEVEL as f32;
+            self.prefilter_effect.program().add_uniform_float("roughness", &roughness)?;
+            self.prefiltered_map.write_sides(mip_level, &self.prefilter_effect)?;
+        }
+        Ok(())
+    }
+
+    pub fn intensity(&self) -> f32
+    {
+        self.intensity
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32)
+    {
+        self.intensity = intensity;
+    }
+
+    ///
+    /// The cube map containing the diffuse irradiance, that is, the cosine weighted incoming light, in each normal direction.
+    ///
+    pub fn irradiance_map(&self) -> &TextureCubeMap
+    {
+        &self.irradiance_map
+    }
+
+    ///
+    /// The cube map containing the environment convolved with the GGX distribution, with roughness going from 0 at the first mip level to 1 at the last.
+    ///
+    pub fn prefiltered_map(&self) -> &TextureCubeMap
+    {
+        &self.prefiltered_map
+    }
+
+    pub(crate) fn use_uniforms(&self, program: &Program) -> Result<(), Error>
+    {
+        program.use_texture(&self.irradiance_map, "irradianceMap")?;
+        program.use_texture(&self.prefiltered_map, "prefilteredMap")?;
+        program.add_uniform_float("environmentIntensity", &self.intensity)?;
+        program.add_uniform_float("prefilteredMaxLod", &(Self::PREFILTERED_MAX_LEVEL as f32))?;
+        Ok(())
+    }
+}
+
 pub struct DirectionalLight {
     gl: Gl,
     light_buffer: UniformBuffer,
     shadow_texture: Texture2D,
-    shadow_camera: Option<Camera>
+    shadow_camera: Option<Camera>,
+    origin: Vec3d
 }
 
 impl DirectionalLight {
@@ -51,7 +336,8 @@ impl DirectionalLight {
             gl: gl.clone(),
             light_buffer: UniformBuffer::new(gl, &[3u32, 1, 3, 1, 16])?,
             shadow_texture: Texture2D::new(gl, 1, 1, Interpolation::Nearest, Interpolation::Nearest, None,Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::Depth32F)?,
-            shadow_camera: None};
+            shadow_camera: None,
+            origin: vec3d(0.0, 0.0, 0.0)};
 
         light.set_intensity(intensity);
         light.set_color(color);
@@ -79,6 +365,21 @@ impl DirectionalLight {
         vec3(d[0], d[1], d[2])
     }
 
+    ///
+    /// Sets the origin of the render space that the shadow map target is given in, which should be the [origin](crate::Camera::origin) of the camera
+    /// when rendering relative to the camera, see [Camera::set_view_f64](crate::Camera::set_view_f64). The shadow camera uses the same origin,
+    /// so that meshes with an origin in world space are rendered into the shadow map in the same render space as they are lit in.
+    ///
+    pub fn set_origin(&mut self, origin: &Vec3d)
+    {
+        self.origin = *origin;
+    }
+
+    pub fn origin(&self) -> &Vec3d
+    {
+        &self.origin
+    }
+
     pub fn clear_shadow_map(&mut self)
     {
         self.shadow_camera = None;
@@ -90,11 +391,16 @@ impl DirectionalLight {
                                   frustrum_width: f32, frustrum_height: f32, frustrum_depth: f32,
                                   texture_width: usize, texture_height: usize, render_scene: F)
     {
+        // The shadow map always uses the standard depth, so that it is compared in the same way in the shaders whether the depth is reversed or not
+        let reversed_depth = state::is_depth_reversed();
+        state::reversed_depth(&self.gl, false);
         let direction = self.direction();
         let up = compute_up_direction(direction);
 
-        self.shadow_camera = Some(Camera::new_orthographic(&self.gl, target - direction.normalize()*0.5*frustrum_depth, *target, up,
-                                                           frustrum_width, frustrum_height, frustrum_depth));
+        let mut shadow_camera = Camera::new_orthographic(&self.gl, target - direction.normalize()*0.5*frustrum_depth, *target, up,
+                                                           frustrum_width, frustrum_height, frustrum_depth);
+        shadow_camera.set_origin(&self.origin);
+        self.shadow_camera = Some(shadow_camera);
         self.light_buffer.update(4, &shadow_matrix(self.shadow_camera.as_ref().unwrap()).to_slice()).unwrap();
 
         state::depth_write(&self.gl, true);
@@ -107,6 +413,7 @@ impl DirectionalLight {
             Some(&self.shadow_texture),
             || {render_scene(self.shadow_camera.as_ref().unwrap())?; Ok(())}).unwrap();
         self.light_buffer.update(3, &[1.0]).unwrap();
+        state::reversed_depth(&self.gl, reversed_depth);
     }
 
     pub fn shadow_map(&self) -> &Texture2D
@@ -118,6 +425,18 @@ impl DirectionalLight {
     {
         &self.light_buffer
     }
+
+    ///
+    /// Sets the uniforms of the DirectionalLight struct with the given name in light_shared.frag, which is used when the light is one of several lights in an array.
+    ///
+    pub(crate) fn use_uniforms(&self, program: &Program, name: &str) -> Result<(), Error>
+    {
+        use_base_light_uniforms(program, name, &self.light_buffer)?;
+        program.add_uniform_vec3(&format!("{}.direction", name), &vec3_from_slice(self.light_buffer.get(2)?))?;
+        program.add_uniform_float(&format!("{}.shadowEnabled", name), &self.light_buffer.get(3)?[0])?;
+        program.add_uniform_mat4(&format!("{}.shadowMVP", name), &mat4_from_slice(self.light_buffer.get(4)?))?;
+        Ok(())
+    }
 }
 
 pub struct PointLight {
@@ -155,22 +474,73 @@ impl PointLight {
         self.light_buffer.update(4, &[exponential]).unwrap();
     }
 
+    ///
+    /// Sets the position of the light in render space. When rendering relative to the camera, see [Camera::set_view_f64](crate::Camera::set_view_f64),
+    /// the position must be relative to the [origin](crate::Camera::origin) of the camera, for example given by [Camera::to_render_space](crate::Camera::to_render_space),
+    /// and it must be set again whenever the camera origin changes.
+    ///
     pub fn set_position(&mut self, position: &Vec3)
     {
         self.light_buffer.update(6, &position.to_slice()).unwrap();
     }
 
+    pub fn color(&self) -> Vec3
+    {
+        let c = self.light_buffer.get(0).unwrap();
+        vec3(c[0], c[1], c[2])
+    }
+
+    pub fn intensity(&self) -> f32
+    {
+        self.light_buffer.get(1).unwrap()[0]
+    }
+
+    ///
+    /// Returns the constant, linear and exponential attenuation.
+    ///
+    pub fn attenuation(&self) -> (f32, f32, f32)
+    {
+        (self.light_buffer.get(2).unwrap()[0], self.light_buffer.get(3).unwrap()[0], self.light_buffer.get(4).unwrap()[0])
+    }
+
+    pub fn position(&self) -> Vec3
+    {
+        let p = self.light_buffer.get(6).unwrap();
+        vec3(p[0], p[1], p[2])
+    }
+
+    ///
+    /// The distance from the light at which the attenuated light falls below 1/256 of the full intensity, that is, below what can be seen with 8 bits per color channel.
+    /// Returns infinity if the light is not attenuated.
+    ///
+    pub fn influence_radius(&self) -> f32
+    {
+        influence_radius(self.intensity(), &self.color(), self.attenuation())
+    }
+
     pub fn buffer(&self) -> &UniformBuffer
     {
         &self.light_buffer
     }
+
+    ///
+    /// Sets the uniforms of the PointLight struct with the given name in light_shared.frag, see [DirectionalLight::use_uniforms].
+    ///
+    pub(crate) fn use_uniforms(&self, program: &Program, name: &str) -> Result<(), Error>
+    {
+        use_base_light_uniforms(program, name, &self.light_buffer)?;
+        use_attenuation_uniforms(program, name, &self.light_buffer)?;
+        program.add_uniform_vec3(&format!("{}.position", name), &vec3_from_slice(self.light_buffer.get(6)?))?;
+        Ok(())
+    }
 }
 
 pub struct SpotLight {
     gl: Gl,
     light_buffer: UniformBuffer,
     shadow_texture: Texture2D,
-    shadow_camera: Option<Camera>
+    shadow_camera: Option<Camera>,
+    origin: Vec3d
 }
 
 impl SpotLight {
@@ -183,7 +553,8 @@ impl SpotLight {
             gl: gl.clone(),
             light_buffer: UniformBuffer::new(gl, &uniform_sizes)?,
             shadow_texture: Texture2D::new(gl, 1, 1, Interpolation::Nearest, Interpolation::Nearest, None,Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::Depth32F)?,
-            shadow_camera: None
+            shadow_camera: None,
+            origin: vec3d(0.0, 0.0, 0.0)
         };
         light.set_intensity(intensity);
         light.set_color(color);
@@ -211,6 +582,11 @@ impl SpotLight {
         self.light_buffer.update(4, &[exponential]).unwrap();
     }
 
+    ///
+    /// Sets the position of the light in render space. When rendering relative to the camera, see [Camera::set_view_f64](crate::Camera::set_view_f64),
+    /// the position must be relative to the [origin](crate::Camera::origin) of the camera, for example given by [Camera::to_render_space](crate::Camera::to_render_space),
+    /// and it must be set again whenever the camera origin changes.
+    ///
     pub fn set_position(&mut self, position: &Vec3)
     {
         self.light_buffer.update(6, &position.to_slice()).unwrap();
@@ -222,11 +598,51 @@ impl SpotLight {
         vec3(p[0], p[1], p[2])
     }
 
+    pub fn color(&self) -> Vec3
+    {
+        let c = self.light_buffer.get(0).unwrap();
+        vec3(c[0], c[1], c[2])
+    }
+
+    pub fn intensity(&self) -> f32
+    {
+        self.light_buffer.get(1).unwrap()[0]
+    }
+
+    ///
+    /// Returns the constant, linear and exponential attenuation.
+    ///
+    pub fn attenuation(&self) -> (f32, f32, f32)
+    {
+        (self.light_buffer.get(2).unwrap()[0], self.light_buffer.get(3).unwrap()[0], self.light_buffer.get(4).unwrap()[0])
+    }
+
+    ///
+    /// The distance from the light at which the attenuated light falls below 1/256 of the full intensity, see [PointLight::influence_radius].
+    ///
+    pub fn influence_radius(&self) -> f32
+    {
+        influence_radius(self.intensity(), &self.color(), self.attenuation())
+    }
+
     pub fn set_cutoff(&mut self, cutoff: f32)
     {
         self.light_buffer.update(7, &[cutoff]).unwrap();
     }
 
+    ///
+    /// The cutoff angle in degrees.
+    ///
+    pub fn cutoff(&self) -> f32
+    {
+        self.light_buffer.get(7).unwrap()[0]
+    }
+
+    pub fn has_shadows(&self) -> bool
+    {
+        self.light_buffer.get(9).unwrap()[0] > 0.5
+    }
+
     pub fn set_direction(&mut self, direction: &Vec3)
     {
         self.light_buffer.update(8, &direction.normalize().to_slice()).unwrap();
@@ -238,6 +654,22 @@ impl SpotLight {
         vec3(d[0], d[1], d[2])
     }
 
+    ///
+    /// Sets the origin of the render space that the shadow camera uses, which should be the [origin](crate::Camera::origin) of the camera
+    /// when rendering relative to the camera, see [Camera::set_view_f64](crate::Camera::set_view_f64), so that meshes with an origin in world space
+    /// are rendered into the shadow map in the same render space as they are lit in. This only affects the shadow camera,
+    /// the [position](SpotLight::set_position) of the light is not converted and must be given in render space.
+    ///
+    pub fn set_origin(&mut self, origin: &Vec3d)
+    {
+        self.origin = *origin;
+    }
+
+    pub fn origin(&self) -> &Vec3d
+    {
+        &self.origin
+    }
+
     pub fn clear_shadow_map(&mut self)
     {
         self.shadow_camera = None;
@@ -247,13 +679,18 @@ impl SpotLight {
 
     pub fn generate_shadow_map<F: FnOnce(&Camera) -> Result<(), Error>>(&mut self, frustrum_depth: f32, texture_size: usize, render_scene: F)
     {
+        // The shadow map always uses the standard depth, see DirectionalLight::generate_shadow_map
+        let reversed_depth = state::is_depth_reversed();
+        state::reversed_depth(&self.gl, false);
         let position = self.position();
         let direction = self.direction();
         let up = compute_up_direction(direction);
         let cutoff = self.light_buffer.get(7).unwrap()[0];
 
-        self.shadow_camera = Some(Camera::new_perspective(&self.gl, position, position + direction, up,
-                                                          degrees(cutoff), 1.0, 0.1, frustrum_depth));
+        let mut shadow_camera = Camera::new_perspective(&self.gl, position, position + direction, up,
+                                                          degrees(cutoff), 1.0, 0.1, frustrum_depth);
+        shadow_camera.set_origin(&self.origin);
+        self.shadow_camera = Some(shadow_camera);
         self.light_buffer.update(10, &shadow_matrix(self.shadow_camera.as_ref().unwrap()).to_slice()).unwrap();
 
         state::depth_write(&self.gl, true);
@@ -265,6 +702,7 @@ impl SpotLight {
         RenderTarget::write_to_depth(&self.gl, 0, 0, texture_size, texture_size, Some(1.0),
             Some(&self.shadow_texture), || {render_scene(self.shadow_camera.as_ref().unwrap())?; Ok(())}).unwrap();
         self.light_buffer.update(9, &[1.0]).unwrap();
+        state::reversed_depth(&self.gl, reversed_depth);
     }
 
     pub fn shadow_map(&self) -> &Texture2D
@@ -276,6 +714,248 @@ impl SpotLight {
     {
         &self.light_buffer
     }
+
+    ///
+    /// Sets the uniforms of the SpotLight struct with the given name in light_shared.frag, see [DirectionalLight::use_uniforms].
+    ///
+    pub(crate) fn use_uniforms(&self, program: &Program, name: &str) -> Result<(), Error>
+    {
+        use_base_light_uniforms(program, name, &self.light_buffer)?;
+        use_attenuation_uniforms(program, name, &self.light_buffer)?;
+        program.add_uniform_vec3(&format!("{}.position", name), &vec3_from_slice(self.light_buffer.get(6)?))?;
+        program.add_uniform_float(&format!("{}.cutoff", name), &self.light_buffer.get(7)?[0])?;
+        program.add_uniform_vec3(&format!("{}.direction", name), &vec3_from_slice(self.light_buffer.get(8)?))?;
+        program.add_uniform_float(&format!("{}.shadowEnabled", name), &self.light_buffer.get(9)?[0])?;
+        program.add_uniform_mat4(&format!("{}.shadowMVP", name), &mat4_from_slice(self.light_buffer.get(10)?))?;
+        Ok(())
+    }
+}
+
+// The color and intensity are the first two entries in the buffer of all lights
+fn use_base_light_uniforms(program: &Program, name: &str, buffer: &UniformBuffer) -> Result<(), Error>
+{
+    program.add_uniform_vec3(&format!("{}.base.color", name), &vec3_from_slice(buffer.get(0)?))?;
+    program.add_uniform_float(&format!("{}.base.intensity", name), &buffer.get(1)?[0])?;
+    Ok(())
+}
+
+// The attenuation follows the color and intensity in the buffer of point and spot lights
+fn use_attenuation_uniforms(program: &Program, name: &str, buffer: &UniformBuffer) -> Result<(), Error>
+{
+    program.add_uniform_float(&format!("{}.attenuation.constant", name), &buffer.get(2)?[0])?;
+    program.add_uniform_float(&format!("{}.attenuation.linear", name), &buffer.get(3)?[0])?;
+    program.add_uniform_float(&format!("{}.attenuation.exp", name), &buffer.get(4)?[0])?;
+    Ok(())
+}
+
+fn vec3_from_slice(data: &[f32]) -> Vec3
+{
+    vec3(data[0], data[1], data[2])
+}
+
+fn mat4_from_slice(data: &[f32]) -> Mat4
+{
+    Mat4::new(data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
+              data[8], data[9], data[10], data[11], data[12], data[13], data[14], data[15])
+}
+
+fn influence_radius(intensity: f32, color: &Vec3, attenuation: (f32, f32, f32)) -> f32
+{
+    // Solve constant + linear * d + exponential * d^2 = 256 * intensity * max color
+    let (constant, linear, exponential) = attenuation;
+    let target = 256.0 * intensity * color.x.max(color.y).max(color.z) - constant;
+    if target <= 0.0 {
+        0.0
+    } else if exponential > 0.0 {
+        (-linear + (linear * linear + 4.0 * exponential * target).sqrt()) / (2.0 * exponential)
+    } else if linear > 0.0 {
+        target / linear
+    } else {
+        f32::INFINITY
+    }
+}
+
+///
+/// A rectangular area light which emits light from one side, the side facing the direction of the light.
+///
+pub struct RectLight
+{
+    color: Vec3,
+    intensity: f32,
+    position: Vec3,
+    direction: Vec3,
+    up: Vec3,
+    width: f32,
+    height: f32
+}
+
+impl RectLight
+{
+    ///
+    /// Creates a new rectangular light centered at the given position, emitting light in the given direction.
+    /// The height of the rectangle is along the up direction, which is projected onto the plane of the rectangle, and the width is orthogonal to that.
+    ///
+    pub fn new(_: &Gl, intensity: f32, color: &Vec3, position: &Vec3, direction: &Vec3, up: &Vec3, width: f32, height: f32) -> Result<RectLight, Error>
+    {
+        let mut light = RectLight { color: *color, intensity, position: *position, direction: vec3(0.0, 0.0, 1.0), up: vec3(0.0, 1.0, 0.0), width, height };
+        light.set_direction(direction, up);
+        Ok(light)
+    }
+
+    pub fn color(&self) -> Vec3
+    {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: &Vec3)
+    {
+        self.color = *color;
+    }
+
+    pub fn intensity(&self) -> f32
+    {
+        self.intensity
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32)
+    {
+        self.intensity = intensity;
+    }
+
+    pub fn position(&self) -> Vec3
+    {
+        self.position
+    }
+
+    ///
+    /// Sets the position of the light in render space. When rendering relative to the camera, see [Camera::set_view_f64](crate::Camera::set_view_f64),
+    /// the position must be relative to the [origin](crate::Camera::origin) of the camera, for example given by [Camera::to_render_space](crate::Camera::to_render_space),
+    /// and it must be set again whenever the camera origin changes.
+    ///
+    pub fn set_position(&mut self, position: &Vec3)
+    {
+        self.position = *position;
+    }
+
+    pub fn direction(&self) -> Vec3
+    {
+        self.direction
+    }
+
+    pub fn set_direction(&mut self, direction: &Vec3, up: &Vec3)
+    {
+        self.direction = direction.normalize();
+        self.up = (up - self.direction * up.dot(self.direction)).normalize();
+    }
+
+    pub fn set_size(&mut self, width: f32, height: f32)
+    {
+        self.width = width;
+        self.height = height;
+    }
+
+    ///
+    /// The corners of the rectangle in counter clockwise order when seen from the lit side.
+    ///
+    pub fn corners(&self) -> [Vec3; 4]
+    {
+        let right = self.up.cross(self.direction) * (0.5 * self.width);
+        let up = self.up * (0.5 * self.height);
+        [self.position - right - up, self.position + right - up, self.position + right + up, self.position - right + up]
+    }
+}
+
+///
+/// A disk shaped area light which emits light from one side, the side facing the direction of the light.
+///
+pub struct DiskLight
+{
+    color: Vec3,
+    intensity: f32,
+    position: Vec3,
+    direction: Vec3,
+    radius: f32
+}
+
+impl DiskLight
+{
+    const POLYGON_VERTEX_COUNT: usize = 12;
+
+    pub fn new(_: &Gl, intensity: f32, color: &Vec3, position: &Vec3, direction: &Vec3, radius: f32) -> Result<DiskLight, Error>
+    {
+        Ok(DiskLight { color: *color, intensity, position: *position, direction: direction.normalize(), radius })
+    }
+
+    pub fn color(&self) -> Vec3
+    {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: &Vec3)
+    {
+        self.color = *color;
+    }
+
+    pub fn intensity(&self) -> f32
+    {
+        self.intensity
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32)
+    {
+        self.intensity = intensity;
+    }
+
+    pub fn position(&self) -> Vec3
+    {
+        self.position
+    }
+
+    ///
+    /// Sets the position of the light in render space. When rendering relative to the camera, see [Camera::set_view_f64](crate::Camera::set_view_f64),
+    /// the position must be relative to the [origin](crate::Camera::origin) of the camera, for example given by [Camera::to_render_space](crate::Camera::to_render_space),
+    /// and it must be set again whenever the camera origin changes.
+    ///
+    pub fn set_position(&mut self, position: &Vec3)
+    {
+        self.position = *position;
+    }
+
+    pub fn direction(&self) -> Vec3
+    {
+        self.direction
+    }
+
+    pub fn set_direction(&mut self, direction: &Vec3)
+    {
+        self.direction = direction.normalize();
+    }
+
+    pub fn radius(&self) -> f32
+    {
+        self.radius
+    }
+
+    pub fn set_radius(&mut self, radius: f32)
+    {
+        self.radius = radius;
+    }
+
+    ///
+    /// The vertices of a regular polygon with the same area as the disk, in counter clockwise order when seen from the lit side.
+    /// The lighting from the disk is evaluated as the lighting from this polygon.
+    ///
+    pub fn polygon(&self) -> Vec<Vec3>
+    {
+        let n = Self::POLYGON_VERTEX_COUNT as f32;
+        let radius = self.radius * (2.0 * std::f32::consts::PI / (n * (2.0 * std::f32::consts::PI / n).sin())).sqrt();
+        let u = compute_up_direction(self.direction);
+        let v = self.direction.cross(u);
+        (0..Self::POLYGON_VERTEX_COUNT).map(|i| {
+            let angle = 2.0 * std::f32::consts::PI * i as f32 / n;
+            self.position + radius * (angle.cos() * u + angle.sin() * v)
+        }).collect()
+    }
 }
 
 fn shadow_matrix(camera: &Camera) -> Mat4
@@ -0,0 +1,94 @@
+use crate::core::*;
+
+///
+/// Renders the scene as reflected in a horizontal plane at a given height into a texture, which can then be sampled in screen space when rendering the reflecting surface,
+/// for example water or a mirror floor. Everything below the plane is clipped away using an oblique near plane.
+///
+pub struct PlanarReflection {
+    gl: Gl,
+    plane_height: f32,
+    camera: Camera,
+    color_texture: Texture2D,
+    depth_texture: Texture2D
+}
+
+impl PlanarReflection {
+
+    ///
+    /// Creates a new planar reflection in the plane y = plane_height, where the reflection is rendered into textures with the given size,
+    /// which should be the same as the size of the screen or render target that the reflecting surface is rendered to.
+    ///
+    pub fn new(gl: &Gl, plane_height: f32, width: usize, height: usize) -> Result<PlanarReflection, Error>
+    {
+        let camera = Camera::new_perspective(gl, vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0), degrees(45.0), 1.0, 0.1, 1.0);
+        let (color_texture, depth_texture) = Self::new_textures(gl, width, height)?;
+        Ok(PlanarReflection { gl: gl.clone(), plane_height, camera, color_texture, depth_texture })
+    }
+
+    ///
+    /// Renders the reflection as seen from the given camera, where the render closure is called with the mirrored camera.
+    /// Since the mirroring reverses the winding order of triangles, front faces should be culled instead of back faces in the closure.
+    ///
+    pub fn render<F: FnOnce(&Camera) -> Result<(), Error>>(&mut self, camera: &Camera, render_scene: F) -> Result<(), Error>
+    {
+        let offset = vec3(0.0, 2.0 * self.plane_height, 0.0);
+        self.camera.copy_from(camera);
+        self.camera.set_view(camera.position() - offset, camera.target() - offset, *camera.up());
+        self.camera.mirror_in_xz_plane();
+        self.camera.set_oblique_clip_plane(&vec4(0.0, 1.0, 0.0, -self.plane_height));
+
+        let camera = &self.camera;
+        RenderTarget::write(&self.gl, 0, 0, self.color_texture.width, self.color_texture.height,
+                            Some(&vec4(0.0, 0.0, 0.0, 1.0)), Some(1.0),
+                            Some(&self.color_texture), Some(&self.depth_texture),
+                            || render_scene(camera))
+    }
+
+    ///
+    /// Resizes the reflection textures, which should be done when the screen is resized.
+    ///
+    pub fn set_size(&mut self, width: usize, height: usize) -> Result<(), Error>
+    {
+        if width != self.color_texture.width || height != self.color_texture.height {
+            let (color_texture, depth_texture) = Self::new_textures(&self.gl, width, height)?;
+            self.color_texture = color_texture;
+            self.depth_texture = depth_texture;
+        }
+        Ok(())
+    }
+
+    pub fn plane_height(&self) -> f32
+    {
+        self.plane_height
+    }
+
+    pub fn set_plane_height(&mut self, plane_height: f32)
+    {
+        self.plane_height = plane_height;
+    }
+
+    ///
+    /// The mirrored camera used the last time the reflection was rendered.
+    ///
+    pub fn camera(&self) -> &Camera
+    {
+        &self.camera
+    }
+
+    ///
+    /// The texture containing the reflection in linear color space, where the pixel at the screen position of a point on the plane contains the reflection at that point.
+    ///
+    pub fn color_texture(&self) -> &Texture2D
+    {
+        &self.color_texture
+    }
+
+    fn new_textures(gl: &Gl, width: usize, height: usize) -> Result<(Texture2D, Texture2D), Error>
+    {
+        let color_texture = Texture2D::new(gl, width, height, Interpolation::Linear, Interpolation::Linear, None,
+                                           Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::SRGB8Alpha8)?;
+        let depth_texture = Texture2D::new(gl, width, height, Interpolation::Nearest, Interpolation::Nearest, None,
+                                           Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::Depth32F)?;
+        Ok((color_texture, depth_texture))
+    }
+}
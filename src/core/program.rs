@@ -1,75 +1,192 @@
 use std::collections::HashMap;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
 use crate::core::*;
 
+#[derive(Clone, Debug)]
+pub struct AttributeInfo {
+    pub name: String,
+    pub location: u32,
+    pub type_: u32,
+    pub size: u32
+}
+
+// The size is the number of array elements (1 if the uniform is not an array).
+// Uniforms in a uniform block have no location; the block is the index into the program's uniform blocks.
+#[derive(Clone, Debug)]
+pub struct UniformInfo {
+    pub name: String,
+    pub type_: u32,
+    pub size: u32,
+    pub block: Option<usize>
+}
+
+#[derive(Clone, Debug)]
+pub struct UniformBlockMember {
+    pub name: String,
+    pub type_: u32,
+    pub size: u32,
+    pub offset: u32,
+    pub array_stride: u32,
+    pub matrix_stride: u32
+}
+
+#[derive(Clone, Debug)]
+pub struct UniformBlockInfo {
+    pub name: String,
+    pub index: u32,
+    pub size_in_bytes: u32,
+    pub members: Vec<UniformBlockMember>
+}
+
+///
+/// A value which can be sent to a uniform in a shader program.
+///
+pub trait UniformValue {
+    fn is_compatible(type_: u32) -> bool;
+    fn send(&self, gl: &Gl, location: &crate::gl::UniformLocation);
+}
+
+impl UniformValue for i32 {
+    fn is_compatible(type_: u32) -> bool {
+        type_ == consts::INT || type_ == consts::BOOL || is_sampler(type_)
+    }
+    fn send(&self, gl: &Gl, location: &crate::gl::UniformLocation) {
+        gl.uniform1i(location, *self);
+    }
+}
+
+impl UniformValue for bool {
+    fn is_compatible(type_: u32) -> bool {
+        type_ == consts::BOOL
+    }
+    fn send(&self, gl: &Gl, location: &crate::gl::UniformLocation) {
+        gl.uniform1i(location, *self as i32);
+    }
+}
+
+impl UniformValue for f32 {
+    fn is_compatible(type_: u32) -> bool {
+        type_ == consts::FLOAT
+    }
+    fn send(&self, gl: &Gl, location: &crate::gl::UniformLocation) {
+        gl.uniform1f(location, *self);
+    }
+}
+
+impl UniformValue for Vec2 {
+    fn is_compatible(type_: u32) -> bool {
+        type_ == consts::FLOAT_VEC2
+    }
+    fn send(&self, gl: &Gl, location: &crate::gl::UniformLocation) {
+        gl.uniform2fv(location, &[self.x, self.y]);
+    }
+}
+
+impl UniformValue for Vec3 {
+    fn is_compatible(type_: u32) -> bool {
+        type_ == consts::FLOAT_VEC3
+    }
+    fn send(&self, gl: &Gl, location: &crate::gl::UniformLocation) {
+        gl.uniform3fv(location, &[self.x, self.y, self.z]);
+    }
+}
+
+impl UniformValue for Vec4 {
+    fn is_compatible(type_: u32) -> bool {
+        type_ == consts::FLOAT_VEC4
+    }
+    fn send(&self, gl: &Gl, location: &crate::gl::UniformLocation) {
+        gl.uniform4fv(location, &[self.x, self.y, self.z, self.w]);
+    }
+}
+
+impl UniformValue for Mat2 {
+    fn is_compatible(type_: u32) -> bool {
+        type_ == consts::FLOAT_MAT2
+    }
+    fn send(&self, gl: &Gl, location: &crate::gl::UniformLocation) {
+        gl.uniform_matrix2fv(location, &self.to_slice());
+    }
+}
+
+impl UniformValue for Mat3 {
+    fn is_compatible(type_: u32) -> bool {
+        type_ == consts::FLOAT_MAT3
+    }
+    fn send(&self, gl: &Gl, location: &crate::gl::UniformLocation) {
+        gl.uniform_matrix3fv(location, &self.to_slice());
+    }
+}
+
+impl UniformValue for Mat4 {
+    fn is_compatible(type_: u32) -> bool {
+        type_ == consts::FLOAT_MAT4
+    }
+    fn send(&self, gl: &Gl, location: &crate::gl::UniformLocation) {
+        gl.uniform_matrix4fv(location, &self.to_slice());
+    }
+}
+
+///
+/// A handle to a uniform which has been looked up and type checked once, see [Program::uniform](struct.Program.html#method.uniform).
+/// Setting a uniform through a handle avoids looking up the uniform by name every frame.
+///
+pub struct Uniform<T: UniformValue> {
+    name: String,
+    index: usize,
+    element: u32,
+    generation: u32,
+    value_type: PhantomData<T>
+}
+
+impl<T: UniformValue> Uniform<T> {
+    pub fn name(&self) -> &str
+    {
+        &self.name
+    }
+}
+
+impl<T: UniformValue> Clone for Uniform<T> {
+    fn clone(&self) -> Self
+    {
+        Uniform {name: self.name.clone(), index: self.index, element: self.element, generation: self.generation, value_type: PhantomData}
+    }
+}
+
 pub struct Program {
     gl: Gl,
-    id: crate::gl::Program,
-    vertex_attributes: HashMap<String, u32>,
+    id: RefCell<crate::gl::Program>,
+    reflection: RefCell<Reflection>,
+    // Incremented every time the program is relinked, which invalidates uniform handles
+    generation: Cell<u32>,
     textures: RefCell<HashMap<String, u32>>,
-    uniforms: HashMap<String, crate::gl::UniformLocation>,
-    uniform_blocks: RefCell<HashMap<String, (u32, u32)>>
+    uniform_blocks: RefCell<HashMap<String, (u32, u32)>>,
+    #[cfg(all(feature = "shader-hot-reload", not(target_arch = "wasm32")))]
+    hot_reload: RefCell<HotReload>
 }
 
 impl Program
 {
     pub fn from_source(gl: &Gl, vertex_shader_source: &str, fragment_shader_source: &str) -> Result<Program, Error>
     {
-        let vert_shader = gl.create_shader(consts::VERTEX_SHADER)
-            .ok_or(Error::FailedToCreateShader{ shader_type: "Vertex shader".to_string(), message:"Unable to create shader object".to_string() })?;
-        let frag_shader = gl.create_shader(consts::FRAGMENT_SHADER)
-            .ok_or(Error::FailedToCreateShader{ shader_type: "Fragment shader".to_string(), message:"Unable to create shader object".to_string() })?;
-        gl.compile_shader(vertex_shader_source, &vert_shader);
-        gl.compile_shader(fragment_shader_source, &frag_shader);
-
-        let id = gl.create_program();
-        gl.attach_shader(&id, &vert_shader);
-        gl.attach_shader(&id, &frag_shader);
-        let success = gl.link_program(&id);
-
-        gl.detach_shader(&id, &vert_shader);
-        gl.detach_shader(&id, &frag_shader);
-        gl.delete_shader(Some(&vert_shader));
-        gl.delete_shader(Some(&frag_shader));
-
-        if !success {
-            let mut message = "Failed to compile shader program:\n".to_string();
-            if let Some(log) = gl.get_program_info_log(&id) {message = format!("{}\nLink error: {}", message, log);}
-            if let Some(log) = gl.get_shader_info_log(&vert_shader) {message = format!("{}\nVertex shader error: {}", message, log);}
-            if let Some(log) = gl.get_shader_info_log(&frag_shader) {message = format!("{}\nFragment shader error: {}", message, log);}
-            return Err(Error::FailedToLinkProgram {message});
-        }
-
-        // Init vertex attributes
-        let num_attribs = gl.get_program_parameter(&id, consts::ACTIVE_ATTRIBUTES);
-        let mut vertex_attributes = HashMap::new();
-        for i in 0..num_attribs {
-            let info = gl.get_active_attrib(&id, i);
-            let location = gl.get_attrib_location(&id, &info.name()).unwrap();
-            //println!("Attribute location: {}, name: {}, type: {}, size: {}", location, info.name(), info.type_(), info.size());
-            vertex_attributes.insert(info.name(), location);
-        }
-
-        // Init uniforms
-        let num_uniforms = gl.get_program_parameter(&id, consts::ACTIVE_UNIFORMS);
-        let mut uniforms = HashMap::new();
-        for i in 0..num_uniforms {
-            let info = gl.get_active_uniform(&id, i);
-            let location = gl.get_uniform_location(&id, &info.name());
-            //println!("Uniform location: {:?}, name: {}, type: {}, size: {}", location, info.name(), info.type_(), info.size());
-            if let Some(loc) = location {
-                uniforms.insert(info.name(), loc);
-            }
-        }
+        Self::from_shader_source(gl, &ShaderSource::new(&[("vertex shader", vertex_shader_source)]),
+                                 &ShaderSource::new(&[("fragment shader", fragment_shader_source)]))
+    }
 
-        Ok(Program { gl: gl.clone(), id, vertex_attributes, uniforms, uniform_blocks: RefCell::new(HashMap::new()),
-            textures: RefCell::new(HashMap::new())})
+    pub fn from_shader_source(gl: &Gl, vertex_shader_source: &ShaderSource, fragment_shader_source: &ShaderSource) -> Result<Program, Error>
+    {
+        let (id, reflection) = link(gl, vertex_shader_source, fragment_shader_source)?;
+        Ok(Program { gl: gl.clone(), id: RefCell::new(id), reflection: RefCell::new(reflection), generation: Cell::new(0),
+            uniform_blocks: RefCell::new(HashMap::new()), textures: RefCell::new(HashMap::new()),
+            #[cfg(all(feature = "shader-hot-reload", not(target_arch = "wasm32")))]
+            hot_reload: RefCell::new(HotReload::new(vertex_shader_source, fragment_shader_source))})
     }
 
     pub fn add_uniform_int(&self, name: &str, data: &i32) -> Result<(), Error>
     {
         let location = self.get_uniform_location(name)?;
-        self.gl.uniform1i(location, *data);
+        self.gl.uniform1i(&location, *data);
         self.gl.unuse_program();
         Ok(())
     }
@@ -77,7 +194,7 @@ impl Program
     pub fn add_uniform_float(&self, name: &str, data: &f32) -> Result<(), Error>
     {
         let location = self.get_uniform_location(name)?;
-        self.gl.uniform1f(location, *data);
+        self.gl.uniform1f(&location, *data);
         self.gl.unuse_program();
         Ok(())
     }
@@ -85,7 +202,7 @@ impl Program
     pub fn add_uniform_vec2(&self, name: &str, data: &Vec2) -> Result<(), Error>
     {
         let location = self.get_uniform_location(name)?;
-        self.gl.uniform2fv(location, &mut [data.x, data.y]);
+        self.gl.uniform2fv(&location, &mut [data.x, data.y]);
         self.gl.unuse_program();
         Ok(())
     }
@@ -93,7 +210,19 @@ impl Program
     pub fn add_uniform_vec3(&self, name: &str, data: &Vec3) -> Result<(), Error>
     {
         let location = self.get_uniform_location(name)?;
-        self.gl.uniform3fv(location, &mut [data.x, data.y, data.z]);
+        self.gl.uniform3fv(&location, &mut [data.x, data.y, data.z]);
+        self.gl.unuse_program();
+        Ok(())
+    }
+
+    ///
+    /// Sets consecutive elements of a uniform array, starting at the element with the given name, for example 'lights' or 'lights[2]'.
+    /// Returns an error if the array does not have enough elements.
+    ///
+    pub fn add_uniform_vec3_array(&self, name: &str, data: &[Vec3]) -> Result<(), Error>
+    {
+        let location = self.get_uniform_array_location(name, data.len() as u32)?;
+        self.gl.uniform3fv(&location, &flatten_vec3(data));
         self.gl.unuse_program();
         Ok(())
     }
@@ -101,7 +230,7 @@ impl Program
     pub fn add_uniform_vec4(&self, name: &str, data: &Vec4) -> Result<(), Error>
     {
         let location= self.get_uniform_location(name)?;
-        self.gl.uniform4fv(location, &mut [data.x, data.y, data.z, data.w]);
+        self.gl.uniform4fv(&location, &mut [data.x, data.y, data.z, data.w]);
         self.gl.unuse_program();
         Ok(())
     }
@@ -109,7 +238,7 @@ impl Program
     pub fn add_uniform_mat2(&self, name: &str, data: &Mat2) -> Result<(), Error>
     {
         let location = self.get_uniform_location(name)?;
-        self.gl.uniform_matrix2fv(location, &mut data.to_slice());
+        self.gl.uniform_matrix2fv(&location, &mut data.to_slice());
         self.gl.unuse_program();
         Ok(())
     }
@@ -117,7 +246,7 @@ impl Program
     pub fn add_uniform_mat3(&self, name: &str, data: &Mat3) -> Result<(), Error>
     {
         let location = self.get_uniform_location(name)?;
-        self.gl.uniform_matrix3fv(location, &mut data.to_slice());
+        self.gl.uniform_matrix3fv(&location, &mut data.to_slice());
         self.gl.unuse_program();
         Ok(())
     }
@@ -125,16 +254,101 @@ impl Program
     pub fn add_uniform_mat4(&self, name: &str, data: &Mat4) -> Result<(), Error>
     {
         let location = self.get_uniform_location(name)?;
-        self.gl.uniform_matrix4fv(location, &mut data.to_slice());
+        self.gl.uniform_matrix4fv(&location, &mut data.to_slice());
         self.gl.unuse_program();
         Ok(())
     }
 
-    fn get_uniform_location(&self, name: &str) -> Result<&crate::gl::UniformLocation, Error>
+    ///
+    /// Sets the uniform with the given name. Returns an error if the uniform is not used in the shader
+    /// or if the type of the value does not match the type of the uniform.
+    ///
+    pub fn set_uniform<T: UniformValue>(&self, name: &str, value: &T) -> Result<(), Error>
+    {
+        let (index, element) = self.find_uniform::<T>(name)?;
+        self.send_uniform(index, element, value);
+        Ok(())
+    }
+
+    ///
+    /// Looks up and type checks the uniform with the given name and returns a handle which can be used with
+    /// [set_uniform_at](#method.set_uniform_at) without any further lookups.
+    ///
+    pub fn uniform<T: UniformValue>(&self, name: &str) -> Result<Uniform<T>, Error>
+    {
+        let (index, element) = self.find_uniform::<T>(name)?;
+        Ok(Uniform {name: name.to_owned(), index, element, generation: self.generation.get(), value_type: PhantomData})
+    }
+
+    ///
+    /// Sets the uniform referred to by the handle. If the program has been relinked since the handle was created,
+    /// the uniform is looked up by name again.
+    ///
+    pub fn set_uniform_at<T: UniformValue>(&self, uniform: &Uniform<T>, value: &T) -> Result<(), Error>
+    {
+        let (index, element) = if uniform.generation == self.generation.get() { (uniform.index, uniform.element) } else { self.find_uniform::<T>(&uniform.name)? };
+        self.send_uniform(index, element, value);
+        Ok(())
+    }
+
+    pub fn attributes(&self) -> Vec<AttributeInfo>
+    {
+        self.reflection.borrow().attributes.clone()
+    }
+
+    pub fn uniforms(&self) -> Vec<UniformInfo>
+    {
+        self.reflection.borrow().uniforms.clone()
+    }
+
+    pub fn uniform_info(&self, name: &str) -> Option<UniformInfo>
+    {
+        let reflection = self.reflection.borrow();
+        reflection.uniform_indices.get(name).map(|(index, _)| reflection.uniforms[*index].clone())
+    }
+
+    pub fn uniform_block_info(&self, block_name: &str) -> Option<UniformBlockInfo>
+    {
+        self.reflection.borrow().uniform_blocks.iter().find(|block| block.name == block_name).cloned()
+    }
+
+    pub fn uniform_blocks(&self) -> Vec<UniformBlockInfo>
+    {
+        self.reflection.borrow().uniform_blocks.clone()
+    }
+
+    fn find_uniform<T: UniformValue>(&self, name: &str) -> Result<(usize, u32), Error>
+    {
+        let reflection = self.reflection.borrow();
+        let (index, element) = reflection.find(name, 1)?;
+        let info = &reflection.uniforms[index];
+        if !T::is_compatible(info.type_) {
+            return Err(Error::FailedToSetUniform {message: format!("Cannot set uniform {} of type {} to a value of type {}",
+                name, glsl_type_name(info.type_), std::any::type_name::<T>())});
+        }
+        Ok((index, element))
+    }
+
+    fn send_uniform<T: UniformValue>(&self, index: usize, element: u32, value: &T)
     {
         self.set_used();
-        let loc = self.uniforms.get(name).ok_or_else(|| Error::FailedToFindUniform {message: format!("Failed to find uniform {}", name)})?;
-        Ok(loc)
+        if let Some(location) = self.reflection.borrow().uniform_locations[index].get(element as usize) {
+            value.send(&self.gl, location);
+        }
+        self.gl.unuse_program();
+    }
+
+    fn get_uniform_location(&self, name: &str) -> Result<crate::gl::UniformLocation, Error>
+    {
+        self.get_uniform_array_location(name, 1)
+    }
+
+    fn get_uniform_array_location(&self, name: &str, count: u32) -> Result<crate::gl::UniformLocation, Error>
+    {
+        self.set_used();
+        let reflection = self.reflection.borrow();
+        let (index, element) = reflection.find(name, count)?;
+        Ok(reflection.uniform_locations[index][element as usize].clone())
     }
 
     pub fn use_texture(&self, texture: &dyn Texture, texture_name: &str) -> Result<(), Error>
@@ -154,12 +368,13 @@ impl Program
     {
         if !self.uniform_blocks.borrow().contains_key(block_name) {
             let mut map = self.uniform_blocks.borrow_mut();
-            let location = self.gl.get_uniform_block_index(&self.id, block_name);
+            let location = self.uniform_block_info(block_name).map(|block| block.index)
+                .unwrap_or_else(|| self.gl.get_uniform_block_index(&self.id.borrow(), block_name));
             let index = map.len() as u32;
             map.insert(block_name.to_owned(), (location, index));
         };
         let (location, index) = self.uniform_blocks.borrow().get(block_name).unwrap().clone();
-        self.gl.uniform_block_binding(&self.id, location, index);
+        self.gl.uniform_block_binding(&self.id.borrow(), location, index);
         buffer.bind(index);
         self.gl.unbind_buffer(consts::UNIFORM_BUFFER);
     }
@@ -172,16 +387,7 @@ impl Program
 
     pub fn use_attribute_float_divisor(&self, buffer: &buffer::VertexBuffer, attribute_name: &str,  divisor: usize) -> Result<(), Error>
     {
-        if buffer.count() > 0 {
-            buffer.bind();
-            let loc = self.location(&attribute_name)?;
-            self.gl.enable_vertex_attrib_array(loc);
-            self.gl.vertex_attrib_pointer(loc, 1, consts::FLOAT, false, 0, 0);
-            self.gl.vertex_attrib_divisor(loc, divisor as u32);
-            self.gl.unbind_buffer(consts::ARRAY_BUFFER);
-            self.gl.unuse_program();
-        }
-        Ok(())
+        self.use_attribute(buffer, attribute_name, &AttributeLayout {divisor: divisor as u32, ..AttributeLayout::new(ComponentType::F32, 1)})
     }
 
     pub fn use_attribute_vec2_float(&self, buffer: &buffer::VertexBuffer, attribute_name: &str) -> Result<(), Error>
@@ -192,16 +398,7 @@ impl Program
 
     pub fn use_attribute_vec2_float_divisor(&self, buffer: &buffer::VertexBuffer, attribute_name: &str, divisor: usize) -> Result<(), Error>
     {
-        if buffer.count() > 0 {
-            buffer.bind();
-            let loc = self.location(&attribute_name)?;
-            self.gl.enable_vertex_attrib_array(loc);
-            self.gl.vertex_attrib_pointer(loc, 2, consts::FLOAT, false, 0, 0);
-            self.gl.vertex_attrib_divisor(loc, divisor as u32);
-            self.gl.unbind_buffer(consts::ARRAY_BUFFER);
-            self.gl.unuse_program();
-        }
-        Ok(())
+        self.use_attribute(buffer, attribute_name, &AttributeLayout {divisor: divisor as u32, ..AttributeLayout::new(ComponentType::F32, 2)})
     }
 
     pub fn use_attribute_vec3_float(&self, buffer: &buffer::VertexBuffer, attribute_name: &str) -> Result<(), Error>
@@ -211,13 +408,62 @@ impl Program
     }
 
     pub fn use_attribute_vec3_float_divisor(&self, buffer: &buffer::VertexBuffer, attribute_name: &str, divisor: usize) -> Result<(), Error>
+    {
+        self.use_attribute(buffer, attribute_name, &AttributeLayout {divisor: divisor as u32, ..AttributeLayout::new(ComponentType::F32, 3)})
+    }
+
+    pub fn use_attribute_vec4_float(&self, buffer: &buffer::VertexBuffer, attribute_name: &str) -> Result<(), Error>
+    {
+        self.use_attribute_vec4_float_divisor(buffer, attribute_name, 0)?;
+        Ok(())
+    }
+
+    pub fn use_attribute_vec4_float_divisor(&self, buffer: &buffer::VertexBuffer, attribute_name: &str, divisor: usize) -> Result<(), Error>
+    {
+        self.use_attribute(buffer, attribute_name, &AttributeLayout {divisor: divisor as u32, ..AttributeLayout::new(ComponentType::F32, 4)})
+    }
+
+    ///
+    /// Uses a buffer of column major 4x4 matrices (16 floats each) as a mat4 attribute, for example instance transformations.
+    ///
+    pub fn use_attribute_mat4_float_divisor(&self, buffer: &buffer::VertexBuffer, attribute_name: &str, divisor: usize) -> Result<(), Error>
+    {
+        let column = AttributeLayout::new(ComponentType::F32, 4);
+        let stride = 4 * column.size_in_bytes();
+        self.use_attribute_columns(buffer, attribute_name, &AttributeLayout {stride, divisor: divisor as u32, ..column}, 4)
+    }
+
+    ///
+    /// Uses the vertex buffer as the source of the attribute with the given name, where the layout describes the component type,
+    /// number of components, normalization, stride, offset and divisor.
+    /// Call this once per attribute with the same buffer to use an interleaved buffer.
+    ///
+    pub fn use_attribute(&self, buffer: &buffer::VertexBuffer, attribute_name: &str, layout: &AttributeLayout) -> Result<(), Error>
+    {
+        self.use_attribute_columns(buffer, attribute_name, layout, 1)
+    }
+
+    fn use_attribute_columns(&self, buffer: &buffer::VertexBuffer, attribute_name: &str, layout: &AttributeLayout, columns: u32) -> Result<(), Error>
     {
         if buffer.count() > 0 {
             buffer.bind();
-            let loc = self.location(&attribute_name)?;
-            self.gl.enable_vertex_attrib_array(loc);
-            self.gl.vertex_attrib_pointer(loc, 3, consts::FLOAT, false, 0, 0);
-            self.gl.vertex_attrib_divisor(loc, divisor as u32);
+            let loc = self.location(attribute_name)?;
+            let integer_attribute = self.reflection.borrow().attributes.iter().find(|attribute| attribute.name == attribute_name)
+                .map(|attribute| is_integer(attribute.type_)).unwrap_or(false);
+            for column in 0..columns {
+                let location = loc + column;
+                let offset = layout.offset + column * layout.size_in_bytes();
+                self.gl.enable_vertex_attrib_array(location);
+                if integer_attribute && layout.component_type.is_integer() {
+                    self.gl.vertex_attrib_i_pointer_with_byte_offset(location, layout.component_count, layout.component_type.gl_type(),
+                                                                     layout.stride, offset);
+                }
+                else {
+                    self.gl.vertex_attrib_pointer_with_byte_offset(location, layout.component_count, layout.component_type.gl_type(),
+                                                                   layout.normalized, layout.stride, offset);
+                }
+                self.gl.vertex_attrib_divisor(location, layout.divisor);
+            }
             self.gl.unbind_buffer(consts::ARRAY_BUFFER);
             self.gl.unuse_program();
         }
@@ -228,10 +474,9 @@ impl Program
     {
         self.set_used();
         self.gl.draw_arrays(consts::TRIANGLES, 0, count);
-        for location in self.vertex_attributes.values() {
-            self.gl.disable_vertex_attrib_array(*location);
-        }
+        self.disable_vertex_attributes();
         self.gl.unuse_program();
+        self.reload_if_modified();
     }
 
     pub fn draw_arrays_instanced(&self, count: u32, instance_count: u32)
@@ -239,10 +484,9 @@ impl Program
         self.set_used();
         self.gl.draw_arrays_instanced(consts::TRIANGLES, 0, count, instance_count);
         self.gl.unbind_buffer(consts::ELEMENT_ARRAY_BUFFER);
-        for location in self.vertex_attributes.values() {
-            self.gl.disable_vertex_attrib_array(*location);
-        }
+        self.disable_vertex_attributes();
         self.gl.unuse_program();
+        self.reload_if_modified();
     }
 
     pub fn draw_elements(&self, element_buffer: &buffer::ElementBuffer)
@@ -254,42 +498,403 @@ impl Program
     {
         self.set_used();
         element_buffer.bind();
-        self.gl.draw_elements(consts::TRIANGLES, count, consts::UNSIGNED_INT, first);
+        self.gl.draw_elements(consts::TRIANGLES, count, element_buffer.data_type(), first);
         self.gl.unbind_buffer(consts::ELEMENT_ARRAY_BUFFER);
 
-        for location in self.vertex_attributes.values() {
-            self.gl.disable_vertex_attrib_array(*location);
-        }
+        self.disable_vertex_attributes();
         self.gl.unuse_program();
+        self.reload_if_modified();
     }
 
     pub fn draw_elements_instanced(&self, element_buffer: &buffer::ElementBuffer, count: u32)
     {
         self.set_used();
         element_buffer.bind();
-        self.gl.draw_elements_instanced(consts::TRIANGLES, element_buffer.count() as u32, consts::UNSIGNED_INT, 0, count);
+        self.gl.draw_elements_instanced(consts::TRIANGLES, element_buffer.count() as u32, element_buffer.data_type(), 0, count);
         self.gl.unbind_buffer(consts::ELEMENT_ARRAY_BUFFER);
-        for location in self.vertex_attributes.values() {
-            self.gl.disable_vertex_attrib_array(*location);
-        }
+        self.disable_vertex_attributes();
         self.gl.unuse_program();
+        self.reload_if_modified();
+    }
+
+    fn disable_vertex_attributes(&self)
+    {
+        for attribute in self.reflection.borrow().attributes.iter() {
+            for i in 0..location_count(attribute.type_) * attribute.size {
+                self.gl.disable_vertex_attrib_array(attribute.location + i);
+            }
+        }
     }
 
     fn location(&self, name: &str) -> Result<u32, Error>
     {
         self.set_used();
-        let location = self.vertex_attributes.get(name).ok_or_else(
+        let location = self.reflection.borrow().attributes.iter().find(|attribute| attribute.name == name).map(|attribute| attribute.location).ok_or_else(
             || Error::FailedToFindAttribute {message: format!("The attribute {} is sent to the shader but never used.", name)})?;
-        Ok(*location)
+        Ok(location)
+    }
+
+    // Relinks the program if one of the shader files has changed on disk since it was linked (requires the shader-hot-reload feature).
+    // If the new source fails to compile or link, the previous program stays active and the error is logged.
+    pub fn reload_if_modified(&self) -> bool
+    {
+        #[cfg(all(feature = "shader-hot-reload", not(target_arch = "wasm32")))]
+        {
+            let sources = self.hot_reload.borrow_mut().modified_sources();
+            if let Some((vertex_shader_source, fragment_shader_source)) = sources {
+                match link(&self.gl, &vertex_shader_source, &fragment_shader_source) {
+                    Ok((id, reflection)) => {
+                        let old_id = self.id.replace(id);
+                        self.gl.delete_program(&old_id);
+                        self.reflection.replace(reflection);
+                        self.generation.set(self.generation.get() + 1);
+                        self.uniform_blocks.borrow_mut().clear();
+                        log::info!("Reloaded shader program");
+                        return true;
+                    },
+                    Err(Error::FailedToCreateShader {message, ..}) | Err(Error::FailedToLinkProgram {message}) => {
+                        log::error!("Failed to reload shader program, keeping the previous version.\n{}", message);
+                    },
+                    Err(e) => {
+                        log::error!("Failed to reload shader program, keeping the previous version.\n{:?}", e);
+                    }
+                }
+            }
+        }
+        false
     }
 
     fn set_used(&self) {
-        self.gl.use_program(&self.id);
+        self.gl.use_program(&self.id.borrow());
     }
 }
 
 impl Drop for Program {
     fn drop(&mut self) {
-        self.gl.delete_program(&self.id);
+        self.gl.delete_program(&self.id.borrow());
+    }
+}
+
+#[cfg(all(feature = "shader-hot-reload", not(target_arch = "wasm32")))]
+struct HotReload {
+    vertex_shader_source: ShaderSource,
+    fragment_shader_source: ShaderSource,
+    last_modified: Option<std::time::SystemTime>,
+    last_check: std::time::Instant
+}
+
+#[cfg(all(feature = "shader-hot-reload", not(target_arch = "wasm32")))]
+impl HotReload {
+    fn new(vertex_shader_source: &ShaderSource, fragment_shader_source: &ShaderSource) -> Self
+    {
+        HotReload { vertex_shader_source: vertex_shader_source.clone(), fragment_shader_source: fragment_shader_source.clone(),
+            last_modified: vertex_shader_source.modified().max(fragment_shader_source.modified()),
+            last_check: std::time::Instant::now() }
+    }
+
+    fn modified_sources(&mut self) -> Option<(ShaderSource, ShaderSource)>
+    {
+        // Checking the file system on every draw call is too expensive
+        if self.last_check.elapsed() < std::time::Duration::from_millis(500) {
+            return None;
+        }
+        self.last_check = std::time::Instant::now();
+
+        let modified = self.vertex_shader_source.modified().max(self.fragment_shader_source.modified());
+        if modified <= self.last_modified {
+            return None;
+        }
+
+        match (self.vertex_shader_source.reload(), self.fragment_shader_source.reload()) {
+            (Ok(vertex_shader_source), Ok(fragment_shader_source)) => {
+                self.last_modified = modified;
+                Some((vertex_shader_source, fragment_shader_source))
+            },
+            // The files are read again at the next check, for example when an editor has not finished writing them
+            (Err(e), _) | (_, Err(e)) => {
+                log::error!("Failed to read shader file: {}", e);
+                None
+            }
+        }
+    }
+}
+
+struct Reflection {
+    attributes: Vec<AttributeInfo>,
+    uniforms: Vec<UniformInfo>,
+    // The location of each array element (one for uniforms which are not arrays and none for uniforms in a uniform block)
+    uniform_locations: Vec<Vec<crate::gl::UniformLocation>>,
+    // Maps a name to the uniform and the array element it refers to.
+    // Arrays can be found both by their base name and by the name of each element, for example 'lights', 'lights[0]' and 'lights[1]'
+    uniform_indices: HashMap<String, (usize, u32)>,
+    uniform_blocks: Vec<UniformBlockInfo>
+}
+
+impl Reflection {
+    fn add_uniform(&mut self, info: UniformInfo, locations: Vec<crate::gl::UniformLocation>)
+    {
+        let index = self.uniforms.len();
+        if let Some(base_name) = info.name.strip_suffix("[0]") {
+            self.uniform_indices.insert(base_name.to_owned(), (index, 0));
+        }
+        for (element, name) in element_names(&info.name, info.size).into_iter().enumerate() {
+            self.uniform_indices.insert(name, (index, element as u32));
+        }
+        self.uniform_locations.push(locations);
+        self.uniforms.push(info);
+    }
+
+    // Finds the uniform and the array element with the given name and checks that the given number of elements,
+    // starting at that element, can be set.
+    fn find(&self, name: &str, count: u32) -> Result<(usize, u32), Error>
+    {
+        let (index, element) = *self.uniform_indices.get(name).ok_or_else(|| Error::FailedToFindUniform {message: format!("Failed to find uniform {}", name)})?;
+        if self.uniforms[index].block.is_some() {
+            return Err(Error::FailedToFindUniform {message: format!("The uniform {} is part of a uniform block and must be set using a uniform buffer", name)});
+        }
+        let location_count = self.uniform_locations[index].len() as u32;
+        if element + count > location_count {
+            return Err(Error::FailedToSetUniform {message: format!("Cannot set {} elements of uniform {} which only has {} elements from that element",
+                count, name, location_count.saturating_sub(element))});
+        }
+        Ok((index, element))
+    }
+}
+
+// The names of the elements of a uniform, where an array is reported by the name of the first element, for example 'lights[0]'
+fn element_names(name: &str, size: u32) -> Vec<String>
+{
+    match name.strip_suffix("[0]") {
+        Some(base_name) => (0..size).map(|element| format!("{}[{}]", base_name, element)).collect(),
+        None => vec![name.to_owned()]
     }
-}
\ No newline at end of file
+}
+
+fn link(gl: &Gl, vertex_shader_source: &ShaderSource, fragment_shader_source: &ShaderSource) -> Result<(crate::gl::Program, Reflection), Error>
+{
+    let header_lines = crate::gl::SHADER_HEADER.matches('\n').count();
+    let vert_shader = gl.create_shader(consts::VERTEX_SHADER)
+        .ok_or(Error::FailedToCreateShader{ shader_type: "Vertex shader".to_string(), message:"Unable to create shader object".to_string() })?;
+    let frag_shader = gl.create_shader(consts::FRAGMENT_SHADER)
+        .ok_or(Error::FailedToCreateShader{ shader_type: "Fragment shader".to_string(), message:"Unable to create shader object".to_string() })?;
+
+    for (shader, source, shader_type) in [(&vert_shader, vertex_shader_source, "Vertex shader"), (&frag_shader, fragment_shader_source, "Fragment shader")].iter() {
+        if !gl.compile_shader(&source.source(), shader) {
+            let log = gl.get_shader_info_log(shader).unwrap_or_default();
+            gl.delete_shader(Some(&vert_shader));
+            gl.delete_shader(Some(&frag_shader));
+            return Err(Error::FailedToCreateShader {shader_type: shader_type.to_string(),
+                message: format!("Failed to compile {}:\n{}", shader_type.to_lowercase(), source.format_log(&log, header_lines))});
+        }
+    }
+
+    let id = gl.create_program();
+    gl.attach_shader(&id, &vert_shader);
+    gl.attach_shader(&id, &frag_shader);
+    let success = gl.link_program(&id);
+
+    if !success {
+        let mut message = "Failed to link shader program:\n".to_string();
+        if let Some(log) = gl.get_program_info_log(&id) {message = format!("{}\nLink error: {}", message, log);}
+        if let Some(log) = gl.get_shader_info_log(&vert_shader) {message = format!("{}\nVertex shader error: {}", message, vertex_shader_source.format_log(&log, header_lines));}
+        if let Some(log) = gl.get_shader_info_log(&frag_shader) {message = format!("{}\nFragment shader error: {}", message, fragment_shader_source.format_log(&log, header_lines));}
+        gl.delete_shader(Some(&vert_shader));
+        gl.delete_shader(Some(&frag_shader));
+        gl.delete_program(&id);
+        return Err(Error::FailedToLinkProgram {message});
+    }
+
+    gl.detach_shader(&id, &vert_shader);
+    gl.detach_shader(&id, &frag_shader);
+    gl.delete_shader(Some(&vert_shader));
+    gl.delete_shader(Some(&frag_shader));
+
+    let reflection = reflect(gl, &id);
+    Ok((id, reflection))
+}
+
+fn reflect(gl: &Gl, id: &crate::gl::Program) -> Reflection
+{
+    // Vertex attributes
+    let num_attribs = gl.get_program_parameter(id, consts::ACTIVE_ATTRIBUTES);
+    let mut attributes = Vec::new();
+    for i in 0..num_attribs {
+        let info = gl.get_active_attrib(id, i);
+        if let Some(location) = gl.get_attrib_location(id, &info.name()) {
+            attributes.push(AttributeInfo {name: info.name(), location, type_: info.type_(), size: info.size() as u32});
+        }
+    }
+
+    // Uniform blocks
+    let num_blocks = gl.get_program_parameter(id, consts::ACTIVE_UNIFORM_BLOCKS);
+    let mut uniform_blocks = Vec::new();
+    for i in 0..num_blocks {
+        uniform_blocks.push(UniformBlockInfo {name: gl.get_active_uniform_block_name(id, i), index: i,
+            size_in_bytes: gl.get_active_uniform_block_parameter(id, i, consts::UNIFORM_BLOCK_DATA_SIZE),
+            members: Vec::new()});
+    }
+
+    // Uniforms
+    let num_uniforms = gl.get_program_parameter(id, consts::ACTIVE_UNIFORMS);
+    let all_indices: Vec<u32> = (0..num_uniforms).collect();
+    let block_indices = gl.get_active_uniforms_parameter(id, &all_indices, consts::UNIFORM_BLOCK_INDEX);
+    let offsets = gl.get_active_uniforms_parameter(id, &all_indices, consts::UNIFORM_OFFSET);
+    let array_strides = gl.get_active_uniforms_parameter(id, &all_indices, consts::UNIFORM_ARRAY_STRIDE);
+    let matrix_strides = gl.get_active_uniforms_parameter(id, &all_indices, consts::UNIFORM_MATRIX_STRIDE);
+    let mut reflection = Reflection {attributes, uniforms: Vec::new(), uniform_locations: Vec::new(), uniform_indices: HashMap::new(), uniform_blocks: Vec::new()};
+    for i in 0..num_uniforms as usize {
+        let info = gl.get_active_uniform(id, i as u32);
+        let name = info.name();
+        let block = block_indices.get(i).cloned().filter(|index| *index >= 0).map(|index| index as usize);
+        if let Some(block) = block {
+            uniform_blocks[block].members.push(UniformBlockMember {name: name.clone(), type_: info.type_(), size: info.size() as u32,
+                offset: offsets[i] as u32, array_stride: array_strides[i] as u32, matrix_stride: matrix_strides[i] as u32});
+        }
+
+        // The elements of an array have consecutive locations on most drivers, but that is not guaranteed, so each element is looked up
+        let locations = if block.is_none() {
+            element_names(&name, info.size() as u32).iter().map_while(|element_name| gl.get_uniform_location(id, element_name)).collect()
+        } else { Vec::new() };
+        reflection.add_uniform(UniformInfo {name, type_: info.type_(), size: info.size() as u32, block}, locations);
+    }
+    reflection.uniform_blocks = uniform_blocks;
+    reflection
+}
+
+fn flatten_vec3(data: &[Vec3]) -> Vec<f32>
+{
+    data.iter().flat_map(|v| [v.x, v.y, v.z]).collect()
+}
+
+fn is_integer(type_: u32) -> bool
+{
+    matches!(type_, consts::INT | consts::INT_VEC2 | consts::INT_VEC3 | consts::INT_VEC4 |
+        consts::UNSIGNED_INT | consts::UNSIGNED_INT_VEC2 | consts::UNSIGNED_INT_VEC3 | consts::UNSIGNED_INT_VEC4)
+}
+
+// The number of consecutive attribute locations taken up by an attribute of the given type
+fn location_count(type_: u32) -> u32
+{
+    match type_ {
+        consts::FLOAT_MAT2 => 2,
+        consts::FLOAT_MAT3 => 3,
+        consts::FLOAT_MAT4 => 4,
+        _ => 1
+    }
+}
+
+fn is_sampler(type_: u32) -> bool
+{
+    matches!(type_, consts::SAMPLER_2D | consts::SAMPLER_3D | consts::SAMPLER_CUBE | consts::SAMPLER_2D_ARRAY |
+        consts::SAMPLER_2D_SHADOW | consts::SAMPLER_2D_ARRAY_SHADOW | consts::SAMPLER_CUBE_SHADOW |
+        consts::INT_SAMPLER_2D | consts::INT_SAMPLER_3D | consts::INT_SAMPLER_CUBE | consts::INT_SAMPLER_2D_ARRAY |
+        consts::UNSIGNED_INT_SAMPLER_2D | consts::UNSIGNED_INT_SAMPLER_3D | consts::UNSIGNED_INT_SAMPLER_CUBE | consts::UNSIGNED_INT_SAMPLER_2D_ARRAY)
+}
+
+///
+/// Returns the GLSL name of a uniform or attribute type as reported by the reflection data, for example 'vec3' for FLOAT_VEC3.
+///
+pub fn glsl_type_name(type_: u32) -> &'static str
+{
+    match type_ {
+        consts::FLOAT => "float",
+        consts::FLOAT_VEC2 => "vec2",
+        consts::FLOAT_VEC3 => "vec3",
+        consts::FLOAT_VEC4 => "vec4",
+        consts::INT => "int",
+        consts::INT_VEC2 => "ivec2",
+        consts::INT_VEC3 => "ivec3",
+        consts::INT_VEC4 => "ivec4",
+        consts::UNSIGNED_INT => "uint",
+        consts::UNSIGNED_INT_VEC2 => "uvec2",
+        consts::UNSIGNED_INT_VEC3 => "uvec3",
+        consts::UNSIGNED_INT_VEC4 => "uvec4",
+        consts::BOOL => "bool",
+        consts::BOOL_VEC2 => "bvec2",
+        consts::BOOL_VEC3 => "bvec3",
+        consts::BOOL_VEC4 => "bvec4",
+        consts::FLOAT_MAT2 => "mat2",
+        consts::FLOAT_MAT3 => "mat3",
+        consts::FLOAT_MAT4 => "mat4",
+        consts::SAMPLER_2D => "sampler2D",
+        consts::SAMPLER_3D => "sampler3D",
+        consts::SAMPLER_CUBE => "samplerCube",
+        consts::SAMPLER_2D_ARRAY => "sampler2DArray",
+        consts::SAMPLER_2D_SHADOW => "sampler2DShadow",
+        consts::SAMPLER_2D_ARRAY_SHADOW => "sampler2DArrayShadow",
+        consts::SAMPLER_CUBE_SHADOW => "samplerCubeShadow",
+        _ => "unknown"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reflection_with_uniforms(uniforms: &[(&str, u32, Option<usize>)]) -> Reflection
+    {
+        let mut reflection = Reflection {attributes: Vec::new(), uniforms: Vec::new(), uniform_locations: Vec::new(), uniform_indices: HashMap::new(), uniform_blocks: Vec::new()};
+        let mut location = 0;
+        for (name, size, block) in uniforms {
+            let locations = if block.is_none() { (0..*size).map(|i| location + i).collect() } else { Vec::new() };
+            location += size;
+            reflection.add_uniform(UniformInfo {name: name.to_string(), type_: consts::FLOAT_VEC3, size: *size, block: *block}, locations);
+        }
+        reflection
+    }
+
+    #[test]
+    fn element_names_of_arrays()
+    {
+        assert_eq!(element_names("color", 1), vec!["color"]);
+        assert_eq!(element_names("lights[0]", 3), vec!["lights[0]", "lights[1]", "lights[2]"]);
+        assert_eq!(element_names("shLight.coefficients[0]", 2), vec!["shLight.coefficients[0]", "shLight.coefficients[1]"]);
+    }
+
+    #[test]
+    fn find_array_elements()
+    {
+        let reflection = reflection_with_uniforms(&[("color", 1, None), ("vertices[0]", 4, None)]);
+        let location = |name: &str| { let (index, element) = reflection.find(name, 1).unwrap(); reflection.uniform_locations[index][element as usize] };
+        assert_eq!(location("color"), 0);
+        assert_eq!(location("vertices"), 1);
+        assert_eq!(location("vertices[0]"), 1);
+        assert_eq!(location("vertices[1]"), 2);
+        assert_eq!(location("vertices[3]"), 4);
+        assert!(reflection.find("vertices[4]", 1).is_err());
+    }
+
+    #[test]
+    fn find_checks_array_size()
+    {
+        let reflection = reflection_with_uniforms(&[("color", 1, None), ("vertices[0]", 4, None), ("member", 1, Some(0))]);
+        assert!(reflection.find("vertices", 4).is_ok());
+        assert!(reflection.find("vertices[2]", 2).is_ok());
+        assert!(reflection.find("vertices", 5).is_err());
+        assert!(reflection.find("vertices[1]", 4).is_err());
+        assert!(reflection.find("color", 2).is_err());
+        assert!(reflection.find("member", 1).is_err());
+    }
+
+    #[test]
+    fn vec3_array_upload()
+    {
+        let reflection = reflection_with_uniforms(&[("areaLightVertices[0]", 12, None)]);
+        let vertices = [vec3(1.0, 2.0, 3.0), vec3(4.0, 5.0, 6.0), vec3(7.0, 8.0, 9.0)];
+        assert_eq!(reflection.find("areaLightVertices", vertices.len() as u32).unwrap(), (0, 0));
+        assert_eq!(reflection.find("areaLightVertices[9]", vertices.len() as u32).unwrap(), (0, 9));
+        assert!(reflection.find("areaLightVertices[10]", vertices.len() as u32).is_err());
+        assert_eq!(flatten_vec3(&vertices), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+    }
+
+    #[test]
+    fn array_in_struct()
+    {
+        // GL reports an array in a struct as one uniform named by the first element, like the coefficients of the SH light
+        let reflection = reflection_with_uniforms(&[("shLight.coefficients[0]", 9, None), ("shLight.intensity", 1, None)]);
+        assert_eq!(reflection.find("shLight.coefficients", 9).unwrap(), (0, 0));
+        assert_eq!(reflection.find("shLight.coefficients[8]", 1).unwrap(), (0, 8));
+        assert_eq!(reflection.find("shLight.intensity", 1).unwrap(), (1, 0));
+        assert_eq!(reflection.uniform_locations[1], vec![9]);
+    }
+}
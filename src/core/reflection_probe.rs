@@ -0,0 +1,116 @@
+use crate::core::*;
+
+///
+/// Defines when a [ReflectionProbe] renders the scene, see [ReflectionProbe::update].
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ProbeUpdateMode {
+    /// The scene is rendered the first time the probe is updated.
+    Once,
+    /// The scene is rendered each time the probe is updated.
+    EveryFrame,
+    /// The scene is rendered the first time the probe is updated and after each call to [ReflectionProbe::request_update].
+    OnDemand
+}
+
+///
+/// Captures the scene surrounding a position in a cube map, which meshes can then sample for glossy reflections through the
+/// [environment light](EnvironmentLight) computed from the captured cube map.
+///
+pub struct ReflectionProbe {
+    gl: Gl,
+    position: Vec3,
+    z_near: f32,
+    z_far: f32,
+    update_mode: ProbeUpdateMode,
+    needs_update: bool,
+    cube_map: TextureCubeMap,
+    environment_light: EnvironmentLight
+}
+
+impl ReflectionProbe {
+
+    ///
+    /// Creates a new reflection probe at the given position, capturing the scene in a cube map with the given side size
+    /// and using the near and far plane for the cameras used when capturing.
+    ///
+    pub fn new(gl: &Gl, position: &Vec3, size: usize, z_near: f32, z_far: f32, update_mode: ProbeUpdateMode) -> Result<ReflectionProbe, Error>
+    {
+        let cube_map = TextureCubeMap::new(gl, size, size, Interpolation::Linear, Interpolation::Linear, Some(Interpolation::Linear),
+                                           Wrapping::ClampToEdge, Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::RGBA16F)?;
+        let environment_light = EnvironmentLight::new(gl, 1.0, &cube_map)?;
+        Ok(ReflectionProbe { gl: gl.clone(), position: *position, z_near, z_far, update_mode, needs_update: true, cube_map, environment_light })
+    }
+
+    ///
+    /// Renders the scene into the cube map if the update mode requires it, in which case the render closure is called once for each cube map side
+    /// with a camera looking in the direction of that side, and the environment light is recomputed.
+    /// Note that objects which sample this probe should not be rendered in the closure.
+    /// Returns whether the scene was rendered.
+    ///
+    pub fn update<F: FnMut(&Camera) -> Result<(), Error>>(&mut self, render_scene: F) -> Result<bool, Error>
+    {
+        if !self.needs_update && self.update_mode != ProbeUpdateMode::EveryFrame {
+            return Ok(false);
+        }
+        RenderTarget::write_to_cube_map(&self.gl, &self.position, self.z_near, self.z_far, Some(&vec4(0.0, 0.0, 0.0, 1.0)),
+                                        &self.cube_map, render_scene)?;
+        self.environment_light.update(&self.cube_map)?;
+        self.needs_update = false;
+        Ok(true)
+    }
+
+    ///
+    /// Makes the next call to [update](Self::update) render the scene, which is only needed when the update mode is [ProbeUpdateMode::OnDemand].
+    ///
+    pub fn request_update(&mut self)
+    {
+        self.needs_update = true;
+    }
+
+    pub fn update_mode(&self) -> ProbeUpdateMode
+    {
+        self.update_mode
+    }
+
+    pub fn set_update_mode(&mut self, update_mode: ProbeUpdateMode)
+    {
+        self.update_mode = update_mode;
+    }
+
+    pub fn position(&self) -> &Vec3
+    {
+        &self.position
+    }
+
+    ///
+    /// Moves the probe, the scene is captured from the new position the next time the probe is updated.
+    ///
+    pub fn set_position(&mut self, position: &Vec3)
+    {
+        self.position = *position;
+        self.needs_update = true;
+    }
+
+    ///
+    /// The cube map containing the scene as seen from the probe position.
+    ///
+    pub fn cube_map(&self) -> &TextureCubeMap
+    {
+        &self.cube_map
+    }
+
+    ///
+    /// The environment light computed from the captured cube map, which can be used to render meshes with reflections of the surrounding scene,
+    /// for example with `PhongForwardMesh::render_with_environment`.
+    ///
+    pub fn environment_light(&self) -> &EnvironmentLight
+    {
+        &self.environment_light
+    }
+
+    pub fn environment_light_mut(&mut self) -> &mut EnvironmentLight
+    {
+        &mut self.environment_light
+    }
+}
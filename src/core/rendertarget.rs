@@ -90,6 +90,68 @@ impl RenderTarget
         Ok(())
     }
 
+    ///
+    /// Renders to one side of a cube map, where the sides are ordered right, left, top, bottom, front, back (positive x, negative x, positive y, negative y, positive z, negative z).
+    /// The depth texture, if any, must have the same size as the cube map side at the given mip level.
+    /// The mip maps are generated after rendering to the first mip level, so when rendering to other mip levels, the first level should be rendered first.
+    ///
+    pub fn write_to_cube_map_side<F: FnOnce() -> Result<(), Error>>(gl: &Gl, x: i32, y: i32, width: usize, height: usize,
+                 clear_color: Option<&Vec4>, clear_depth: Option<f32>,
+                 color_texture: Option<&TextureCubeMap>, depth_texture: Option<&Texture2D>, side: usize, mip_level: usize,
+                 render: F) -> Result<(), Error>
+    {
+        if side >= 6 {
+            Err(Error::FailedToCreateFramebuffer {message: format!("A cube map has 6 sides, not {}", side + 1)})?
+        }
+        gl.viewport(x, y, width, height);
+        let id = RenderTarget::new_framebuffer(gl, if color_texture.is_some() {1} else {0})?;
+
+        if let Some(color_texture) = color_texture {
+            color_texture.bind_as_color_target(side, 0, mip_level);
+        }
+
+        if let Some(depth_texture) = depth_texture {
+            depth_texture.bind_as_depth_target();
+        }
+
+        #[cfg(feature = "debug")]
+        {
+            gl.check_framebuffer_status().or_else(|message| Err(Error::FailedToCreateFramebuffer {message}))?;
+        }
+        RenderTarget::clear(gl, clear_color, clear_depth);
+
+        render()?;
+
+        gl.delete_framebuffer(Some(&id));
+
+        if let Some(color_texture) = color_texture {
+            if mip_level == 0 {
+                color_texture.generate_mip_maps();
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Renders the scene to all six sides of the color cube map as seen from the given position, that is, the render closure is called once for each side
+    /// with a camera with a 90 degree field of view looking in the direction of that side. The depth is rendered to a temporary depth texture.
+    ///
+    pub fn write_to_cube_map<F: FnMut(&Camera) -> Result<(), Error>>(gl: &Gl, position: &Vec3, z_near: f32, z_far: f32, clear_color: Option<&Vec4>,
+                 color_texture: &TextureCubeMap, mut render: F) -> Result<(), Error>
+    {
+        let depth_texture = Texture2D::new(gl, color_texture.width, color_texture.height, Interpolation::Nearest, Interpolation::Nearest, None,
+                                           Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::Depth32F)?;
+        let directions = [(vec3(1.0, 0.0, 0.0), vec3(0.0, -1.0, 0.0)), (vec3(-1.0, 0.0, 0.0), vec3(0.0, -1.0, 0.0)),
+            (vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 1.0)), (vec3(0.0, -1.0, 0.0), vec3(0.0, 0.0, -1.0)),
+            (vec3(0.0, 0.0, 1.0), vec3(0.0, -1.0, 0.0)), (vec3(0.0, 0.0, -1.0), vec3(0.0, -1.0, 0.0))];
+        for (side, (direction, up)) in directions.iter().enumerate() {
+            let camera = Camera::new_perspective(gl, *position, position + direction, *up, degrees(90.0), 1.0, z_near, z_far);
+            RenderTarget::write_to_cube_map_side(gl, 0, 0, color_texture.width, color_texture.height, clear_color, Some(1.0),
+                                                 Some(color_texture), Some(&depth_texture), side, 0, || render(&camera))?;
+        }
+        Ok(())
+    }
+
     pub fn write_to_color_array<F: FnOnce() -> Result<(), Error>>(gl: &Gl, x: i32, y: i32, width: usize, height: usize,
                                 clear_color: Option<&Vec4>,
                        color_texture_array: Option<&Texture2DArray>,
@@ -196,7 +258,8 @@ impl RenderTarget
         Ok(id)
     }
 
-    fn clear(gl: &Gl, clear_color: Option<&Vec4>, clear_depth: Option<f32>) {
+    pub(crate) fn clear(gl: &Gl, clear_color: Option<&Vec4>, clear_depth: Option<f32>) {
+        let clear_depth = clear_depth.map(|depth| if is_depth_reversed() { 1.0 - depth } else { depth });
         if let Some(color) = clear_color {
             if let Some(depth) = clear_depth {
                 gl.clear_color(color.x, color.y, color.z, color.w);
@@ -0,0 +1,215 @@
+use std::path::{Path, PathBuf};
+
+// Creates a shader segment from a file next to the calling source file.
+// The source is embedded with include_str!, but the segment also remembers where the file is on disk, so it can be reloaded.
+macro_rules! shader_segment {
+    ($path:expr) => {
+        $crate::ShaderSegment::from_file(include_str!($path),
+            &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(file!()).parent().unwrap().join($path))
+    };
+}
+
+#[derive(Clone)]
+pub struct ShaderSegment {
+    pub name: String,
+    pub source: String,
+    pub path: Option<PathBuf>
+}
+
+impl ShaderSegment
+{
+    pub fn new(name: &str, source: &str) -> Self
+    {
+        ShaderSegment {name: name.to_owned(), source: source.to_owned(), path: None}
+    }
+
+    pub fn from_file(source: &str, path: &Path) -> Self
+    {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("unknown");
+        ShaderSegment {name: name.to_owned(), source: source.to_owned(), path: Some(path.to_path_buf())}
+    }
+
+    fn line_count(&self) -> usize
+    {
+        self.source.matches('\n').count() + 1
+    }
+}
+
+#[derive(Clone)]
+pub struct ShaderSource {
+    segments: Vec<ShaderSegment>
+}
+
+impl ShaderSource
+{
+    pub fn new(segments: &[(&str, &str)]) -> Self
+    {
+        ShaderSource {segments: segments.iter().map(|(name, source)| ShaderSegment::new(name, source)).collect()}
+    }
+
+    pub fn from_segments(segments: Vec<ShaderSegment>) -> Self
+    {
+        ShaderSource {segments}
+    }
+
+    pub fn segments(&self) -> &[ShaderSegment]
+    {
+        &self.segments
+    }
+
+    pub fn source(&self) -> String
+    {
+        self.segments.iter().map(|segment| segment.source.as_str()).collect::<Vec<&str>>().join("\n")
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn modified(&self) -> Option<std::time::SystemTime>
+    {
+        self.segments.iter().filter_map(|segment| segment.path.as_ref())
+            .filter_map(|path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok())
+            .max()
+    }
+
+    // Reads the segments which originate from a file from disk again.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload(&self) -> std::io::Result<ShaderSource>
+    {
+        let mut segments = Vec::new();
+        for segment in self.segments.iter() {
+            let source = if let Some(ref path) = segment.path { std::fs::read_to_string(path)? } else { segment.source.clone() };
+            segments.push(ShaderSegment {name: segment.name.clone(), source, path: segment.path.clone()});
+        }
+        Ok(ShaderSource {segments})
+    }
+
+    // Maps a 1-based line number in the concatenated source to the segment name and the 1-based line in that segment.
+    pub fn locate(&self, line: usize) -> Option<(&str, usize)>
+    {
+        self.find(line).map(|(segment, local_line)| (segment.name.as_str(), local_line))
+    }
+
+    pub fn line(&self, line: usize) -> Option<&str>
+    {
+        let (segment, local_line) = self.find(line)?;
+        segment.source.lines().nth(local_line - 1).or(Some(""))
+    }
+
+    // Rewrites a compiler info log so that every message refers to the original segment and line and shows the offending source line.
+    // The header lines are the lines the GL backend prepends to the source before compiling (for example the #version directive).
+    pub fn format_log(&self, log: &str, header_lines: usize) -> String
+    {
+        let mut result = String::new();
+        for log_line in log.lines().map(|l| l.trim_end()).filter(|l| !l.trim().is_empty()) {
+            let location = parse_line_number(log_line)
+                .and_then(|line| if line > header_lines {Some(line - header_lines)} else {None});
+            match location.and_then(|line| self.locate(line).map(|(name, local_line)| (line, name, local_line))) {
+                Some((line, name, local_line)) => {
+                    result.push_str(&format!("{}:{}: {}\n", name, local_line, log_line.trim()));
+                    result.push_str(&format!("{:>6} | {}\n", local_line, self.line(line).unwrap_or("").trim_end()));
+                },
+                None => {
+                    result.push_str(log_line.trim());
+                    result.push('\n');
+                }
+            }
+        }
+        result
+    }
+
+    fn find(&self, line: usize) -> Option<(&ShaderSegment, usize)>
+    {
+        let mut first_line = 1;
+        for segment in self.segments.iter() {
+            let count = segment.line_count();
+            if line >= first_line && line < first_line + count {
+                return Some((segment, line - first_line + 1));
+            }
+            first_line += count;
+        }
+        None
+    }
+}
+
+// Finds the line number in a single line of a shader info log.
+// Handles the common driver formats: '0(12) : error ...' (Nvidia), '0:12(5): error: ...' (Mesa) and 'ERROR: 0:12: ...' (AMD, ANGLE, WebGL).
+pub fn parse_line_number(log_line: &str) -> Option<usize>
+{
+    let bytes = log_line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i].is_ascii_digit() && (i == 0 || !bytes[i-1].is_ascii_alphanumeric()) {
+            let mut j = i;
+            while j < bytes.len() && bytes[j].is_ascii_digit() { j += 1; }
+            if j + 1 < bytes.len() && (bytes[j] == b'(' || bytes[j] == b':') && bytes[j+1].is_ascii_digit() {
+                let start = j + 1;
+                let mut end = start;
+                while end < bytes.len() && bytes[end].is_ascii_digit() { end += 1; }
+                let terminated = bytes[j] == b':' || (end < bytes.len() && bytes[end] == b')');
+                if terminated {
+                    return log_line[start..end].parse::<usize>().ok();
+                }
+            }
+            i = j;
+        }
+        else {
+            i += 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> ShaderSource
+    {
+        ShaderSource::new(&[("shared.frag", "uniform float a;\nuniform float b;"), ("main.frag", "void main()\n{\n    x = 1.0;\n}")])
+    }
+
+    #[test]
+    fn parse_nvidia_line_number()
+    {
+        assert_eq!(parse_line_number("0(12) : error C0000: syntax error, unexpected identifier"), Some(12));
+    }
+
+    #[test]
+    fn parse_mesa_line_number()
+    {
+        assert_eq!(parse_line_number("0:12(3): error: `x' undeclared"), Some(12));
+    }
+
+    #[test]
+    fn parse_amd_line_number()
+    {
+        assert_eq!(parse_line_number("ERROR: 0:12: 'x' : undeclared identifier"), Some(12));
+    }
+
+    #[test]
+    fn parse_line_without_location()
+    {
+        assert_eq!(parse_line_number("ERROR: 1 compilation errors.  No code generated."), None);
+        assert_eq!(parse_line_number("error in vec3(1.0)"), None);
+    }
+
+    #[test]
+    fn locate_across_segment_boundaries()
+    {
+        let source = source();
+        assert_eq!(source.locate(1), Some(("shared.frag", 1)));
+        assert_eq!(source.locate(2), Some(("shared.frag", 2)));
+        assert_eq!(source.locate(3), Some(("main.frag", 1)));
+        assert_eq!(source.locate(6), Some(("main.frag", 4)));
+        assert_eq!(source.locate(7), None);
+        assert_eq!(source.locate(0), None);
+        assert_eq!(source.line(5), Some("    x = 1.0;"));
+    }
+
+    #[test]
+    fn format_log_subtracts_header_lines()
+    {
+        let log = "0:6(5): error: `x' undeclared\nERROR: 0:1: '#version' : bad version\n\nlink failed";
+        assert_eq!(source().format_log(log, 1),
+                   "main.frag:3: 0:6(5): error: `x' undeclared\n     3 |     x = 1.0;\nERROR: 0:1: '#version' : bad version\nlink failed\n");
+    }
+}
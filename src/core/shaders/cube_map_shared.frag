@@ -0,0 +1,13 @@
+uniform int side;
+
+// The direction which is sampled at the given uv coordinates on a side of a cube map
+vec3 cube_map_direction(vec2 uv)
+{
+    vec2 st = 2.0 * uv - 1.0;
+    if(side == 0) return vec3(1.0, -st.y, -st.x);
+    if(side == 1) return vec3(-1.0, -st.y, st.x);
+    if(side == 2) return vec3(st.x, 1.0, st.y);
+    if(side == 3) return vec3(st.x, -1.0, -st.y);
+    if(side == 4) return vec3(st.x, -st.y, 1.0);
+    return vec3(-st.x, -st.y, -1.0);
+}
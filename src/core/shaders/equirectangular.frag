@@ -0,0 +1,15 @@
+uniform sampler2D equirectangularMap;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+const float PI = 3.14159265359;
+
+void main()
+{
+    vec3 direction = normalize(cube_map_direction(uv));
+    // The first row of the panorama is the top, which is at v = 0
+    vec2 equirectangular_uv = vec2(atan(direction.z, direction.x) / (2.0 * PI) + 0.5, 0.5 - asin(direction.y) / PI);
+    color = vec4(texture(equirectangularMap, equirectangular_uv).rgb, 1.0);
+}
@@ -0,0 +1,31 @@
+uniform samplerCube environmentMap;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+const float PI = 3.14159265359;
+
+// Integrates the incoming light over the hemisphere around the normal, weighted by the cosine of the angle to the normal
+void main()
+{
+    vec3 normal = normalize(cube_map_direction(uv));
+    vec3 up = abs(normal.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+    vec3 right = normalize(cross(up, normal));
+    up = cross(normal, right);
+
+    vec3 irradiance = vec3(0.0);
+    float sample_count = 0.0;
+    const float sample_delta = 0.05;
+    for(float phi = 0.0; phi < 2.0 * PI; phi += sample_delta)
+    {
+        for(float theta = 0.0; theta < 0.5 * PI; theta += sample_delta)
+        {
+            vec3 tangent_sample = vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            vec3 sample_direction = tangent_sample.x * right + tangent_sample.y * up + tangent_sample.z * normal;
+            irradiance += texture(environmentMap, sample_direction).rgb * cos(theta) * sin(theta);
+            sample_count++;
+        }
+    }
+    color = vec4(PI * irradiance / sample_count, 1.0);
+}
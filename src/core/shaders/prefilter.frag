@@ -0,0 +1,54 @@
+uniform samplerCube environmentMap;
+uniform float roughness;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+const float PI = 3.14159265359;
+
+float radical_inverse(uint bits)
+{
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return float(bits) * 2.3283064365386963e-10;
+}
+
+vec3 importance_sample_ggx(vec2 xi, vec3 normal)
+{
+    float a = roughness * roughness;
+    float phi = 2.0 * PI * xi.x;
+    float cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    float sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+    vec3 h = vec3(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta);
+
+    vec3 up = abs(normal.z) < 0.999 ? vec3(0.0, 0.0, 1.0) : vec3(1.0, 0.0, 0.0);
+    vec3 tangent = normalize(cross(up, normal));
+    vec3 bitangent = cross(normal, tangent);
+    return normalize(tangent * h.x + bitangent * h.y + normal * h.z);
+}
+
+// Convolves the environment with the GGX distribution, assuming that the view direction is equal to the normal and the reflection direction
+void main()
+{
+    vec3 normal = normalize(cube_map_direction(uv));
+    const uint sample_count = 256u;
+    vec3 prefiltered = vec3(0.0);
+    float total_weight = 0.0;
+    for(uint i = 0u; i < sample_count; i++)
+    {
+        vec2 xi = vec2(float(i) / float(sample_count), radical_inverse(i));
+        vec3 h = importance_sample_ggx(xi, normal);
+        vec3 l = normalize(2.0 * dot(normal, h) * h - normal);
+        float n_dot_l = dot(normal, l);
+        if(n_dot_l > 0.0)
+        {
+            prefiltered += texture(environmentMap, l).rgb * n_dot_l;
+            total_weight += n_dot_l;
+        }
+    }
+    color = vec4(prefiltered / total_weight, 1.0);
+}
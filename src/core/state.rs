@@ -1,5 +1,6 @@
 use crate::gl::consts;
 use crate::gl::Gl;
+use crate::core::Viewport;
 
 #[derive(PartialEq)]
 pub enum BlendType {
@@ -88,6 +89,16 @@ pub enum DepthTestType {
 
 pub fn depth_test(gl: &Gl, depth_test_type: DepthTestType)
 {
+    // With a reversed depth, closer fragments have a larger depth, so the comparison is flipped
+    let depth_test_type = if is_depth_reversed() {
+        match depth_test_type {
+            DepthTestType::Less => DepthTestType::Greater,
+            DepthTestType::LessOrEqual => DepthTestType::GreaterOrEqual,
+            DepthTestType::Greater => DepthTestType::Less,
+            DepthTestType::GreaterOrEqual => DepthTestType::LessOrEqual,
+            depth_test_type => depth_test_type
+        }
+    } else { depth_test_type };
     unsafe {
         static mut CURRENT: DepthTestType = DepthTestType::None;
         if depth_test_type != CURRENT
@@ -131,6 +142,45 @@ pub fn depth_test(gl: &Gl, depth_test_type: DepthTestType)
     }
 }
 
+static mut DEPTH_REVERSED: bool = false;
+static mut CLIP_DEPTH_ZERO_TO_ONE: bool = false;
+
+///
+/// Reverses the depth, so that the near plane has depth 1 and the far plane depth 0, which together with a floating point depth buffer, like [Format::Depth32F](crate::Format::Depth32F),
+/// distributes the precision much more evenly over the distance and thereby avoids z-fighting in large scenes.
+/// When the depth is reversed, the comparison given to [depth_test] and the depth that render targets are cleared with are flipped automatically,
+/// so for example [DepthTestType::LessOrEqual] still keeps the closest fragment and clearing with depth 1 still clears to the far plane.
+/// This must be set before the cameras are created, since the projection of a camera depends on it, see [Camera::is_depth_reversed](crate::Camera::is_depth_reversed).
+/// Where supported, the depth range of the clip space is also changed to [0, 1], since most of the improved precision is otherwise lost; this is not possible on web.
+///
+pub fn reversed_depth(gl: &Gl, reversed: bool)
+{
+    let zero_to_one = reversed && gl.supports_clip_control();
+    unsafe {
+        if zero_to_one != CLIP_DEPTH_ZERO_TO_ONE {
+            gl.set_clip_depth_zero_to_one(zero_to_one);
+            CLIP_DEPTH_ZERO_TO_ONE = zero_to_one;
+        }
+        DEPTH_REVERSED = reversed;
+    }
+}
+
+///
+/// Whether the depth is reversed, see [reversed_depth].
+///
+pub fn is_depth_reversed() -> bool
+{
+    unsafe { DEPTH_REVERSED }
+}
+
+///
+/// Whether the depth range of the clip space is [0, 1] instead of [-1, 1], see [reversed_depth].
+///
+pub fn is_clip_depth_zero_to_one() -> bool
+{
+    unsafe { CLIP_DEPTH_ZERO_TO_ONE }
+}
+
 pub fn depth_write(gl: &Gl, enable: bool)
 {
     unsafe {
@@ -141,4 +191,27 @@ pub fn depth_write(gl: &Gl, enable: bool)
             CURRENTLY_ENABLED = enable;
         }
     }
-}
\ No newline at end of file
+}
+
+///
+/// Restricts all rendering, including clearing, to the given viewport or removes the restriction if no viewport is given.
+///
+pub fn scissor(gl: &Gl, viewport: Option<&Viewport>)
+{
+    unsafe {
+        static mut CURRENTLY_ENABLED: bool = false;
+        if let Some(viewport) = viewport {
+            gl.scissor(viewport.x, viewport.y, viewport.width, viewport.height);
+        }
+        if viewport.is_some() != CURRENTLY_ENABLED
+        {
+            if viewport.is_some() {
+                gl.enable(consts::SCISSOR_TEST);
+            }
+            else {
+                gl.disable(consts::SCISSOR_TEST);
+            }
+            CURRENTLY_ENABLED = viewport.is_some();
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use crate::core::Error;
 use crate::gl::Gl;
 use crate::gl::consts;
-use crate::Image;
+use crate::{Image, HDRImage};
+use crate::core::CompressedImage;
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub enum Interpolation {
@@ -21,13 +22,35 @@ pub enum Format {
     R8 = consts::R8 as isize,
     R32F = consts::R32F as isize,
     RGB8 = consts::RGB8 as isize,
+    RGB16F = consts::RGB16F as isize,
     RGB32F = consts::RGB32F as isize,
     RGBA4 = consts::RGBA4 as isize,
     RGBA8 = consts::RGBA8 as isize,
+    SRGB8 = consts::SRGB8 as isize,
+    SRGB8Alpha8 = consts::SRGB8_ALPHA8 as isize,
+    RGBA16F = consts::RGBA16F as isize,
     RGBA32F = consts::RGBA32F as isize,
     Depth16 = consts::DEPTH_COMPONENT16 as isize,
     Depth24 = consts::DEPTH_COMPONENT24 as isize,
-    Depth32F = consts::DEPTH_COMPONENT32F as isize
+    Depth32F = consts::DEPTH_COMPONENT32F as isize,
+    // Compressed formats, which are only available if the GPU supports them, see CompressedImage::is_supported.
+    // The values are given explicitly since the constants are only defined by extensions.
+    RGBDXT1 = 0x83F0,
+    RGBADXT1 = 0x83F1,
+    RGBADXT3 = 0x83F2,
+    RGBADXT5 = 0x83F3,
+    SRGBDXT1 = 0x8C4C,
+    SRGBAlphaDXT1 = 0x8C4D,
+    SRGBAlphaDXT3 = 0x8C4E,
+    SRGBAlphaDXT5 = 0x8C4F,
+    RGB8ETC2 = 0x9274,
+    SRGB8ETC2 = 0x9275,
+    RGB8A1ETC2 = 0x9276,
+    SRGB8A1ETC2 = 0x9277,
+    RGBA8ETC2 = 0x9278,
+    SRGB8Alpha8ETC2 = 0x9279,
+    RGBAASTC4x4 = 0x93B0,
+    SRGB8Alpha8ASTC4x4 = 0x93D0
 }
 
 pub trait Texture {
@@ -62,70 +85,152 @@ impl Texture2D
     pub fn new_with_u8(gl: &Gl, min_filter: Interpolation, mag_filter: Interpolation, mip_map_filter: Option<Interpolation>,
            wrap_s: Wrapping, wrap_t: Wrapping, image: &Image) -> Result<Texture2D, Error>
     {
-        let number_of_channels = image.bytes.len() as u32 / (image.width * image.height);
-        let format = match number_of_channels {
-            1 => Ok(Format::R8),
-            3 => Ok(Format::RGB8),
-            4 => Ok(Format::RGBA8),
-            _ => Err(Error::FailedToCreateTexture {message: format!("Unsupported texture format")})
-        }?;
-
+        let format = format_from_image(image, false)?;
         let mut texture = Texture2D::new(gl, image.width as usize, image.height as usize,
             min_filter, mag_filter, mip_map_filter, wrap_s, wrap_t, format)?;
         texture.fill_with_u8(&image.bytes)?;
         Ok(texture)
     }
 
-    pub fn fill_with_u8(&mut self, data: &[u8]) -> Result<(), Error>
+    ///
+    /// Creates a texture from an image in the sRGB color space, which is the case for most color textures.
+    /// The colors are converted to linear color space when sampled in a shader, so lighting calculations are done in linear space.
+    ///
+    pub fn new_with_srgb_u8(gl: &Gl, min_filter: Interpolation, mag_filter: Interpolation, mip_map_filter: Option<Interpolation>,
+           wrap_s: Wrapping, wrap_t: Wrapping, image: &Image) -> Result<Texture2D, Error>
     {
-        let format =
-            match self.format {
-                Format::R8 => Ok(consts::RED),
-                Format::RGB8 => Ok(consts::RGB),
-                Format::RGBA8 => Ok(consts::RGBA),
-                _ => Err(Error::FailedToCreateTexture {message: "Wrong texture format".to_string()})
-            }?;
+        let format = format_from_image(image, true)?;
+        let mut texture = Texture2D::new(gl, image.width as usize, image.height as usize,
+            min_filter, mag_filter, mip_map_filter, wrap_s, wrap_t, format)?;
+        texture.fill_with_u8(&srgb_bytes(image, format))?;
+        Ok(texture)
+    }
 
-        let mut desired_length = self.width * self.height;
-        if format == consts::RGB { desired_length *= 3 };
-        if format == consts::RGBA { desired_length *= 4 };
+    ///
+    /// Creates a texture from a compressed image and its mip chain. If the GPU does not support the compressed format,
+    /// the image is decoded to 8 bit RGBA on the CPU instead, which uses more memory but looks the same.
+    /// Mip maps cannot be generated for compressed textures, so the mip map filter is only used if the image contains more than one level.
+    ///
+    pub fn new_with_compressed(gl: &Gl, min_filter: Interpolation, mag_filter: Interpolation, mip_map_filter: Option<Interpolation>,
+           wrap_s: Wrapping, wrap_t: Wrapping, image: &CompressedImage) -> Result<Texture2D, Error>
+    {
+        let number_of_mip_maps = image.levels.len() as u32;
+        if number_of_mip_maps == 0 {
+            Err(Error::FailedToCreateTexture {message: "The compressed image has no data".to_string()})?
+        }
+        let supported = image.is_supported(gl);
+        let format = if supported {image.format} else if image.is_srgb() {Format::SRGB8Alpha8} else {Format::RGBA8};
+        let (width, height) = (image.width as usize, image.height as usize);
 
-        if data.len() != desired_length {
-            Err(Error::FailedToCreateTexture {message: format!("Wrong size of data for the texture ({} != {})", data.len(), desired_length)})?
+        let id = generate(gl)?;
+        set_parameters(gl, &id,consts::TEXTURE_2D, min_filter, mag_filter, if number_of_mip_maps == 1 {None} else {mip_map_filter}, wrap_s, wrap_t, None);
+        gl.tex_storage_2d(consts::TEXTURE_2D, number_of_mip_maps, format as u32, width as u32, height as u32);
+        let texture = Self { gl: gl.clone(), id, width, height, format, number_of_mip_maps };
+
+        for level in 0..image.levels.len() {
+            let (w, h) = image.level_size(level)?;
+            if supported {
+                let desired_length = (w as usize).div_ceil(4) * (h as usize).div_ceil(4) * crate::core::compressed_image::block_size_in_bytes(format)?;
+                if image.levels[level].len() != desired_length {
+                    Err(Error::FailedToCreateTexture {message: format!("Wrong size of data for mip level {} ({} != {})", level, image.levels[level].len(), desired_length)})?
+                }
+                gl.compressed_tex_sub_image_2d(consts::TEXTURE_2D, level as u32, 0, 0, w, h, format as u32, &image.levels[level]);
+            } else {
+                let decoded = image.decode(level)?;
+                gl.tex_sub_image_2d_with_u8_data(consts::TEXTURE_2D, level as u32, 0, 0, w, h, consts::RGBA, consts::UNSIGNED_BYTE, &decoded.bytes);
+            }
         }
+        Ok(texture)
+    }
+
+    pub fn fill_with_u8(&mut self, data: &[u8]) -> Result<(), Error>
+    {
+        self.fill_subset_with_u8(0, 0, self.width, self.height, data)
+    }
+
+    pub fn fill_with_f32(&mut self, data: &[f32]) -> Result<(), Error>
+    {
+        self.fill_subset_with_f32(0, 0, self.width, self.height, data)
+    }
+
+    ///
+    /// Updates the rectangle with the lower left corner at (x, y) without reallocating the texture, for example to stream video frames.
+    ///
+    pub fn fill_subset_with_u8(&mut self, x: usize, y: usize, width: usize, height: usize, data: &[u8]) -> Result<(), Error>
+    {
+        let format = u8_format(self.format)?;
+        check_region((x, y, 0, width, height, 1), (self.width, self.height, 1), format, data.len())?;
         self.gl.bind_texture(consts::TEXTURE_2D, &self.id);
-        self.gl.tex_sub_image_2d_with_u8_data(consts::TEXTURE_2D, 0, 0, 0,
-                                              self.width as u32, self.height as u32,
+        self.gl.tex_sub_image_2d_with_u8_data(consts::TEXTURE_2D, 0, x as u32, y as u32,
+                                              width as u32, height as u32,
                                               format, consts::UNSIGNED_BYTE, data);
         self.generate_mip_maps();
         Ok(())
     }
 
-    pub fn fill_with_f32(&mut self, data: &[f32]) -> Result<(), Error>
+    pub fn fill_subset_with_f32(&mut self, x: usize, y: usize, width: usize, height: usize, data: &[f32]) -> Result<(), Error>
     {
-        let format =
-            match self.format {
-                Format::R32F => Ok(consts::RED),
-                Format::RGB32F => Ok(consts::RGB),
-                Format::RGBA32F => Ok(consts::RGBA),
-                _ => Err(Error::FailedToCreateTexture {message: "Wrong texture format".to_string()})
-            }?;
-
-        let mut desired_length = self.width * self.height;
-        if format == consts::RGB { desired_length *= 3 };
-        if format == consts::RGBA { desired_length *= 4 };
-
-        if data.len() != desired_length {
-            Err(Error::FailedToCreateTexture {message: format!("Wrong size of data for the texture ({} != {})", data.len(), desired_length)})?
-        }
+        let format = f32_format(self.format)?;
+        check_region((x, y, 0, width, height, 1), (self.width, self.height, 1), format, data.len())?;
         self.gl.bind_texture(consts::TEXTURE_2D, &self.id);
-        self.gl.tex_sub_image_2d_with_f32_data(consts::TEXTURE_2D,0,0,0,
-                                           self.width as u32,self.height as u32,
-                                               format,consts::FLOAT,data);
+        self.gl.tex_sub_image_2d_with_f32_data(consts::TEXTURE_2D, 0, x as u32, y as u32,
+                                               width as u32, height as u32,
+                                               format, consts::FLOAT, data);
         self.generate_mip_maps();
         Ok(())
     }
 
+    ///
+    /// Sets the maximum anisotropy of the texture, clamped to the maximum supported by the hardware, and returns the value that is used.
+    /// A value of 1 disables anisotropic filtering, which is always the case if the EXT_texture_filter_anisotropic extension is not available.
+    /// Anisotropic filtering improves the quality of textures seen at a grazing angle and is most useful together with mip maps.
+    ///
+    pub fn set_anisotropy(&mut self, max_anisotropy: f32) -> f32
+    {
+        set_anisotropy(&self.gl, &self.id, consts::TEXTURE_2D, max_anisotropy)
+    }
+
+    ///
+    /// Limits the mip levels used when sampling to the levels from base_level to max_level (both included).
+    ///
+    pub fn set_mip_levels(&mut self, base_level: u32, max_level: u32) -> Result<(), Error>
+    {
+        set_mip_levels(&self.gl, &self.id, consts::TEXTURE_2D, base_level, max_level, self.number_of_mip_maps)
+    }
+
+    ///
+    /// Adds the bias to the mip level which is chosen when sampling. Not supported in WebGL2.
+    ///
+    pub fn set_lod_bias(&mut self, bias: f32) -> Result<(), Error>
+    {
+        set_lod_bias(&self.gl, &self.id, consts::TEXTURE_2D, bias)
+    }
+
+    ///
+    /// Downloads the texture to the CPU. Only available for 8 bit formats and the data contains the channels of the format.
+    ///
+    pub fn read_u8(&self) -> Result<Vec<u8>, Error>
+    {
+        read_u8(&self.gl, &self.id, ReadSource::Image(consts::TEXTURE_2D), self.format, self.width, self.height)
+    }
+
+    ///
+    /// Downloads the texture to the CPU. Only available for float and depth formats (depth only on desktop).
+    ///
+    pub fn read_f32(&self) -> Result<Vec<f32>, Error>
+    {
+        read_f32(&self.gl, &self.id, ReadSource::Image(consts::TEXTURE_2D), self.format, self.width, self.height)
+    }
+
+    ///
+    /// Downloads the texture to the CPU as an 8 bit image which can be saved with [Saver::save_image](struct.Saver.html#method.save_image).
+    /// Float formats are clamped to [0, 1] and depth formats are scaled so the nearest depth is black and the farthest is white.
+    ///
+    pub fn read_image(&self) -> Result<Image, Error>
+    {
+        read_image(&self.gl, &self.id, ReadSource::Image(consts::TEXTURE_2D), self.format, self.width, self.height)
+    }
+
     pub(crate) fn generate_mip_maps(&self) {
         if self.number_of_mip_maps > 1 {
             self.gl.bind_texture(consts::TEXTURE_2D, &self.id);
@@ -192,53 +297,181 @@ impl TextureCubeMap
     pub fn new_with_u8(gl: &Gl, min_filter: Interpolation, mag_filter: Interpolation, mip_map_filter: Option<Interpolation>,
            wrap_s: Wrapping, wrap_t: Wrapping, wrap_r: Wrapping, right: &Image, left: &Image, top: &Image, bottom: &Image, front: &Image, back: &Image) -> Result<Self, Error>
     {
-        let number_of_channels = right.bytes.len() as u32 / (right.width * right.height);
-        let format = match number_of_channels {
-            1 => Ok(Format::R8),
-            3 => Ok(Format::RGB8),
-            4 => Ok(Format::RGBA8),
-            _ => Err(Error::FailedToCreateTexture {message: format!("Unsupported texture format")})
-        }?;
-
+        let format = format_from_image(right, false)?;
         let mut texture = Self::new(gl, right.width as usize, right.height as usize,
             min_filter, mag_filter, mip_map_filter, wrap_s, wrap_t, wrap_r, format)?;
         texture.fill_with_u8([&right.bytes, &left.bytes, &top.bytes, &bottom.bytes, &front.bytes, &back.bytes])?;
         Ok(texture)
     }
 
-    pub fn fill_with_u8(&mut self, data: [&[u8]; 6]) -> Result<(), Error>
+    ///
+    /// Creates a cube map from images in the sRGB color space, see [Texture2D::new_with_srgb_u8](struct.Texture2D.html#method.new_with_srgb_u8).
+    ///
+    pub fn new_with_srgb_u8(gl: &Gl, min_filter: Interpolation, mag_filter: Interpolation, mip_map_filter: Option<Interpolation>,
+           wrap_s: Wrapping, wrap_t: Wrapping, wrap_r: Wrapping, right: &Image, left: &Image, top: &Image, bottom: &Image, front: &Image, back: &Image) -> Result<Self, Error>
+    {
+        let format = format_from_image(right, true)?;
+        let mut texture = Self::new(gl, right.width as usize, right.height as usize,
+            min_filter, mag_filter, mip_map_filter, wrap_s, wrap_t, wrap_r, format)?;
+        texture.fill_with_u8([&srgb_bytes(right, format), &srgb_bytes(left, format), &srgb_bytes(top, format),
+                              &srgb_bytes(bottom, format), &srgb_bytes(front, format), &srgb_bytes(back, format)])?;
+        Ok(texture)
+    }
+
+    ///
+    /// Creates a cube map with sides of the given size from an equirectangular panorama, for example loaded from a .hdr file with
+    /// [Loader::get_hdr_image](struct.Loader.html#method.get_hdr_image). The conversion is done on the GPU and the cube map has the
+    /// RGBA16F format, so the high dynamic range is kept. On web, this requires the EXT_color_buffer_float extension.
+    ///
+    pub fn new_from_equirectangular(gl: &Gl, min_filter: Interpolation, mag_filter: Interpolation, mip_map_filter: Option<Interpolation>,
+           image: &HDRImage, size: usize) -> Result<Self, Error>
     {
-        let format =
-            match self.format {
-                Format::R8 => Ok(consts::RED),
-                Format::RGB8 => Ok(consts::RGB),
-                Format::RGBA8 => Ok(consts::RGBA),
-                _ => Err(Error::FailedToCreateTexture {message: "Wrong texture format".to_string()})
-            }?;
+        #[cfg(target_arch = "wasm32")]
+        {
+            if !gl.supports_extension("EXT_color_buffer_float") {
+                Err(Error::FailedToCreateTexture {message: "Rendering to float textures (EXT_color_buffer_float) is not supported".to_string()})?
+            }
+        }
+        let mut equirectangular = Texture2D::new(gl, image.width as usize, image.height as usize, Interpolation::Linear, Interpolation::Linear, None,
+                                                 Wrapping::Repeat, Wrapping::ClampToEdge, Format::RGB16F)?;
+        equirectangular.fill_with_f32(&image.pixels)?;
+        let texture = Self::new(gl, size, size, min_filter, mag_filter, mip_map_filter,
+                                Wrapping::ClampToEdge, Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::RGBA16F)?;
 
-        let mut desired_length = self.width * self.height;
-        if format == consts::RGB { desired_length *= 3 };
-        if format == consts::RGBA { desired_length *= 4 };
+        let effect = crate::ImageEffect::from_shader_source(gl, &crate::ShaderSource::from_segments(vec![
+            shader_segment!("shaders/cube_map_shared.frag"),
+            shader_segment!("shaders/equirectangular.frag")]))?;
+        effect.program().use_texture(&equirectangular, "equirectangularMap")?;
+        texture.write_sides(0, &effect)?;
+        Ok(texture)
+    }
 
-        if data[0].len() != desired_length {
-            Err(Error::FailedToCreateTexture {message: format!("Wrong size of data for the texture ({} != {})", data[0].len(), desired_length)})?
+    ///
+    /// Renders the image effect to each side of the given mip level. The effect has access to the function cube_map_direction(uv) from cube_map_shared.frag,
+    /// which returns the direction corresponding to the uv coordinates on the side that is currently rendered.
+    ///
+    pub(crate) fn write_sides(&self, mip_level: usize, effect: &crate::ImageEffect) -> Result<(), Error>
+    {
+        let size = (self.width >> mip_level).max(1);
+        for side in 0..6 {
+            crate::RenderTarget::write_to_cube_map_side(&self.gl, 0, 0, size, size, None, None, Some(self), None, side, mip_level, || {
+                crate::state::depth_test(&self.gl, crate::state::DepthTestType::None);
+                crate::state::blend(&self.gl, crate::state::BlendType::None);
+                effect.program().add_uniform_int("side", &(side as i32))?;
+                effect.apply();
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn fill_with_u8(&mut self, data: [&[u8]; 6]) -> Result<(), Error>
+    {
+        let format = u8_format(self.format)?;
+        for side in data.iter() {
+            check_region((0, 0, 0, self.width, self.height, 1), (self.width, self.height, 1), format, side.len())?;
         }
         self.gl.bind_texture(consts::TEXTURE_CUBE_MAP, &self.id);
-        for i in 0..6 {
+        for (i, side) in data.iter().enumerate() {
             self.gl.tex_sub_image_2d_with_u8_data(consts::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32, 0, 0, 0,
                                                   self.width as u32, self.height as u32,
-                                                  format, consts::UNSIGNED_BYTE, data[i]);
+                                                  format, consts::UNSIGNED_BYTE, side);
         }
         self.generate_mip_maps();
         Ok(())
     }
 
+    ///
+    /// Updates a rectangle of one side of the cube map without reallocating it.
+    /// The sides are ordered right, left, top, bottom, front, back (positive x, negative x, positive y, negative y, positive z, negative z).
+    ///
+    pub fn fill_subset_with_u8(&mut self, side: usize, x: usize, y: usize, width: usize, height: usize, data: &[u8]) -> Result<(), Error>
+    {
+        if side >= 6 {
+            Err(Error::FailedToCreateTexture {message: format!("A cube map has 6 sides, not {}", side + 1)})?
+        }
+        let format = u8_format(self.format)?;
+        check_region((x, y, 0, width, height, 1), (self.width, self.height, 1), format, data.len())?;
+        self.gl.bind_texture(consts::TEXTURE_CUBE_MAP, &self.id);
+        self.gl.tex_sub_image_2d_with_u8_data(consts::TEXTURE_CUBE_MAP_POSITIVE_X + side as u32, 0, x as u32, y as u32,
+                                              width as u32, height as u32,
+                                              format, consts::UNSIGNED_BYTE, data);
+        self.generate_mip_maps();
+        Ok(())
+    }
+
+    ///
+    /// Sets the maximum anisotropy, see [Texture2D::set_anisotropy](struct.Texture2D.html#method.set_anisotropy).
+    ///
+    pub fn set_anisotropy(&mut self, max_anisotropy: f32) -> f32
+    {
+        set_anisotropy(&self.gl, &self.id, consts::TEXTURE_CUBE_MAP, max_anisotropy)
+    }
+
+    ///
+    /// Limits the mip levels used when sampling to the levels from base_level to max_level (both included).
+    ///
+    pub fn set_mip_levels(&mut self, base_level: u32, max_level: u32) -> Result<(), Error>
+    {
+        set_mip_levels(&self.gl, &self.id, consts::TEXTURE_CUBE_MAP, base_level, max_level, self.number_of_mip_maps)
+    }
+
+    ///
+    /// Adds the bias to the mip level which is chosen when sampling. Not supported in WebGL2.
+    ///
+    pub fn set_lod_bias(&mut self, bias: f32) -> Result<(), Error>
+    {
+        set_lod_bias(&self.gl, &self.id, consts::TEXTURE_CUBE_MAP, bias)
+    }
+
+    ///
+    /// Downloads one side of the cube map to the CPU, see [Texture2D::read_u8](struct.Texture2D.html#method.read_u8).
+    /// The sides are ordered as in [fill_subset_with_u8](#method.fill_subset_with_u8).
+    ///
+    pub fn read_side_u8(&self, side: usize) -> Result<Vec<u8>, Error>
+    {
+        read_u8(&self.gl, &self.id, cube_map_side(side)?, self.format, self.width, self.height)
+    }
+
+    pub fn read_side_f32(&self, side: usize) -> Result<Vec<f32>, Error>
+    {
+        read_f32(&self.gl, &self.id, cube_map_side(side)?, self.format, self.width, self.height)
+    }
+
+    pub fn read_side_image(&self, side: usize) -> Result<Image, Error>
+    {
+        read_image(&self.gl, &self.id, cube_map_side(side)?, self.format, self.width, self.height)
+    }
+
+    ///
+    /// Reads a side as three linear float values (red, green and blue) per texel, where sRGB encoded values are decoded and single channel formats are repeated.
+    ///
+    pub(crate) fn read_side_linear_rgb(&self, side: usize) -> Result<Vec<f32>, Error>
+    {
+        let channels = channel_count(self.format);
+        let values = match self.format {
+            Format::R8 | Format::RGB8 | Format::RGBA4 | Format::RGBA8 => self.read_side_u8(side)?.iter().map(|v| *v as f32 / 255.0).collect(),
+            Format::SRGB8 | Format::SRGB8Alpha8 => self.read_side_u8(side)?.iter().map(|v| {
+                let v = *v as f32 / 255.0;
+                if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+            }).collect(),
+            _ => self.read_side_f32(side)?
+        };
+        Ok(values.chunks(channels).flat_map(|texel| if channels >= 3 { [texel[0], texel[1], texel[2]] } else { [texel[0]; 3] }).collect())
+    }
+
     pub(crate) fn generate_mip_maps(&self) {
         if self.number_of_mip_maps > 1 {
             self.gl.bind_texture(consts::TEXTURE_CUBE_MAP, &self.id);
             self.gl.generate_mipmap(consts::TEXTURE_CUBE_MAP);
         }
     }
+
+    pub(crate) fn bind_as_color_target(&self, side: usize, channel: usize, mip_level: usize)
+    {
+        self.gl.framebuffer_texture_2d(consts::FRAMEBUFFER,
+                       consts::COLOR_ATTACHMENT0 + channel as u32, consts::TEXTURE_CUBE_MAP_POSITIVE_X + side as u32, &self.id, mip_level as u32);
+    }
 }
 
 impl Texture for TextureCubeMap
@@ -263,6 +496,7 @@ pub struct Texture2DArray {
     pub width: usize,
     pub height: usize,
     pub depth: usize,
+    format: Format,
     number_of_mip_maps: u32
 }
 
@@ -281,7 +515,84 @@ impl Texture2DArray
                         width as u32,
                         height as u32,
                         depth as u32);
-        Ok(Self { gl: gl.clone(), id, width, height, depth, number_of_mip_maps })
+        Ok(Self { gl: gl.clone(), id, width, height, depth, format, number_of_mip_maps })
+    }
+
+    ///
+    /// Updates the box with the lower left corner at (x, y) in the layers first_layer to first_layer + layers without reallocating the texture.
+    ///
+    pub fn fill_subset_with_u8(&mut self, x: usize, y: usize, first_layer: usize, width: usize, height: usize, layers: usize, data: &[u8]) -> Result<(), Error>
+    {
+        let format = u8_format(self.format)?;
+        check_region((x, y, first_layer, width, height, layers), (self.width, self.height, self.depth), format, data.len())?;
+        self.gl.bind_texture(consts::TEXTURE_2D_ARRAY, &self.id);
+        self.gl.tex_sub_image_3d_with_u8_data(consts::TEXTURE_2D_ARRAY, 0, x as u32, y as u32, first_layer as u32,
+                                              width as u32, height as u32, layers as u32,
+                                              format, consts::UNSIGNED_BYTE, data);
+        self.generate_mip_maps();
+        Ok(())
+    }
+
+    pub fn fill_subset_with_f32(&mut self, x: usize, y: usize, first_layer: usize, width: usize, height: usize, layers: usize, data: &[f32]) -> Result<(), Error>
+    {
+        let format = f32_format(self.format)?;
+        check_region((x, y, first_layer, width, height, layers), (self.width, self.height, self.depth), format, data.len())?;
+        self.gl.bind_texture(consts::TEXTURE_2D_ARRAY, &self.id);
+        self.gl.tex_sub_image_3d_with_f32_data(consts::TEXTURE_2D_ARRAY, 0, x as u32, y as u32, first_layer as u32,
+                                               width as u32, height as u32, layers as u32,
+                                               format, consts::FLOAT, data);
+        self.generate_mip_maps();
+        Ok(())
+    }
+
+    ///
+    /// Sets the maximum anisotropy, see [Texture2D::set_anisotropy](struct.Texture2D.html#method.set_anisotropy).
+    ///
+    pub fn set_anisotropy(&mut self, max_anisotropy: f32) -> f32
+    {
+        set_anisotropy(&self.gl, &self.id, consts::TEXTURE_2D_ARRAY, max_anisotropy)
+    }
+
+    ///
+    /// Limits the mip levels used when sampling to the levels from base_level to max_level (both included).
+    ///
+    pub fn set_mip_levels(&mut self, base_level: u32, max_level: u32) -> Result<(), Error>
+    {
+        set_mip_levels(&self.gl, &self.id, consts::TEXTURE_2D_ARRAY, base_level, max_level, self.number_of_mip_maps)
+    }
+
+    ///
+    /// Adds the bias to the mip level which is chosen when sampling. Not supported in WebGL2.
+    ///
+    pub fn set_lod_bias(&mut self, bias: f32) -> Result<(), Error>
+    {
+        set_lod_bias(&self.gl, &self.id, consts::TEXTURE_2D_ARRAY, bias)
+    }
+
+    ///
+    /// Downloads one layer of the texture array to the CPU, see [Texture2D::read_u8](struct.Texture2D.html#method.read_u8).
+    ///
+    pub fn read_layer_u8(&self, layer: usize) -> Result<Vec<u8>, Error>
+    {
+        read_u8(&self.gl, &self.id, self.layer(layer)?, self.format, self.width, self.height)
+    }
+
+    pub fn read_layer_f32(&self, layer: usize) -> Result<Vec<f32>, Error>
+    {
+        read_f32(&self.gl, &self.id, self.layer(layer)?, self.format, self.width, self.height)
+    }
+
+    pub fn read_layer_image(&self, layer: usize) -> Result<Image, Error>
+    {
+        read_image(&self.gl, &self.id, self.layer(layer)?, self.format, self.width, self.height)
+    }
+
+    fn layer(&self, layer: usize) -> Result<ReadSource, Error>
+    {
+        if layer >= self.depth {
+            Err(Error::FailedToCreateTexture {message: format!("The layer {} is outside the texture array with {} layers", layer, self.depth)})?
+        }
+        Ok(ReadSource::Layer(layer as u32))
     }
 
     pub(crate) fn generate_mip_maps(&self) {
@@ -321,6 +632,168 @@ impl Drop for Texture2DArray
 }
 
 
+///
+/// A 3D texture, for example for volume data or a color grading lookup table, which is sampled with a sampler3D in a shader.
+///
+pub struct Texture3D {
+    gl: Gl,
+    id: crate::gl::Texture,
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    format: Format,
+    number_of_mip_maps: u32
+}
+
+impl Texture3D
+{
+    pub fn new(gl: &Gl, width: usize, height: usize, depth: usize, min_filter: Interpolation, mag_filter: Interpolation, mip_map_filter: Option<Interpolation>,
+           wrap_s: Wrapping, wrap_t: Wrapping, wrap_r: Wrapping, format: Format) -> Result<Self, Error>
+    {
+        let id = generate(gl)?;
+        let number_of_mip_maps = calculate_number_of_mip_maps(mip_map_filter, width, height, depth);
+        set_parameters(gl, &id,consts::TEXTURE_3D, min_filter, mag_filter, if number_of_mip_maps == 1 {None} else {mip_map_filter}, wrap_s, wrap_t, Some(wrap_r));
+        gl.bind_texture(consts::TEXTURE_3D, &id);
+        gl.tex_storage_3d(consts::TEXTURE_3D,
+                        number_of_mip_maps,
+                        format as u32,
+                        width as u32,
+                        height as u32,
+                        depth as u32);
+        Ok(Self { gl: gl.clone(), id, width, height, depth, format, number_of_mip_maps })
+    }
+
+    ///
+    /// Creates a texture with the given size and data, where the data is ordered in slices along z, each slice in rows along y.
+    ///
+    pub fn new_with_u8(gl: &Gl, width: usize, height: usize, depth: usize, min_filter: Interpolation, mag_filter: Interpolation, mip_map_filter: Option<Interpolation>,
+           wrap_s: Wrapping, wrap_t: Wrapping, wrap_r: Wrapping, format: Format, data: &[u8]) -> Result<Self, Error>
+    {
+        let mut texture = Texture3D::new(gl, width, height, depth, min_filter, mag_filter, mip_map_filter, wrap_s, wrap_t, wrap_r, format)?;
+        texture.fill_with_u8(data)?;
+        Ok(texture)
+    }
+
+    pub fn new_with_f32(gl: &Gl, width: usize, height: usize, depth: usize, min_filter: Interpolation, mag_filter: Interpolation, mip_map_filter: Option<Interpolation>,
+           wrap_s: Wrapping, wrap_t: Wrapping, wrap_r: Wrapping, format: Format, data: &[f32]) -> Result<Self, Error>
+    {
+        let mut texture = Texture3D::new(gl, width, height, depth, min_filter, mag_filter, mip_map_filter, wrap_s, wrap_t, wrap_r, format)?;
+        texture.fill_with_f32(data)?;
+        Ok(texture)
+    }
+
+    pub fn fill_with_u8(&mut self, data: &[u8]) -> Result<(), Error>
+    {
+        self.fill_subset_with_u8(0, 0, 0, self.width, self.height, self.depth, data)
+    }
+
+    pub fn fill_with_f32(&mut self, data: &[f32]) -> Result<(), Error>
+    {
+        self.fill_subset_with_f32(0, 0, 0, self.width, self.height, self.depth, data)
+    }
+
+    ///
+    /// Updates the box with the lower left front corner at (x, y, z) without reallocating the texture.
+    ///
+    pub fn fill_subset_with_u8(&mut self, x: usize, y: usize, z: usize, width: usize, height: usize, depth: usize, data: &[u8]) -> Result<(), Error>
+    {
+        let format = u8_format(self.format)?;
+        check_region((x, y, z, width, height, depth), (self.width, self.height, self.depth), format, data.len())?;
+        self.gl.bind_texture(consts::TEXTURE_3D, &self.id);
+        self.gl.tex_sub_image_3d_with_u8_data(consts::TEXTURE_3D, 0, x as u32, y as u32, z as u32,
+                                              width as u32, height as u32, depth as u32,
+                                              format, consts::UNSIGNED_BYTE, data);
+        self.generate_mip_maps();
+        Ok(())
+    }
+
+    pub fn fill_subset_with_f32(&mut self, x: usize, y: usize, z: usize, width: usize, height: usize, depth: usize, data: &[f32]) -> Result<(), Error>
+    {
+        let format = f32_format(self.format)?;
+        check_region((x, y, z, width, height, depth), (self.width, self.height, self.depth), format, data.len())?;
+        self.gl.bind_texture(consts::TEXTURE_3D, &self.id);
+        self.gl.tex_sub_image_3d_with_f32_data(consts::TEXTURE_3D, 0, x as u32, y as u32, z as u32,
+                                               width as u32, height as u32, depth as u32,
+                                               format, consts::FLOAT, data);
+        self.generate_mip_maps();
+        Ok(())
+    }
+
+    ///
+    /// Sets the maximum anisotropy, see [Texture2D::set_anisotropy](struct.Texture2D.html#method.set_anisotropy).
+    ///
+    pub fn set_anisotropy(&mut self, max_anisotropy: f32) -> f32
+    {
+        set_anisotropy(&self.gl, &self.id, consts::TEXTURE_3D, max_anisotropy)
+    }
+
+    ///
+    /// Limits the mip levels used when sampling to the levels from base_level to max_level (both included).
+    ///
+    pub fn set_mip_levels(&mut self, base_level: u32, max_level: u32) -> Result<(), Error>
+    {
+        set_mip_levels(&self.gl, &self.id, consts::TEXTURE_3D, base_level, max_level, self.number_of_mip_maps)
+    }
+
+    ///
+    /// Adds the bias to the mip level which is chosen when sampling. Not supported in WebGL2.
+    ///
+    pub fn set_lod_bias(&mut self, bias: f32) -> Result<(), Error>
+    {
+        set_lod_bias(&self.gl, &self.id, consts::TEXTURE_3D, bias)
+    }
+
+    ///
+    /// Downloads the slice at the given z coordinate to the CPU, see [Texture2D::read_u8](struct.Texture2D.html#method.read_u8).
+    ///
+    pub fn read_slice_u8(&self, z: usize) -> Result<Vec<u8>, Error>
+    {
+        read_u8(&self.gl, &self.id, self.slice(z)?, self.format, self.width, self.height)
+    }
+
+    pub fn read_slice_f32(&self, z: usize) -> Result<Vec<f32>, Error>
+    {
+        read_f32(&self.gl, &self.id, self.slice(z)?, self.format, self.width, self.height)
+    }
+
+    pub fn read_slice_image(&self, z: usize) -> Result<Image, Error>
+    {
+        read_image(&self.gl, &self.id, self.slice(z)?, self.format, self.width, self.height)
+    }
+
+    fn slice(&self, z: usize) -> Result<ReadSource, Error>
+    {
+        if z >= self.depth {
+            Err(Error::FailedToCreateTexture {message: format!("The slice {} is outside the 3D texture with depth {}", z, self.depth)})?
+        }
+        Ok(ReadSource::Layer(z as u32))
+    }
+
+    pub(crate) fn generate_mip_maps(&self) {
+        if self.number_of_mip_maps > 1 {
+            self.gl.bind_texture(consts::TEXTURE_3D, &self.id);
+            self.gl.generate_mipmap(consts::TEXTURE_3D);
+        }
+    }
+}
+
+impl Texture for Texture3D
+{
+    fn bind(&self, location: u32)
+    {
+        bind_at(&self.gl, &self.id, consts::TEXTURE_3D, location);
+    }
+}
+
+impl Drop for Texture3D
+{
+    fn drop(&mut self)
+    {
+        self.gl.delete_texture(&self.id);
+    }
+}
+
+
 // COMMON FUNCTIONS
 fn generate(gl: &Gl) -> Result<crate::gl::Texture, Error>
 {
@@ -333,6 +806,231 @@ fn bind_at(gl: &Gl, id: &crate::gl::Texture, target: u32, location: u32)
     gl.bind_texture(target, id);
 }
 
+fn u8_format(format: Format) -> Result<u32, Error>
+{
+    match format {
+        Format::R8 => Ok(consts::RED),
+        Format::RGB8 | Format::SRGB8 => Ok(consts::RGB),
+        Format::RGBA8 | Format::SRGB8Alpha8 => Ok(consts::RGBA),
+        _ => Err(Error::FailedToCreateTexture {message: "Wrong texture format".to_string()})
+    }
+}
+
+fn f32_format(format: Format) -> Result<u32, Error>
+{
+    match format {
+        Format::R32F => Ok(consts::RED),
+        Format::RGB16F | Format::RGB32F => Ok(consts::RGB),
+        Format::RGBA16F | Format::RGBA32F => Ok(consts::RGBA),
+        _ => Err(Error::FailedToCreateTexture {message: "Wrong texture format".to_string()})
+    }
+}
+
+// Checks that the region (x, y, z, width, height, depth) is inside a texture of the given size and that the data has the right length
+fn check_region(region: (usize, usize, usize, usize, usize, usize), size: (usize, usize, usize), format: u32, data_length: usize) -> Result<(), Error>
+{
+    let (x, y, z, width, height, depth) = region;
+    if x + width > size.0 || y + height > size.1 || z + depth > size.2 {
+        Err(Error::FailedToCreateTexture {message: format!("The region {}x{}x{} at ({}, {}, {}) is outside the texture of size {}x{}x{}",
+                                                           width, height, depth, x, y, z, size.0, size.1, size.2)})?
+    }
+
+    let mut desired_length = width * height * depth;
+    if format == consts::RGB { desired_length *= 3 };
+    if format == consts::RGBA { desired_length *= 4 };
+
+    if data_length != desired_length {
+        Err(Error::FailedToCreateTexture {message: format!("Wrong size of data for the texture ({} != {})", data_length, desired_length)})?
+    }
+    Ok(())
+}
+
+// Three channel sRGB images are stored as SRGB8Alpha8 since SRGB8 is not color renderable in WebGL2 and OpenGL ES,
+// which means that mip maps cannot be generated. There is no single channel sRGB format.
+fn format_from_image(image: &Image, srgb: bool) -> Result<Format, Error>
+{
+    let number_of_channels = image.bytes.len() as u32 / (image.width * image.height);
+    match number_of_channels {
+        1 => Ok(Format::R8),
+        3 => Ok(if srgb {Format::SRGB8Alpha8} else {Format::RGB8}),
+        4 => Ok(if srgb {Format::SRGB8Alpha8} else {Format::RGBA8}),
+        _ => Err(Error::FailedToCreateTexture {message: "Unsupported texture format".to_string()})
+    }
+}
+
+fn srgb_bytes(image: &Image, format: Format) -> Vec<u8>
+{
+    let number_of_channels = image.bytes.len() as u32 / (image.width * image.height);
+    if format == Format::SRGB8Alpha8 && number_of_channels == 3 {
+        image.bytes.chunks(3).flat_map(|pixel| vec![pixel[0], pixel[1], pixel[2], 255]).collect()
+    } else {
+        image.bytes.clone()
+    }
+}
+
+// The 2D image of a texture to read from, either a 2D texture or cube map side (given by the texture target) or a layer of a texture array
+#[derive(Clone, Copy)]
+enum ReadSource {
+    Image(u32),
+    Layer(u32)
+}
+
+fn cube_map_side(side: usize) -> Result<ReadSource, Error>
+{
+    if side >= 6 {
+        Err(Error::FailedToCreateTexture {message: format!("A cube map has 6 sides, not {}", side + 1)})?
+    }
+    Ok(ReadSource::Image(consts::TEXTURE_CUBE_MAP_POSITIVE_X + side as u32))
+}
+
+fn is_depth_format(format: Format) -> bool
+{
+    format == Format::Depth16 || format == Format::Depth24 || format == Format::Depth32F
+}
+
+fn channel_count(format: Format) -> usize
+{
+    match format {
+        Format::R8 | Format::R32F | Format::Depth16 | Format::Depth24 | Format::Depth32F => 1,
+        Format::RGB8 | Format::SRGB8 | Format::RGB16F | Format::RGB32F => 3,
+        Format::RGBA4 | Format::RGBA8 | Format::SRGB8Alpha8 | Format::RGBA16F | Format::RGBA32F => 4,
+        Format::RGBDXT1 | Format::SRGBDXT1 | Format::RGB8ETC2 | Format::SRGB8ETC2 => 3,
+        _ => 4
+    }
+}
+
+// Attaches the texture to a temporary read framebuffer, so it can be read with read_pixels. The draw framebuffer is not changed.
+// Returns the temporary framebuffer and the read framebuffer which was bound before, which are passed to unbind_after_reading.
+fn bind_for_reading(gl: &Gl, id: &crate::gl::Texture, source: ReadSource, format: Format) -> Result<(crate::gl::Framebuffer, Option<crate::gl::Framebuffer>), Error>
+{
+    let previous = gl.framebuffer_binding(consts::READ_FRAMEBUFFER);
+    let framebuffer = gl.create_framebuffer()
+        .ok_or_else(|| Error::FailedToCreateFramebuffer {message: "Failed to create framebuffer".to_string()} )?;
+    gl.bind_framebuffer(consts::READ_FRAMEBUFFER, Some(&framebuffer));
+    let attachment = if is_depth_format(format) {consts::DEPTH_ATTACHMENT} else {consts::COLOR_ATTACHMENT0};
+    match source {
+        ReadSource::Image(target) => gl.framebuffer_texture_2d(consts::READ_FRAMEBUFFER, attachment, target, id, 0),
+        ReadSource::Layer(layer) => gl.framebuffer_texture_layer(consts::READ_FRAMEBUFFER, attachment, id, 0, layer)
+    }
+    gl.read_buffer(if is_depth_format(format) {consts::NONE} else {consts::COLOR_ATTACHMENT0});
+    Ok((framebuffer, previous))
+}
+
+// Deletes the temporary read framebuffer and binds the read framebuffer which was bound before, for example by a render target
+fn unbind_after_reading(gl: &Gl, (framebuffer, previous): (crate::gl::Framebuffer, Option<crate::gl::Framebuffer>))
+{
+    gl.delete_framebuffer(Some(&framebuffer));
+    gl.bind_framebuffer(consts::READ_FRAMEBUFFER, previous.as_ref());
+}
+
+// Always reads four channels since that is the only combination which is guaranteed to be supported, and then removes the channels the format does not have
+fn remove_channels<T: Copy>(pixels: Vec<T>, channels: usize) -> Vec<T>
+{
+    if channels == 4 {
+        return pixels;
+    }
+    pixels.chunks(4).flat_map(|pixel| pixel[0..channels].to_vec()).collect()
+}
+
+fn read_u8(gl: &Gl, id: &crate::gl::Texture, source: ReadSource, format: Format, width: usize, height: usize) -> Result<Vec<u8>, Error>
+{
+    match format {
+        Format::R8 | Format::RGB8 | Format::RGBA4 | Format::RGBA8 | Format::SRGB8 | Format::SRGB8Alpha8 => {},
+        _ => Err(Error::FailedToCreateTexture {message: format!("Cannot read a texture with format {:?} as 8 bit data", format)})?
+    }
+    let framebuffer = bind_for_reading(gl, id, source, format)?;
+    let mut pixels = vec![0u8; width * height * 4];
+    gl.read_pixels_with_u8_data(0, 0, width as u32, height as u32, consts::RGBA, consts::UNSIGNED_BYTE, &mut pixels);
+    unbind_after_reading(gl, framebuffer);
+    Ok(remove_channels(pixels, channel_count(format)))
+}
+
+fn read_f32(gl: &Gl, id: &crate::gl::Texture, source: ReadSource, format: Format, width: usize, height: usize) -> Result<Vec<f32>, Error>
+{
+    match format {
+        Format::R32F | Format::RGB16F | Format::RGB32F | Format::RGBA16F | Format::RGBA32F => {
+            let framebuffer = bind_for_reading(gl, id, source, format)?;
+            let mut pixels = vec![0f32; width * height * 4];
+            gl.read_pixels_with_f32_data(0, 0, width as u32, height as u32, consts::RGBA, consts::FLOAT, &mut pixels);
+            unbind_after_reading(gl, framebuffer);
+            Ok(remove_channels(pixels, channel_count(format)))
+        },
+        #[cfg(not(target_arch = "wasm32"))]
+        Format::Depth16 | Format::Depth24 | Format::Depth32F => {
+            let framebuffer = bind_for_reading(gl, id, source, format)?;
+            let mut pixels = vec![0f32; width * height];
+            gl.read_pixels_with_f32_data(0, 0, width as u32, height as u32, consts::DEPTH_COMPONENT, consts::FLOAT, &mut pixels);
+            unbind_after_reading(gl, framebuffer);
+            Ok(pixels)
+        },
+        _ => Err(Error::FailedToCreateTexture {message: format!("Cannot read a texture with format {:?} as float data", format)})
+    }
+}
+
+// Downloads a texture as an 8 bit image with the same number of channels as the texture format (depth formats have one channel).
+// Float formats are clamped to the range [0, 1]. Depth values are scaled so that the minimum depth is black and the maximum depth is white,
+// which makes for example shadow maps visible. The rows are in the same order as when uploading, that is the first row is at the bottom.
+fn read_image(gl: &Gl, id: &crate::gl::Texture, source: ReadSource, format: Format, width: usize, height: usize) -> Result<Image, Error>
+{
+    let bytes = match format {
+        Format::R8 | Format::RGB8 | Format::RGBA4 | Format::RGBA8 | Format::SRGB8 | Format::SRGB8Alpha8 => read_u8(gl, id, source, format, width, height)?,
+        Format::R32F | Format::RGB16F | Format::RGB32F | Format::RGBA16F | Format::RGBA32F => {
+            read_f32(gl, id, source, format, width, height)?.iter().map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8).collect()
+        },
+        Format::Depth16 | Format::Depth24 | Format::Depth32F => {
+            let depths = read_f32(gl, id, source, format, width, height)?;
+            let min = depths.iter().cloned().fold(f32::INFINITY, f32::min);
+            let max = depths.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            let range = if max > min {max - min} else {1.0};
+            depths.iter().map(|v| ((v - min) / range * 255.0).round() as u8).collect()
+        },
+        _ => Err(Error::FailedToCreateTexture {message: format!("Cannot read a texture with the compressed format {:?}", format)})?
+    };
+    Ok(Image {bytes, width: width as u32, height: height as u32})
+}
+
+// The constants from the EXT_texture_filter_anisotropic extension, which are the same on desktop and web
+const TEXTURE_MAX_ANISOTROPY: u32 = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: u32 = 0x84FF;
+
+fn set_anisotropy(gl: &Gl, id: &crate::gl::Texture, target: u32, max_anisotropy: f32) -> f32
+{
+    if !gl.supports_extension("EXT_texture_filter_anisotropic") {
+        return 1.0;
+    }
+    let anisotropy = max_anisotropy.clamp(1.0, gl.get_float(MAX_TEXTURE_MAX_ANISOTROPY).max(1.0));
+    gl.bind_texture(target, id);
+    gl.tex_parameterf(target, TEXTURE_MAX_ANISOTROPY, anisotropy);
+    anisotropy
+}
+
+fn set_mip_levels(gl: &Gl, id: &crate::gl::Texture, target: u32, base_level: u32, max_level: u32, number_of_mip_maps: u32) -> Result<(), Error>
+{
+    if base_level > max_level || max_level >= number_of_mip_maps {
+        Err(Error::FailedToCreateTexture {message: format!("The mip levels {} to {} are not valid for a texture with {} mip levels",
+                                                           base_level, max_level, number_of_mip_maps)})?
+    }
+    gl.bind_texture(target, id);
+    gl.tex_parameteri(target, consts::TEXTURE_BASE_LEVEL, base_level as i32);
+    gl.tex_parameteri(target, consts::TEXTURE_MAX_LEVEL, max_level as i32);
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn set_lod_bias(gl: &Gl, id: &crate::gl::Texture, target: u32, bias: f32) -> Result<(), Error>
+{
+    gl.bind_texture(target, id);
+    gl.tex_parameterf(target, consts::TEXTURE_LOD_BIAS, bias);
+    Ok(())
+}
+
+// WebGL2 has no texture LOD bias parameter, instead the bias can be given as the third argument to texture() in the shader
+#[cfg(target_arch = "wasm32")]
+fn set_lod_bias(_gl: &Gl, _id: &crate::gl::Texture, _target: u32, _bias: f32) -> Result<(), Error>
+{
+    Err(Error::FailedToCreateTexture {message: "LOD bias is not supported in WebGL2, use the bias argument of texture() in the shader instead".to_string()})
+}
+
 fn set_parameters(gl: &Gl, id: &crate::gl::Texture, target: u32, min_filter: Interpolation, mag_filter: Interpolation, mip_map_filter: Option<Interpolation>, wrap_s: Wrapping, wrap_t: Wrapping, wrap_r: Option<Wrapping>)
 {
     gl.bind_texture(target, id);
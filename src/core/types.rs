@@ -7,6 +7,7 @@ pub use cgmath::ortho;
 pub type Vec2 = Vector2<f32>;
 pub type Vec3 = Vector3<f32>;
 pub type Vec4 = Vector4<f32>;
+pub type Vec3d = Vector3<f64>;
 pub type Mat2 = Matrix2<f32>;
 pub type Mat3 = Matrix3<f32>;
 pub type Mat4 = Matrix4<f32>;
@@ -29,6 +30,11 @@ pub fn vec4(x: f32, y: f32, z: f32, w: f32) -> Vec4
     Vector4::new(x, y, z, w)
 }
 
+pub fn vec3d(x: f64, y: f64, z: f64) -> Vec3d
+{
+    Vector3::new(x, y, z)
+}
+
 pub trait Vec2Ext {
     fn to_slice(&self) -> [f32; 2];
 }
@@ -12,7 +12,7 @@ impl FogEffect {
 
     pub fn new(gl: &Gl) -> Result<FogEffect, Error>
     {
-        Ok(FogEffect {gl: gl.clone(), color: vec3(0.8, 0.8, 0.8), density: 0.2, animation: 0.1, image_effect: ImageEffect::new(gl, include_str!("shaders/fog.frag"))?})
+        Ok(FogEffect {gl: gl.clone(), color: vec3(0.8, 0.8, 0.8), density: 0.2, animation: 0.1, image_effect: ImageEffect::from_shader_source(gl, &ShaderSource::from_segments(vec![shader_segment!("shaders/fog.frag")]))?})
     }
 
     pub fn apply(&self, time: f32, camera: &camera::Camera, depth_texture: &Texture2DArray) -> Result<(), Error>
@@ -22,7 +22,7 @@ impl FogEffect {
         state::blend(&self.gl, state::BlendType::SrcAlphaOneMinusSrcAlpha);
 
         self.image_effect.program().use_texture(depth_texture, "depthMap")?;
-        self.image_effect.program().add_uniform_mat4("viewProjectionInverse", &(camera.get_projection() * camera.get_view()).invert().unwrap())?;
+        self.image_effect.program().add_uniform_mat4("viewProjectionInverse", &camera.view_projection_inverse())?;
         self.image_effect.program().add_uniform_vec3("fogColor", &self.color)?;
         self.image_effect.program().add_uniform_float("fogDensity", &self.density)?;
         self.image_effect.program().add_uniform_float("animation", &self.animation)?;
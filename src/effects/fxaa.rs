@@ -12,7 +12,7 @@ impl FXAAEffect {
 
     pub fn new(gl: &Gl) -> Result<Self, Error>
     {
-        Ok(Self {gl: gl.clone(), color: vec3(0.8, 0.8, 0.8), density: 0.2, animation: 0.1, image_effect: ImageEffect::new(gl, include_str!("shaders/fxaa.frag"))?})
+        Ok(Self {gl: gl.clone(), color: vec3(0.8, 0.8, 0.8), density: 0.2, animation: 0.1, image_effect: ImageEffect::from_shader_source(gl, &ShaderSource::from_segments(vec![shader_segment!("shaders/fxaa.frag")]))?})
     }
 
     pub fn apply(&self, color_texture: &Texture2D) -> Result<(), Error>
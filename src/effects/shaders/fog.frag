@@ -139,7 +139,8 @@ void main()
     vec3 pos = WorldPosFromDepth(depth, uv);
 
     // Distance
-    float dist = depth < 0.999f ? distance(pos, eyePosition) : 100.f;
+    // The depth is 1 at the far plane, or 0 if the depth is reversed
+    float dist = depth > 0.0 && depth < 1.0 ? distance(pos, eyePosition) : 100.f;
 
     float x = dist * fogDensity;
     float factor = 1. - 1. / exp(x * x);
@@ -1,4 +1,5 @@
 uniform samplerCube texture0;
+uniform float exposure;
 
 layout (std140) uniform Camera
 {
@@ -6,7 +7,7 @@ layout (std140) uniform Camera
     mat4 view;
     mat4 projection;
     vec3 position;
-    float padding;
+    float farClipDepth;
 } camera;
 
 in vec3 coords;
@@ -14,5 +15,5 @@ in vec3 coords;
 layout (location = 0) out vec4 color;
 
 void main() {
-    color = vec4(texture(texture0, coords).rgb, 1.0);
+    color = vec4(exposure * texture(texture0, coords).rgb, 1.0);
 }
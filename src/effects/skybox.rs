@@ -6,29 +6,40 @@ pub struct Skybox {
     gl: Gl,
     program: program::Program,
     vertex_buffer: VertexBuffer,
-    texture: texture::TextureCubeMap
+    texture: texture::TextureCubeMap,
+    exposure: f32
 }
 
 impl Skybox
 {
     pub fn new(gl: &Gl, right: &Image, left: &Image, top: &Image, front: &Image, back: &Image) -> Result<Skybox, Error>
     {
-        let texture = TextureCubeMap::new_with_u8(&gl,
+        let texture = TextureCubeMap::new_with_srgb_u8(&gl,
                                                   Interpolation::Linear, Interpolation::Linear, None,
                                                   Wrapping::ClampToEdge, Wrapping::ClampToEdge, Wrapping::ClampToEdge,
                                                   right, left, top, top, front, back)?;
         Self::new_with_texture(gl, texture)
     }
 
+    ///
+    /// Creates a skybox from an equirectangular panorama with high dynamic range, for example loaded from a .hdr file with
+    /// [Loader::get_hdr_image](struct.Loader.html#method.get_hdr_image). Use [set_exposure](#method.set_exposure) to control the brightness.
+    ///
+    pub fn new_from_equirectangular(gl: &Gl, image: &HDRImage) -> Result<Skybox, Error>
+    {
+        let texture = TextureCubeMap::new_from_equirectangular(gl, Interpolation::Linear, Interpolation::Linear, Some(Interpolation::Linear),
+                                                               image, (image.width as usize / 4).max(1))?;
+        Self::new_with_texture(gl, texture)
+    }
+
     pub fn new_with_texture(gl: &Gl, texture: texture::TextureCubeMap) -> Result<Skybox, Error>
     {
-        let program = program::Program::from_source(gl,
-                                                    include_str!("shaders/skybox.vert"),
-                                                    include_str!("shaders/skybox.frag"))?;
+        let program = program::Program::from_shader_source(gl, &ShaderSource::from_segments(vec![shader_segment!("shaders/skybox.vert")]),
+                                                    &ShaderSource::from_segments(vec![shader_segment!("shaders/skybox.frag")]))?;
 
         let vertex_buffer = VertexBuffer::new_with_static_f32(gl, &get_positions())?;
 
-        Ok(Skybox { gl: gl.clone(), program, vertex_buffer, texture })
+        Ok(Skybox { gl: gl.clone(), program, vertex_buffer, texture, exposure: 1.0 })
     }
 
     pub fn apply(&self, camera: &camera::Camera) -> Result<(), Error>
@@ -39,6 +50,7 @@ impl Skybox
         state::blend(&self.gl, state::BlendType::None);
 
         self.program.use_texture(&self.texture, "texture0")?;
+        self.program.add_uniform_float("exposure", &self.exposure)?;
         self.program.use_uniform_block(camera.matrix_buffer(), "Camera");
 
         self.program.use_attribute_vec3_float(&self.vertex_buffer, "position")?;
@@ -48,6 +60,19 @@ impl Skybox
         Ok(())
     }
 
+    ///
+    /// Sets the factor which the colors of the skybox are multiplied with, which is mostly useful for skyboxes with high dynamic range. The default is 1.
+    ///
+    pub fn set_exposure(&mut self, exposure: f32)
+    {
+        self.exposure = exposure;
+    }
+
+    pub fn exposure(&self) -> f32
+    {
+        self.exposure
+    }
+
     pub fn get_texture(&self) -> &texture::TextureCubeMap
     {
         &self.texture
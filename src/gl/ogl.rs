@@ -22,6 +22,8 @@ impl ActiveInfo {
     pub fn name(&self) -> String {self.name.clone()}
 }
 
+pub const SHADER_HEADER: &str = "#version 330 core\n";
+
 pub struct Glstruct {
     inner: InnerGl
 }
@@ -50,10 +52,9 @@ impl Glstruct {
         Some(id)
     }
 
-    pub fn compile_shader(&self, source: &str, shader: &Shader)
+    pub fn compile_shader(&self, source: &str, shader: &Shader) -> bool
     {
-        let header = "#version 330 core\n";
-        let s: &str = &[header, source].concat();
+        let s: &str = &[SHADER_HEADER, source].concat();
 
         use std::ffi::{CStr, CString};
         let c_str: &CStr = &CString::new(s).unwrap();
@@ -62,6 +63,12 @@ impl Glstruct {
             self.inner.ShaderSource(*shader, 1, &c_str.as_ptr(), std::ptr::null());
             self.inner.CompileShader(*shader);
         }
+
+        let mut success: consts::types::GLint = 1;
+        unsafe {
+            self.inner.GetShaderiv(*shader, consts::COMPILE_STATUS, &mut success);
+        }
+        success == 1
     }
 
     pub fn get_shader_info_log(&self, shader: &Shader) -> Option<String> {
@@ -143,6 +150,37 @@ impl Glstruct {
         ActiveInfo::new(size as u32, _type as u32, s)
     }
 
+    pub fn get_active_uniform_block_parameter(&self, program: &Program, index: u32, pname: u32) -> u32
+    {
+        let mut out = 0;
+        unsafe {
+            self.inner.GetActiveUniformBlockiv(*program, index, pname, &mut out);
+        }
+        out as u32
+    }
+
+    pub fn get_active_uniform_block_name(&self, program: &Program, index: u32) -> String
+    {
+        let mut length = 128;
+        let name = create_whitespace_cstring_with_len(length as usize);
+        unsafe {
+            self.inner.GetActiveUniformBlockName(*program, index, length, &mut length, name.as_ptr() as *mut consts::types::GLchar);
+        }
+
+        let mut s = name.to_string_lossy().into_owned();
+        s.truncate(length as usize);
+        s
+    }
+
+    pub fn get_active_uniforms_parameter(&self, program: &Program, indices: &[u32], pname: u32) -> Vec<i32>
+    {
+        let mut out = vec![0; indices.len()];
+        unsafe {
+            self.inner.GetActiveUniformsiv(*program, indices.len() as i32, indices.as_ptr(), pname, out.as_mut_ptr());
+        }
+        out
+    }
+
     pub fn create_buffer(&self) -> Option<Buffer>
     {
         let mut id: u32 = 0;
@@ -256,6 +294,18 @@ impl Glstruct {
         }
     }
 
+    pub fn buffer_sub_data_u8(&self, target: u32, offset_in_bytes: u32, data: &[u8])
+    {
+        unsafe {
+            self.inner.BufferSubData(
+                target,
+                offset_in_bytes as consts::types::GLintptr,
+                data.len() as consts::types::GLsizeiptr, // size of data in bytes
+                data.as_ptr() as *const consts::types::GLvoid // pointer to data
+            );
+        }
+    }
+
     pub fn create_vertex_array(&self) -> Option<VertexArrayObject>
     {
         let mut id: u32 = 0;
@@ -377,6 +427,24 @@ impl Glstruct {
         }
     }
 
+    pub fn vertex_attrib_pointer_with_byte_offset(&self, location: AttributeLocation, size: u32, data_type: u32, normalized: bool, byte_stride: u32, byte_offset: u32)
+    {
+        unsafe {
+            self.inner.VertexAttribPointer(location as consts::types::GLuint, size as consts::types::GLint, data_type as consts::types::GLenum,
+                                           normalized as consts::types::GLboolean, byte_stride as consts::types::GLint,
+                                           byte_offset as usize as *const consts::types::GLvoid);
+        }
+    }
+
+    // Integer attributes which are not converted to floating point (for example joint indices)
+    pub fn vertex_attrib_i_pointer_with_byte_offset(&self, location: AttributeLocation, size: u32, data_type: u32, byte_stride: u32, byte_offset: u32)
+    {
+        unsafe {
+            self.inner.VertexAttribIPointer(location as consts::types::GLuint, size as consts::types::GLint, data_type as consts::types::GLenum,
+                                            byte_stride as consts::types::GLint, byte_offset as usize as *const consts::types::GLvoid);
+        }
+    }
+
     pub fn vertex_attrib_divisor(&self, location: AttributeLocation, divisor: u32)
     {
         unsafe {
@@ -417,7 +485,7 @@ impl Glstruct {
     pub fn uniform3fv(&self, location: &UniformLocation, data: &[f32])
     {
         unsafe {
-            self.inner.Uniform3fv(*location as i32, 1, data.as_ptr());
+            self.inner.Uniform3fv(*location as i32, data.len() as i32 / 3, data.as_ptr());
         }
     }
 
@@ -449,6 +517,13 @@ impl Glstruct {
         }
     }
 
+    pub fn read_buffer(&self, src: u32)
+    {
+        unsafe {
+            self.inner.ReadBuffer(src);
+        }
+    }
+
     pub fn draw_buffers(&self, draw_buffers: &[u32])
     {
         unsafe {
@@ -473,6 +548,19 @@ impl Glstruct {
         }
     }
 
+    ///
+    /// The framebuffer which is currently bound to the given target, that is, DRAW_FRAMEBUFFER or READ_FRAMEBUFFER, or None for the default framebuffer.
+    ///
+    pub fn framebuffer_binding(&self, target: u32) -> Option<Framebuffer>
+    {
+        let pname = if target == consts::READ_FRAMEBUFFER { consts::READ_FRAMEBUFFER_BINDING } else { consts::DRAW_FRAMEBUFFER_BINDING };
+        let mut id = 0;
+        unsafe {
+            self.inner.GetIntegerv(pname, &mut id);
+        }
+        if id == 0 { None } else { Some(id as Framebuffer) }
+    }
+
     pub fn delete_framebuffer(&self, framebuffer: Option<&Framebuffer>)
     {
         let id = match framebuffer { Some(fb) => fb, None => &0 };
@@ -517,6 +605,31 @@ impl Glstruct {
         }
     }
 
+    ///
+    /// Whether the depth range of the clip space can be changed to [0, 1], see [set_clip_depth_zero_to_one](Glstruct::set_clip_depth_zero_to_one).
+    ///
+    pub fn supports_clip_control(&self) -> bool
+    {
+        self.inner.ClipControl.is_loaded()
+    }
+
+    ///
+    /// Changes the depth range of the clip space from [-1, 1] to [0, 1], which avoids a loss of precision when the depth is mapped to the depth buffer.
+    ///
+    pub fn set_clip_depth_zero_to_one(&self, zero_to_one: bool)
+    {
+        unsafe {
+            self.inner.ClipControl(consts::LOWER_LEFT, if zero_to_one { consts::ZERO_TO_ONE } else { consts::NEGATIVE_ONE_TO_ONE });
+        }
+    }
+
+    pub fn scissor(&self, x: i32, y: i32, width: usize, height: usize)
+    {
+        unsafe {
+            self.inner.Scissor(x, y, width as i32, height as i32);
+        }
+    }
+
     pub fn clear_color(&self, red: f32, green: f32, blue: f32, alpha: f32)
     {
         unsafe {
@@ -664,6 +777,20 @@ impl Glstruct {
         }
     }
 
+    pub fn tex_sub_image_3d_with_u8_data(&self, target: u32, level: u32, x_offset: u32, y_offset: u32, z_offset: u32, width: u32, height: u32, depth: u32, format: u32, data_type: u32, pixels: &[u8])
+    {
+        unsafe {
+            self.inner.TexSubImage3D(target, level as i32, x_offset as i32, y_offset as i32, z_offset as i32, width as i32, height as i32, depth as i32, format, data_type, pixels.as_ptr() as *const consts::types::GLvoid);
+        }
+    }
+
+    pub fn tex_sub_image_3d_with_f32_data(&self, target: u32, level: u32, x_offset: u32, y_offset: u32, z_offset: u32, width: u32, height: u32, depth: u32, format: u32, data_type: u32, pixels: &[f32])
+    {
+        unsafe {
+            self.inner.TexSubImage3D(target, level as i32, x_offset as i32, y_offset as i32, z_offset as i32, width as i32, height as i32, depth as i32, format, data_type, pixels.as_ptr() as *const consts::types::GLvoid);
+        }
+    }
+
     pub fn tex_image_3d(&self, target: u32, level: u32, internalformat: u32, width: u32, height: u32, depth: u32, format: u32, data_type: u32)
     {
         unsafe {
@@ -678,6 +805,54 @@ impl Glstruct {
         }
     }
 
+    pub fn compressed_tex_sub_image_2d(&self, target: u32, level: u32, x_offset: u32, y_offset: u32, width: u32, height: u32, format: u32, data: &[u8])
+    {
+        unsafe {
+            self.inner.CompressedTexSubImage2D(target, level as i32, x_offset as i32, y_offset as i32, width as i32, height as i32, format,
+                                               data.len() as i32, data.as_ptr() as *const consts::types::GLvoid);
+        }
+    }
+
+    pub fn tex_parameterf(&self, target: u32, pname: u32, param: f32)
+    {
+        unsafe {
+            self.inner.TexParameterf(target, pname, param);
+        }
+    }
+
+    pub fn tex_image_3d_with_u8_data(&self, target: u32, level: u32, internalformat: u32, width: u32, height: u32, depth: u32, format: u32, data_type: u32, pixels: &[u8])
+    {
+        unsafe {
+            self.inner.TexImage3D(target, level as i32, internalformat as i32, width as i32, height as i32, depth as i32, 0, format, data_type, pixels.as_ptr() as *const consts::types::GLvoid);
+        }
+    }
+
+    pub fn get_float(&self, pname: u32) -> f32
+    {
+        let mut value = 0.0;
+        unsafe {
+            self.inner.GetFloatv(pname, &mut value);
+        }
+        value
+    }
+
+    ///
+    /// Returns whether the extension is supported. The name is given without the "GL_" prefix, for example "EXT_texture_filter_anisotropic",
+    /// which is the same name as in WebGL.
+    ///
+    pub fn supports_extension(&self, name: &str) -> bool
+    {
+        unsafe {
+            let mut count = 0;
+            self.inner.GetIntegerv(consts::NUM_EXTENSIONS, &mut count);
+            (0..count as u32).any(|i| {
+                let extension = self.inner.GetStringi(consts::EXTENSIONS, i);
+                !extension.is_null() && std::ffi::CStr::from_ptr(extension as *const std::os::raw::c_char)
+                    .to_str().map(|e| e.trim_start_matches("GL_") == name).unwrap_or(false)
+            })
+        }
+    }
+
     pub fn delete_texture(&self, texture: &Texture)
     {
         unsafe {
@@ -806,12 +981,15 @@ fn create_whitespace_cstring_with_len(len: usize) -> std::ffi::CString {
 pub fn byte_size_for_type(data_type: u32, count: u32) -> u32
 {
     match data_type {
-        consts::FLOAT => {
-            count * std::mem::size_of::<f32>() as u32
-        },
-        consts::UNSIGNED_INT => {
+        consts::FLOAT | consts::INT | consts::UNSIGNED_INT => {
             count * std::mem::size_of::<u32>() as u32
-        }
+        },
+        consts::HALF_FLOAT | consts::SHORT | consts::UNSIGNED_SHORT => {
+            count * std::mem::size_of::<u16>() as u32
+        },
+        consts::BYTE | consts::UNSIGNED_BYTE => {
+            count
+        },
         _ => { 0 }
     }
 }
\ No newline at end of file
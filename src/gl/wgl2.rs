@@ -15,6 +15,8 @@ pub use web_sys::WebGlVertexArrayObject as VertexArrayObject;
 pub use web_sys::WebGlActiveInfo as ActiveInfo;
 pub use web_sys::WebGlSync as Sync;
 
+pub const SHADER_HEADER: &str = "#version 300 es\nprecision highp float;\nprecision highp int;\nprecision highp sampler2DArray;\nprecision highp sampler3D;\n";
+
 #[derive(Clone)]
 pub struct Glstruct {
     inner: InnerGl,
@@ -63,6 +65,11 @@ impl Glstruct {
         self.inner.buffer_data_with_u8_array(target, data, usage)
     }
 
+    pub fn buffer_sub_data_u8(&self, target: u32, offset_in_bytes: u32, data: &[u8])
+    {
+        self.inner.buffer_sub_data_with_i32_and_u8_array(target, offset_in_bytes as i32, data)
+    }
+
     pub fn buffer_data_u32(&self, target: u32, data: &[u32], usage: u32)
     {
         use wasm_bindgen::JsCast;
@@ -97,13 +104,13 @@ impl Glstruct {
         );
     }
 
-    pub fn compile_shader(&self, source: &str, shader: &Shader)
+    pub fn compile_shader(&self, source: &str, shader: &Shader) -> bool
     {
-        let header = "#version 300 es\nprecision highp float;\nprecision highp int;\nprecision highp sampler2DArray;\n";
-        let s: &str = &[header, source].concat();
+        let s: &str = &[SHADER_HEADER, source].concat();
 
         self.inner.shader_source(shader, s);
         self.inner.compile_shader(shader);
+        self.inner.get_shader_parameter(shader, consts::COMPILE_STATUS).as_bool().unwrap_or(false)
     }
 
     pub fn create_program(&self) -> Program
@@ -168,6 +175,26 @@ impl Glstruct {
                                                                                               Some(pixels)).unwrap();
     }
 
+    pub fn tex_sub_image_3d_with_u8_data(&self, target: u32, level: u32, x_offset: u32, y_offset: u32, z_offset: u32, width: u32, height: u32, depth: u32, format: u32, data_type: u32, pixels: &[u8])
+    {
+        self.inner.tex_sub_image_3d_with_opt_u8_array(target, level as i32, x_offset as i32, y_offset as i32, z_offset as i32,
+                                                      width as i32, height as i32, depth as i32, format, data_type, Some(pixels)).unwrap();
+    }
+
+    pub fn tex_sub_image_3d_with_f32_data(&self, target: u32, level: u32, x_offset: u32, y_offset: u32, z_offset: u32, width: u32, height: u32, depth: u32, format: u32, data_type: u32, pixels: &[f32])
+    {
+        use wasm_bindgen::JsCast;
+        let memory_buffer = wasm_bindgen::memory()
+            .dyn_into::<js_sys::WebAssembly::Memory>().unwrap()
+            .buffer();
+        let data_location = pixels.as_ptr() as u32 / 4;
+        let array = js_sys::Float32Array::new(&memory_buffer)
+            .subarray(data_location, data_location + pixels.len() as u32);
+
+        self.inner.tex_sub_image_3d_with_opt_array_buffer_view(target, level as i32, x_offset as i32, y_offset as i32, z_offset as i32,
+                                                               width as i32, height as i32, depth as i32, format, data_type, Some(&array)).unwrap();
+    }
+
     pub fn tex_image_2d_with_u8_data(&self, target: u32, level: u32, internalformat: u32, width: u32, height: u32, border: u32, format: u32, data_type: u32, pixels: &[u8])
     {
         self.inner.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(target,
@@ -224,6 +251,30 @@ impl Glstruct {
 
     }
 
+    pub fn compressed_tex_sub_image_2d(&self, target: u32, level: u32, x_offset: u32, y_offset: u32, width: u32, height: u32, format: u32, data: &[u8])
+    {
+        self.inner.compressed_tex_sub_image_2d_with_u8_array(target, level as i32, x_offset as i32, y_offset as i32, width as i32, height as i32, format, data);
+    }
+
+    pub fn tex_image_3d_with_u8_data(&self, target: u32, level: u32, internalformat: u32, width: u32, height: u32, depth: u32, format: u32, data_type: u32, pixels: &[u8])
+    {
+        self.inner.tex_image_3d_with_opt_u8_array(target, level as i32, internalformat as i32, width as i32, height as i32, depth as i32,
+                                                  0, format, data_type, Some(pixels)).unwrap();
+    }
+
+    pub fn get_float(&self, pname: u32) -> f32
+    {
+        self.inner.get_parameter(pname).ok().and_then(|v| v.as_f64()).unwrap_or(0.0) as f32
+    }
+
+    ///
+    /// Returns whether the extension is supported and enables it, since WebGL extensions has to be enabled before use.
+    ///
+    pub fn supports_extension(&self, name: &str) -> bool
+    {
+        self.inner.get_extension(name).ok().flatten().is_some()
+    }
+
     pub fn framebuffer_texture_2d(&self, target: u32, attachment: u32, textarget: u32, texture: &Texture, level: u32)
     {
         self.inner.framebuffer_texture_2d(target, attachment, textarget, Some(texture), level as i32);
@@ -234,11 +285,38 @@ impl Glstruct {
         self.inner.framebuffer_texture_layer(target, attachment, Some(texture), level as i32, layer as i32);
     }
 
+    ///
+    /// The framebuffer which is currently bound to the given target, that is, DRAW_FRAMEBUFFER or READ_FRAMEBUFFER, or None for the default framebuffer.
+    ///
+    pub fn framebuffer_binding(&self, target: u32) -> Option<Framebuffer>
+    {
+        use wasm_bindgen::JsCast;
+        let pname = if target == consts::READ_FRAMEBUFFER { consts::READ_FRAMEBUFFER_BINDING } else { consts::DRAW_FRAMEBUFFER_BINDING };
+        self.inner.get_parameter(pname).ok().and_then(|binding| binding.dyn_into::<Framebuffer>().ok())
+    }
+
     pub fn viewport(&self, x: i32, y: i32, width: usize, height: usize)
     {
         self.inner.viewport(x, y, width as i32, height as i32);
     }
 
+    ///
+    /// Changing the depth range of the clip space is not supported in WebGL 2.
+    ///
+    pub fn supports_clip_control(&self) -> bool
+    {
+        false
+    }
+
+    pub fn set_clip_depth_zero_to_one(&self, _zero_to_one: bool)
+    {
+    }
+
+    pub fn scissor(&self, x: i32, y: i32, width: usize, height: usize)
+    {
+        self.inner.scissor(x, y, width as i32, height as i32);
+    }
+
     pub fn get_attrib_location(&self, program: &Program, name: &str) -> Option<AttributeLocation>
     {
         Some(self.inner.get_attrib_location(program, name) as u32)
@@ -280,12 +358,12 @@ impl Glstruct {
 
     pub fn draw_elements(&self, mode: u32, count: u32, data_type: u32, offset: u32)
     {
-        self.inner.draw_elements_with_i32(mode, count as i32, data_type, offset as i32);
+        self.inner.draw_elements_with_i32(mode, count as i32, data_type, byte_size_for_type(data_type, offset) as i32);
     }
 
     pub fn draw_elements_instanced(&self, mode: u32, count: u32, data_type: u32, offset: u32, instance_count: u32)
     {
-        self.inner.draw_elements_instanced_with_i32(mode, count as i32, data_type, offset as i32, instance_count as i32);
+        self.inner.draw_elements_instanced_with_i32(mode, count as i32, data_type, byte_size_for_type(data_type, offset) as i32, instance_count as i32);
     }
 
     pub fn blit_framebuffer(&self, src_x0: u32, src_y0: u32, src_x1: u32, src_y1: u32,
@@ -295,6 +373,28 @@ impl Glstruct {
                                     dst_x0 as i32, dst_y0 as i32, dst_x1 as i32, dst_y1 as i32, mask, filter);
     }
 
+    pub fn read_buffer(&self, src: u32)
+    {
+        self.inner.read_buffer(src);
+    }
+
+    pub fn read_pixels_with_u8_data(&self, x: u32, y: u32, width: u32, height: u32, format: u32, data_type: u32, dst_data: &mut [u8])
+    {
+        self.inner.read_pixels_with_opt_u8_array(x as i32, y as i32, width as i32, height as i32, format, data_type, Some(dst_data)).unwrap();
+    }
+
+    pub fn read_pixels_with_f32_data(&self, x: u32, y: u32, width: u32, height: u32, format: u32, data_type: u32, dst_data: &mut [f32])
+    {
+        use wasm_bindgen::JsCast;
+        let memory_buffer = wasm_bindgen::memory()
+            .dyn_into::<js_sys::WebAssembly::Memory>().unwrap()
+            .buffer();
+        let data_location = dst_data.as_ptr() as u32 / 4;
+        let array = js_sys::Float32Array::new(&memory_buffer)
+            .subarray(data_location, data_location + dst_data.len() as u32);
+        self.inner.read_pixels_with_opt_array_buffer_view(x as i32, y as i32, width as i32, height as i32, format, data_type, Some(&array)).unwrap();
+    }
+
     pub fn draw_buffers(&self, draw_buffers: &[u32])
     {
         use wasm_bindgen::JsCast;
@@ -368,6 +468,17 @@ impl Glstruct {
                                                   byte_size_for_type(data_type, stride) as i32, byte_size_for_type(data_type, offset)  as i32);
     }
 
+    pub fn vertex_attrib_pointer_with_byte_offset(&self, location: AttributeLocation, size: u32, data_type: u32, normalized: bool, byte_stride: u32, byte_offset: u32)
+    {
+        self.inner.vertex_attrib_pointer_with_i32(location, size as i32, data_type, normalized, byte_stride as i32, byte_offset as i32);
+    }
+
+    // Integer attributes which are not converted to floating point (for example joint indices)
+    pub fn vertex_attrib_i_pointer_with_byte_offset(&self, location: AttributeLocation, size: u32, data_type: u32, byte_stride: u32, byte_offset: u32)
+    {
+        self.inner.vertex_attrib_i_pointer_with_i32(location, size as i32, data_type, byte_stride as i32, byte_offset as i32);
+    }
+
     pub fn get_program_parameter(&self, program: &Program, pname: u32) -> u32
     {
         let result = self.inner.get_program_parameter(program, pname);
@@ -384,6 +495,27 @@ impl Glstruct {
         self.inner.get_active_uniform(program, index).unwrap()
     }
 
+    pub fn get_active_uniform_block_parameter(&self, program: &Program, index: u32, pname: u32) -> u32
+    {
+        let result = self.inner.get_active_uniform_block_parameter(program, index, pname).unwrap();
+        result.as_f64().unwrap() as u32
+    }
+
+    pub fn get_active_uniform_block_name(&self, program: &Program, index: u32) -> String
+    {
+        self.inner.get_active_uniform_block_name(program, index).unwrap()
+    }
+
+    pub fn get_active_uniforms_parameter(&self, program: &Program, indices: &[u32], pname: u32) -> Vec<i32>
+    {
+        let array = js_sys::Array::new();
+        for index in indices {
+            array.push(&wasm_bindgen::JsValue::from(*index));
+        }
+        let result: js_sys::Array = self.inner.get_active_uniforms(program, &array, pname).into();
+        result.iter().map(|value| value.as_f64().unwrap_or(0.0) as i32).collect()
+    }
+
     pub fn fence_sync(&self) -> Sync {
         self.inner.fence_sync(consts::SYNC_GPU_COMMANDS_COMPLETE, 0).unwrap()
     }
@@ -408,12 +540,15 @@ impl std::ops::Deref for Glstruct {
 pub fn byte_size_for_type(data_type: u32, count: u32) -> u32
 {
     match data_type {
-        consts::FLOAT => {
-            count * std::mem::size_of::<f32>() as u32
-        },
-        consts::UNSIGNED_INT => {
+        consts::FLOAT | consts::INT | consts::UNSIGNED_INT => {
             count * std::mem::size_of::<u32>() as u32
-        }
+        },
+        consts::HALF_FLOAT | consts::SHORT | consts::UNSIGNED_SHORT => {
+            count * std::mem::size_of::<u16>() as u32
+        },
+        consts::BYTE | consts::UNSIGNED_BYTE => {
+            count
+        },
         _ => { 0 }
     }
 }
\ No newline at end of file
@@ -16,6 +16,12 @@ pub mod obj;
 #[cfg(feature = "obj-io")]
 pub use obj::*;
 
+pub mod ktx2;
+pub use ktx2::*;
+
+pub mod dds;
+pub use dds::*;
+
 
 #[derive(Debug)]
 pub enum Error {
@@ -104,6 +110,31 @@ impl Loader {
         Ok(crate::Image {bytes: img.to_bytes(), width: img.width(), height: img.height()})
     }
 
+    ///
+    /// Returns the image with high dynamic range from a Radiance .hdr file, for example an equirectangular environment map.
+    ///
+    #[cfg(feature = "image-io")]
+    pub fn get_hdr_image<P: AsRef<Path>>(loaded: &Loaded, path: P) -> Result<crate::HDRImage, Error> {
+        let decoder = image::hdr::HdrDecoder::new(Self::get(loaded, path)?)?;
+        let metadata = decoder.metadata();
+        let pixels = decoder.read_image_hdr()?.iter().flat_map(|pixel| pixel.0.to_vec()).collect();
+        Ok(crate::HDRImage {pixels, width: metadata.width, height: metadata.height})
+    }
+
+    ///
+    /// Returns the compressed image with its mip chain from a KTX2 or DDS file, see [Ktx2](struct.Ktx2.html) and [Dds](struct.Dds.html).
+    ///
+    pub fn get_compressed_image<P: AsRef<Path>>(loaded: &Loaded, path: P) -> Result<crate::CompressedImage, Error> {
+        let bytes = Self::get(loaded, path.as_ref())?;
+        if Ktx2::is_ktx2(bytes) {
+            Ktx2::parse_bytes(bytes)
+        } else if Dds::is_dds(bytes) {
+            Dds::parse_bytes(bytes)
+        } else {
+            Err(Error::FailedToLoad {message: format!("{} is neither a KTX2 nor a DDS file", path.as_ref().to_str().unwrap())})
+        }
+    }
+
     fn wait_local<F, G>(loads: RefLoaded, progress_callback: G, on_done: F)
         where
             G: 'static + Fn(f32),
@@ -231,23 +262,39 @@ impl Saver {
         Ok(())
     }
 
+    ///
+    /// Saves pixels read from the GPU, for example with [Screen::read_color](../struct.Screen.html#method.read_color) or
+    /// [Texture2D::read_image](../struct.Texture2D.html#method.read_image), as an image file.
+    /// The number of channels (1, 3 or 4) is deduced from the length of the pixel data, and the rows are flipped since the first row read from the GPU is the bottom row.
+    ///
     #[cfg(feature = "image-io")]
     pub fn save_pixels<P: AsRef<Path>>(path: P, pixels: &[u8], width: usize, height: usize) -> Result<(), Error>
     {
-        let mut pixels_out = vec![0u8; width * height * 3];
+        let number_of_channels = pixels.len().checked_div(width * height).unwrap_or(0);
+        let format = match number_of_channels {
+            1 => Ok(image::ColorType::L8),
+            3 => Ok(image::ColorType::Rgb8),
+            4 => Ok(image::ColorType::Rgba8),
+            _ => Err(Error::FailedToSave {message: format!("Cannot save pixels with {} bytes as a {}x{} image", pixels.len(), width, height)})
+        }?;
+
+        let row_length = number_of_channels * width;
+        let mut pixels_out = vec![0u8; row_length * height];
         for row in 0..height {
-            for col in 0..width {
-                for i in 0..3 {
-                    pixels_out[3 * width * (height - row - 1) + 3 * col + i] =
-                        pixels[3 * width * row + 3 * col + i];
-                }
-            }
+            pixels_out[row_length * (height - row - 1)..row_length * (height - row)]
+                .copy_from_slice(&pixels[row_length * row..row_length * (row + 1)]);
         }
 
-        image::save_buffer(path, &pixels_out, width as u32, height as u32, image::ColorType::Rgb8)?;
+        image::save_buffer(path, &pixels_out, width as u32, height as u32, format)?;
         Ok(())
     }
 
+    #[cfg(feature = "image-io")]
+    pub fn save_image<P: AsRef<Path>>(path: P, image: &crate::Image) -> Result<(), Error>
+    {
+        Self::save_pixels(path, &image.bytes, image.width as usize, image.height as usize)
+    }
+
     pub fn save_file<P: AsRef<Path>>(path: P, bytes: &[u8]) -> Result<(), Error>
     {
         let mut file = std::fs::File::create(path)?;
@@ -0,0 +1,136 @@
+use crate::io::*;
+use crate::io::ktx2::read_u32;
+use std::path::Path;
+use crate::{CompressedImage, Format};
+
+///
+/// Parses DDS files which contain a 2D texture compressed with DXT1, DXT3 or DXT5 (BC1, BC2 or BC3), including files with the DX10 header.
+///
+pub struct Dds {
+
+}
+
+impl Dds {
+    pub fn parse<P: AsRef<Path>>(loaded: &Loaded, path: P) -> Result<CompressedImage, Error>
+    {
+        Self::parse_bytes(Loader::get(loaded, path)?)
+    }
+
+    pub fn is_dds(bytes: &[u8]) -> bool
+    {
+        bytes.starts_with(b"DDS ")
+    }
+
+    pub fn parse_bytes(bytes: &[u8]) -> Result<CompressedImage, Error>
+    {
+        if !Self::is_dds(bytes) || bytes.len() < 128 {
+            Err(Error::FailedToLoad {message: "Not a DDS file".to_string()})?
+        }
+        let flags = read_u32(bytes, 8)?;
+        let height = read_u32(bytes, 12)?;
+        let width = read_u32(bytes, 16)?;
+        // A 32 bit size cannot have more than 32 mip levels
+        let level_count = if flags & MIP_MAP_COUNT != 0 {read_u32(bytes, 28)?.clamp(1, 32)} else {1};
+        let four_cc = &bytes[84..88];
+
+        let (format, mut offset): (Format, usize) = if four_cc == b"DX10" {
+            let dimension = read_u32(bytes, 132)?;
+            let array_size = read_u32(bytes, 140)?;
+            if dimension != 3 || array_size > 1 {
+                Err(Error::FailedToLoad {message: "Only 2D textures are supported in DDS files".to_string()})?
+            }
+            (format_from_dxgi_format(read_u32(bytes, 128)?)?, 148)
+        } else {
+            let format = match four_cc {
+                b"DXT1" => Format::RGBADXT1,
+                b"DXT3" => Format::RGBADXT3,
+                b"DXT5" => Format::RGBADXT5,
+                _ => Err(Error::FailedToLoad {message: format!("The DDS format {} is not supported", String::from_utf8_lossy(four_cc))})?
+            };
+            (format, 128)
+        };
+
+        let block_size = if format == Format::RGBADXT1 || format == Format::SRGBAlphaDXT1 {8} else {16};
+        let mut levels = Vec::new();
+        for level in 0..level_count {
+            let w = (width >> level).max(1) as usize;
+            let h = (height >> level).max(1) as usize;
+            let end = w.div_ceil(4).checked_mul(h.div_ceil(4)).and_then(|blocks| blocks.checked_mul(block_size))
+                .and_then(|length| offset.checked_add(length));
+            let data = end.and_then(|end| bytes.get(offset..end))
+                .ok_or_else(|| Error::FailedToLoad {message: format!("The mip level {} is outside the DDS file", level)})?;
+            levels.push(data.to_vec());
+            offset += data.len();
+        }
+        Ok(CompressedImage {format, width, height, levels})
+    }
+}
+
+const MIP_MAP_COUNT: u32 = 0x20000;
+
+fn format_from_dxgi_format(dxgi_format: u32) -> Result<Format, Error>
+{
+    Ok(match dxgi_format {
+        71 => Format::RGBADXT1,
+        72 => Format::SRGBAlphaDXT1,
+        74 => Format::RGBADXT3,
+        75 => Format::SRGBAlphaDXT3,
+        77 => Format::RGBADXT5,
+        78 => Format::SRGBAlphaDXT5,
+        _ => Err(Error::FailedToLoad {message: format!("The DXGI format {} in the DDS file is not supported", dxgi_format)})?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dds(width: u32, height: u32, level_count: u32, four_cc: &[u8; 4], data_length: usize) -> Vec<u8>
+    {
+        let mut bytes = vec![0u8; 128];
+        bytes[0..4].copy_from_slice(b"DDS ");
+        for (position, value) in [(4, 124u32), (8, MIP_MAP_COUNT), (12, height), (16, width), (28, level_count)].iter() {
+            bytes[*position..*position + 4].copy_from_slice(&value.to_le_bytes());
+        }
+        bytes[84..88].copy_from_slice(four_cc);
+        bytes.extend((0..data_length).map(|i| i as u8));
+        bytes
+    }
+
+    #[test]
+    fn parse_dxt1_mip_chain()
+    {
+        let image = Dds::parse_bytes(&dds(8, 8, 4, b"DXT1", 32 + 8 + 8 + 8)).unwrap();
+        assert_eq!(image.format, Format::RGBADXT1);
+        assert_eq!(image.levels.iter().map(|level| level.len()).collect::<Vec<_>>(), vec![32, 8, 8, 8]);
+        assert_eq!(image.levels[1][0], 32);
+    }
+
+    #[test]
+    fn parse_dxt5()
+    {
+        let image = Dds::parse_bytes(&dds(4, 8, 1, b"DXT5", 32)).unwrap();
+        assert_eq!(image.format, Format::RGBADXT5);
+        assert_eq!(image.levels[0].len(), 32);
+    }
+
+    #[test]
+    fn level_count_is_clamped()
+    {
+        let image = Dds::parse_bytes(&dds(1, 1, 1000, b"DXT1", 40 * 8)).unwrap();
+        assert_eq!(image.levels.len(), 32);
+    }
+
+    #[test]
+    fn level_outside_file()
+    {
+        assert!(Dds::parse_bytes(&dds(8, 8, 4, b"DXT1", 40)).is_err());
+        assert!(Dds::parse_bytes(&dds(u32::MAX, u32::MAX, 1, b"DXT3", 16)).is_err());
+    }
+
+    #[test]
+    fn unsupported_format()
+    {
+        assert!(Dds::parse_bytes(&dds(4, 4, 1, b"ATI2", 16)).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use crate::io::*;
+use std::path::Path;
+use crate::{CompressedImage, Format};
+
+///
+/// Parses KTX2 files which contain a 2D texture in one of the block compressed formats supported by [Format](enum.Format.html).
+/// Supercompressed files (for example Basis Universal) are not supported.
+///
+pub struct Ktx2 {
+
+}
+
+impl Ktx2 {
+    pub fn parse<P: AsRef<Path>>(loaded: &Loaded, path: P) -> Result<CompressedImage, Error>
+    {
+        Self::parse_bytes(Loader::get(loaded, path)?)
+    }
+
+    pub fn is_ktx2(bytes: &[u8]) -> bool
+    {
+        bytes.starts_with(&IDENTIFIER)
+    }
+
+    pub fn parse_bytes(bytes: &[u8]) -> Result<CompressedImage, Error>
+    {
+        if !Self::is_ktx2(bytes) || bytes.len() < 80 {
+            Err(Error::FailedToLoad {message: "Not a KTX2 file".to_string()})?
+        }
+        let vk_format = read_u32(bytes, 12)?;
+        let width = read_u32(bytes, 20)?;
+        let height = read_u32(bytes, 24)?;
+        let depth = read_u32(bytes, 28)?;
+        let layer_count = read_u32(bytes, 32)?;
+        let face_count = read_u32(bytes, 36)?;
+        let level_count = read_u32(bytes, 40)?.max(1);
+        let supercompression_scheme = read_u32(bytes, 44)?;
+
+        if depth > 0 || layer_count > 1 || face_count != 1 {
+            Err(Error::FailedToLoad {message: "Only 2D textures are supported in KTX2 files".to_string()})?
+        }
+        if supercompression_scheme != 0 {
+            Err(Error::FailedToLoad {message: format!("The KTX2 supercompression scheme {} is not supported", supercompression_scheme)})?
+        }
+        let format = format_from_vk_format(vk_format)?;
+
+        let mut levels = Vec::new();
+        for level in 0..level_count as usize {
+            let offset = read_u64(bytes, 80 + level * 24)? as usize;
+            let length = read_u64(bytes, 88 + level * 24)? as usize;
+            let data = offset.checked_add(length).and_then(|end| bytes.get(offset..end))
+                .ok_or_else(|| Error::FailedToLoad {message: format!("The mip level {} is outside the KTX2 file", level)})?;
+            levels.push(data.to_vec());
+        }
+        Ok(CompressedImage {format, width, height, levels})
+    }
+}
+
+const IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+
+fn format_from_vk_format(vk_format: u32) -> Result<Format, Error>
+{
+    Ok(match vk_format {
+        131 => Format::RGBDXT1,
+        132 => Format::SRGBDXT1,
+        133 => Format::RGBADXT1,
+        134 => Format::SRGBAlphaDXT1,
+        135 => Format::RGBADXT3,
+        136 => Format::SRGBAlphaDXT3,
+        137 => Format::RGBADXT5,
+        138 => Format::SRGBAlphaDXT5,
+        147 => Format::RGB8ETC2,
+        148 => Format::SRGB8ETC2,
+        149 => Format::RGB8A1ETC2,
+        150 => Format::SRGB8A1ETC2,
+        151 => Format::RGBA8ETC2,
+        152 => Format::SRGB8Alpha8ETC2,
+        157 => Format::RGBAASTC4x4,
+        158 => Format::SRGB8Alpha8ASTC4x4,
+        _ => Err(Error::FailedToLoad {message: format!("The Vulkan format {} in the KTX2 file is not supported", vk_format)})?
+    })
+}
+
+pub(crate) fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, Error>
+{
+    let b = bytes.get(offset..offset + 4).ok_or_else(|| Error::FailedToLoad {message: "Unexpected end of file".to_string()})?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, Error>
+{
+    Ok(read_u32(bytes, offset)? as u64 | (read_u32(bytes, offset + 4)? as u64) << 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 4x4 BC1 texture with one mip level, where the level index points at the given offset and length
+    fn ktx2(offset: u64, length: u64) -> Vec<u8>
+    {
+        let mut bytes = vec![0u8; 104];
+        bytes[0..12].copy_from_slice(&IDENTIFIER);
+        for (position, value) in [(12, 131u32), (20, 4), (24, 4), (36, 1), (40, 1)].iter() {
+            bytes[*position..*position + 4].copy_from_slice(&value.to_le_bytes());
+        }
+        bytes[80..88].copy_from_slice(&offset.to_le_bytes());
+        bytes[88..96].copy_from_slice(&length.to_le_bytes());
+        bytes[96..104].copy_from_slice(&length.to_le_bytes());
+        bytes.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        bytes
+    }
+
+    #[test]
+    fn parse_bc1()
+    {
+        let image = Ktx2::parse_bytes(&ktx2(104, 8)).unwrap();
+        assert_eq!(image.format, Format::RGBDXT1);
+        assert_eq!((image.width, image.height), (4, 4));
+        assert_eq!(image.levels, vec![vec![1, 2, 3, 4, 5, 6, 7, 8]]);
+    }
+
+    #[test]
+    fn level_outside_file()
+    {
+        assert!(Ktx2::parse_bytes(&ktx2(104, 9)).is_err());
+        assert!(Ktx2::parse_bytes(&ktx2(u64::MAX - 2, 8)).is_err());
+        assert!(Ktx2::parse_bytes(&ktx2(104, u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn not_ktx2()
+    {
+        assert!(Ktx2::parse_bytes(b"DDS ").is_err());
+        assert!(Ktx2::parse_bytes(&ktx2(104, 8)[0..60]).is_err());
+    }
+}
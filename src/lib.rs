@@ -1,6 +1,7 @@
 
 pub mod gl;
 
+#[macro_use]
 pub mod core;
 pub use crate::core::*;
 
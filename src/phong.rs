@@ -9,8 +9,15 @@ pub mod sphere_instances;
 pub mod cylinder_instances;
 pub mod mesh;
 pub mod imposter;
+pub mod reflective_mesh;
+pub mod gbuffer;
+mod light_clusters;
+mod light_volumes;
+mod area_lights;
 
 pub use crate::sphere_instances::*;
 pub use crate::cylinder_instances::*;
 pub use crate::mesh::*;
-pub use crate::imposter::*;
\ No newline at end of file
+pub use crate::imposter::*;
+pub use crate::reflective_mesh::*;
+pub use crate::gbuffer::*;
\ No newline at end of file
@@ -0,0 +1,168 @@
+use crate::*;
+use std::f32::consts::PI;
+
+const LUT_SIZE: usize = 32;
+const SAMPLE_COUNT: usize = 32;
+const MAX_VERTEX_COUNT: usize = 12;
+// Very low roughness gives a lobe which is too narrow to sample when building the look-up tables
+const MIN_ROUGHNESS: f32 = 0.03;
+
+///
+/// Applies rectangular and disk lights in the deferred light pass and renders the emissive shapes of the lights.
+/// The lighting is evaluated with linearly transformed cosines, see Heitz et al. 2016, "Real-Time Polygonal-Light Shading with Linearly Transformed Cosines",
+/// where the specular lobe is approximated by a transformed clamped cosine, which can be integrated analytically over the polygon of the light.
+/// The transformations are fitted to the Phong lobe on the CPU and stored in look-up tables indexed by roughness and view angle.
+///
+pub(crate) struct AreaLights {
+    gl: Gl,
+    effect: ImageEffect,
+    emissive_program: Program,
+    ltc_matrix: Texture2D,
+    ltc_magnitude: Texture2D
+}
+
+impl AreaLights {
+
+    pub fn new(gl: &Gl, gbuffer_layout: &GBufferLayout) -> Result<Self, Error>
+    {
+        let (matrix_data, magnitude_data) = Self::fit_lookup_tables();
+        let mut ltc_matrix = Self::new_lookup_table(gl)?;
+        ltc_matrix.fill_with_f32(&matrix_data)?;
+        let mut ltc_magnitude = Self::new_lookup_table(gl)?;
+        ltc_magnitude.fill_with_f32(&magnitude_data)?;
+        Ok(Self {
+            gl: gl.clone(),
+            effect: ImageEffect::from_shader_source(gl, &ShaderSource::from_segments([gbuffer_layout.shader_segments(), vec![
+                ShaderSegment::new("area_light_defines", &format!("#define MAX_AREA_LIGHT_VERTICES {}\n#define LTC_LUT_SIZE {:.1}\n", MAX_VERTEX_COUNT, LUT_SIZE as f32)),
+                shader_segment!("shaders/light_shared.frag"),
+                shader_segment!("shaders/deferred_light_shared.frag"),
+                shader_segment!("shaders/area_light.frag")]].concat()))?,
+            emissive_program: Program::from_shader_source(gl, &ShaderSource::from_segments(vec![shader_segment!("shaders/emissive.vert")]),
+                                                          &ShaderSource::from_segments(vec![shader_segment!("shaders/emissive.frag")]))?,
+            ltc_matrix, ltc_magnitude
+        })
+    }
+
+    pub fn render_rect_light(&self, camera: &Camera, gbuffer: &Texture2DArray, depth_map: &Texture2DArray, light: &RectLight) -> Result<(), Error>
+    {
+        self.render_light(camera, gbuffer, depth_map, &light.corners(), light.direction(), light.color(), light.intensity())
+    }
+
+    pub fn render_disk_light(&self, camera: &Camera, gbuffer: &Texture2DArray, depth_map: &Texture2DArray, light: &DiskLight) -> Result<(), Error>
+    {
+        self.render_light(camera, gbuffer, depth_map, &light.polygon(), light.direction(), light.color(), light.intensity())
+    }
+
+    ///
+    /// Renders the shapes of the given lights with the color of the light, tested against the depth of the scene.
+    ///
+    pub fn render_emissive_shapes(&self, camera: &Camera, rect_lights: &[&RectLight], disk_lights: &[&DiskLight]) -> Result<(), Error>
+    {
+        let shapes = rect_lights.iter().map(|light| (light.corners().to_vec(), light.color() * light.intensity()))
+            .chain(disk_lights.iter().map(|light| (light.polygon(), light.color() * light.intensity())));
+        let mut positions = Vec::new();
+        let mut colors = Vec::new();
+        for (polygon, color) in shapes {
+            // The polygon is convex, so it is rendered as a triangle fan
+            for i in 1..polygon.len() - 1 {
+                for vertex in &[polygon[0], polygon[i], polygon[i + 1]] {
+                    positions.extend_from_slice(&[vertex.x, vertex.y, vertex.z]);
+                    colors.extend_from_slice(&[color.x, color.y, color.z]);
+                }
+            }
+        }
+        if positions.is_empty() {
+            return Ok(());
+        }
+
+        let program = &self.emissive_program;
+        program.add_uniform_mat4("viewProjection", &(camera.get_projection() * camera.get_view()))?;
+        program.use_attribute_vec3_float(&VertexBuffer::new_with_static_f32(&self.gl, &positions)?, "position")?;
+        program.use_attribute_vec3_float(&VertexBuffer::new_with_static_f32(&self.gl, &colors)?, "color")?;
+        program.draw_arrays(positions.len() as u32 / 3);
+        Ok(())
+    }
+
+    fn render_light(&self, camera: &Camera, gbuffer: &Texture2DArray, depth_map: &Texture2DArray, vertices: &[Vec3], normal: Vec3, color: Vec3, intensity: f32) -> Result<(), Error>
+    {
+        let program = self.effect.program();
+        if vertices.len() > MAX_VERTEX_COUNT {
+            return Err(Error::FailedToRender {message: format!("An area light has {} vertices, but at most {} are supported", vertices.len(), MAX_VERTEX_COUNT)});
+        }
+        program.use_texture(gbuffer, "gbuffer")?;
+        program.use_texture(depth_map, "depthMap")?;
+        program.use_texture(&self.ltc_matrix, "ltcMatrix")?;
+        program.use_texture(&self.ltc_magnitude, "ltcMagnitude")?;
+        program.add_uniform_vec3("eyePosition", camera.position())?;
+        program.add_uniform_mat4("viewProjectionInverse", &camera.view_projection_inverse())?;
+        program.add_uniform_vec3_array("areaLightVertices", vertices)?;
+        program.add_uniform_int("areaLightVertexCount", &(vertices.len() as i32))?;
+        program.add_uniform_vec3("areaLightNormal", &normal)?;
+        program.add_uniform_vec3("areaLightColor", &color)?;
+        program.add_uniform_float("areaLightIntensity", &intensity)?;
+        self.effect.apply();
+        state::blend(&self.gl, state::BlendType::OneOne);
+        Ok(())
+    }
+
+    fn new_lookup_table(gl: &Gl) -> Result<Texture2D, Error>
+    {
+        Texture2D::new(gl, LUT_SIZE, LUT_SIZE, Interpolation::Linear, Interpolation::Linear, None,
+                       Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::RGBA16F)
+    }
+
+    // Fits the inverse transformation and the magnitude of the Phong lobe for each roughness (x) and view angle (y), where y = sqrt(1 - cos(theta)).
+    // The lobe is sampled to find the average direction and the spread around that direction,
+    // which gives the rotation and the scale of the clamped cosine with the same average direction and spread.
+    fn fit_lookup_tables() -> (Vec<f32>, Vec<f32>)
+    {
+        let mut matrix_data = Vec::with_capacity(4 * LUT_SIZE * LUT_SIZE);
+        let mut magnitude_data = Vec::with_capacity(4 * LUT_SIZE * LUT_SIZE);
+        for j in 0..LUT_SIZE {
+            let v = j as f32 / (LUT_SIZE - 1) as f32;
+            let cos_theta = (1.0 - v * v).max(0.0);
+            let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+            for i in 0..LUT_SIZE {
+                let roughness = (i as f32 / (LUT_SIZE - 1) as f32).max(MIN_ROUGHNESS);
+                let power = 2.0 / (roughness * roughness) - 2.0;
+                let reflection = vec3(-sin_theta, 0.0, cos_theta);
+                let tangent = vec3(cos_theta, 0.0, sin_theta);
+                let bitangent = vec3(0.0, 1.0, 0.0);
+
+                let samples = (0..SAMPLE_COUNT * SAMPLE_COUNT).map(|k| {
+                    let cos_alpha = ((k / SAMPLE_COUNT) as f32 + 0.5) / SAMPLE_COUNT as f32;
+                    let cos_alpha = cos_alpha.powf(1.0 / (power + 1.0));
+                    let sin_alpha = (1.0 - cos_alpha * cos_alpha).max(0.0).sqrt();
+                    let phi = 2.0 * PI * ((k % SAMPLE_COUNT) as f32 + 0.5) / SAMPLE_COUNT as f32;
+                    reflection * cos_alpha + (tangent * phi.cos() + bitangent * phi.sin()) * sin_alpha
+                }).filter(|sample| sample.z > 0.0).collect::<Vec<Vec3>>();
+
+                let magnitude = samples.len() as f32 / (SAMPLE_COUNT * SAMPLE_COUNT) as f32;
+                let average = if samples.is_empty() { vec3(0.0, 0.0, 1.0) } else { samples.iter().fold(vec3(0.0, 0.0, 0.0), |sum, sample| sum + sample).normalize() };
+                let spread = if samples.is_empty() { 0.5 } else { samples.iter().map(|sample| 1.0 - sample.dot(average).powi(2)).sum::<f32>() / samples.len() as f32 };
+                let scale = Self::fit_scale(spread);
+                let phi = average.x.atan2(average.z).clamp(-0.49 * PI, 0.49 * PI);
+
+                matrix_data.extend_from_slice(&[1.0 / scale, -phi.tan() / scale, 1.0 / (scale * phi.cos()), phi.tan()]);
+                magnitude_data.extend_from_slice(&[magnitude, 0.0, 0.0, 1.0]);
+            }
+        }
+        (matrix_data, magnitude_data)
+    }
+
+    // Finds the scale s of a clamped cosine, scaled by s in the x and y directions, which gives the given expected value of sin^2 of the angle to the z axis
+    fn fit_scale(spread: f32) -> f32
+    {
+        let expected_spread = |s: f32| {
+            let s2 = s * s;
+            let k = s2 - 1.0;
+            if k.abs() < 1e-4 { 0.5 } else { s2 * (1.0 / k - s2.ln() / (k * k)) }
+        };
+        let (mut low, mut high) = (-10.0f32, 10.0f32);
+        for _ in 0..50 {
+            let middle = 0.5 * (low + high);
+            if expected_spread(middle.exp()) < spread { low = middle; } else { high = middle; }
+        }
+        (0.5 * (low + high)).exp()
+    }
+}
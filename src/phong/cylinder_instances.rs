@@ -10,18 +10,21 @@ pub struct CylinderInstances {
     index_pairs: std::collections::HashSet<(usize, usize)>,
     no_edges: u32,
     tube_radius: f32,
-    pub material: PhongMaterial
+    pub material: PhongMaterial,
+    ///
+    /// The origin of the end points in world space, which the transformation given when rendering is relative to, see [PhongForwardMesh::origin].
+    ///
+    pub origin: Vec3d
 }
 
 impl CylinderInstances
 {
-    pub(crate) fn new(gl: &Gl, indices: &[u32], positions: &[f32], tube_radius: f32, material: &PhongMaterial) -> Result<Self, Error>
+    pub(crate) fn new(gl: &Gl, gbuffer_layout: &GBufferLayout, indices: &[u32], positions: &[f32], tube_radius: f32, material: &PhongMaterial) -> Result<Self, Error>
     {
-        let program = core::Program::from_source(&gl,
-                                                    include_str!("shaders/cylinder.vert"),
-                                                    &format!("{}\n{}",
-                                                             include_str!("shaders/deferred_objects_shared.frag"),
-                                                             include_str!("shaders/colored_deferred.frag")))?;
+        let program = core::Program::from_shader_source(&gl, &ShaderSource::from_segments(vec![gbuffer_layout.shader_defines(), shader_segment!("shaders/cylinder.vert")]),
+                                                    &ShaderSource::from_segments([gbuffer_layout.shader_segments(), vec![
+                                                                          shader_segment!("shaders/deferred_objects_shared.frag"),
+                                                                          shader_segment!("shaders/colored_deferred.frag")]].concat()))?;
 
         let x_subdivisions = 1;
         let angle_subdivisions = 10;
@@ -67,7 +70,7 @@ impl CylinderInstances
         let direction_buffer = VertexBuffer::new_with_dynamic_f32(gl, &direction)?;
 
         Ok(Self { program, translation_buffer, direction_buffer, cylinder_vertex_buffer, cylinder_index_buffer,
-            index_pairs, no_edges, tube_radius, material: material.clone() })
+            index_pairs, no_edges, tube_radius, material: material.clone(), origin: vec3d(0.0, 0.0, 0.0) })
     }
 
     fn fill_translation_and_direction(index_pairs: &std::collections::HashSet<(usize, usize)>, positions: &[f32]) -> (Vec<f32>, Vec<f32>)
@@ -95,13 +98,14 @@ impl CylinderInstances
         self.program.add_uniform_float("diffuse_intensity", &self.material.diffuse_intensity)?;
         self.program.add_uniform_float("specular_intensity", &self.material.specular_intensity)?;
         self.program.add_uniform_float("specular_power", &self.material.specular_power)?;
+        use_optional_layer_uniforms(&self.program, &self.material, camera)?;
 
         let color = if let ColorSource::Color(c) = self.material.color_source {c} else {vec4(1.0, 0.0, 0.0, 1.0)};
         self.program.add_uniform_vec4("color", &color)?;
 
         self.program.use_uniform_block(camera.matrix_buffer(), "Camera");
         self.program.add_uniform_float("tube_radius", &self.tube_radius)?;
-        self.program.add_uniform_mat4("modelMatrix", &transformation)?;
+        self.program.add_uniform_mat4("modelMatrix", &camera.render_space_transformation(&self.origin, transformation))?;
 
         self.program.use_attribute_vec3_float_divisor(&self.translation_buffer, "translation", 1)?;
         self.program.use_attribute_vec3_float_divisor(&self.direction_buffer, "direction", 1)?;
@@ -0,0 +1,117 @@
+use crate::*;
+
+///
+/// Defines what is stored in the G-buffer written in the geometry pass of the [PhongDeferredPipeline] and how it is encoded.
+/// The first layer always contains the color and diffuse intensity and the second layer the normal, specular intensity and specular power.
+/// The optional layers are added after these in the order emissive, material and motion vectors.
+/// The default layout is two RGBA8 layers, which is the most compact.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GBufferLayout {
+    /// Store the layers as RGBA16F instead of RGBA8, which gives more precision, for example for dark colors, but uses twice the memory.
+    /// On web, this requires the EXT_color_buffer_float extension.
+    pub high_precision: bool,
+    /// Encode the normal with an octahedral mapping into two channels instead of three, which gives a more even precision over all directions
+    /// and leaves room to store the specular intensity and power in separate channels, which also increases the maximum specular power from 30 to 255.
+    pub octahedral_normals: bool,
+    /// Add a layer with the emissive color of the material, see [PhongMaterial::emissive](crate::PhongMaterial::emissive), which is added to the lighting in the light pass.
+    pub emissive: bool,
+    /// Add a layer with the metalness, roughness and material id of the material.
+    pub material: bool,
+    /// Add a layer with the screen space motion since the previous frame, see [Camera::save_previous_view_projection](crate::Camera::save_previous_view_projection).
+    /// Only the motion of the camera is taken into account.
+    pub motion_vectors: bool
+}
+
+impl GBufferLayout {
+
+    ///
+    /// The number of layers in the G-buffer.
+    ///
+    pub fn layer_count(&self) -> usize
+    {
+        2 + self.emissive as usize + self.material as usize + self.motion_vectors as usize
+    }
+
+    ///
+    /// The format of the layers in the G-buffer.
+    ///
+    pub fn format(&self) -> Format
+    {
+        if self.high_precision { Format::RGBA16F } else { Format::RGBA8 }
+    }
+
+    pub fn emissive_layer(&self) -> Option<usize>
+    {
+        if self.emissive { Some(2) } else { None }
+    }
+
+    pub fn material_layer(&self) -> Option<usize>
+    {
+        if self.material { Some(2 + self.emissive as usize) } else { None }
+    }
+
+    pub fn motion_vector_layer(&self) -> Option<usize>
+    {
+        if self.motion_vectors { Some(2 + self.emissive as usize + self.material as usize) } else { None }
+    }
+
+    ///
+    /// The defines describing the layout followed by the encoding and decoding functions in gbuffer_shared.frag.
+    /// These segments must come before deferred_objects_shared.frag, deferred_light_shared.frag and debug.frag.
+    /// The defines are also used by the vertex shaders of the geometry pass to output the positions needed for the motion vectors.
+    ///
+    pub(crate) fn shader_segments(&self) -> Vec<ShaderSegment>
+    {
+        vec![self.shader_defines(), shader_segment!("shaders/gbuffer_shared.frag")]
+    }
+
+    pub(crate) fn shader_defines(&self) -> ShaderSegment
+    {
+        let mut defines = String::new();
+        if self.high_precision {
+            defines += "#define GBUFFER_HIGH_PRECISION\n";
+        }
+        if self.octahedral_normals {
+            defines += "#define GBUFFER_OCTAHEDRAL_NORMALS\n";
+        }
+        if let Some(layer) = self.emissive_layer() {
+            defines += &format!("#define GBUFFER_EMISSIVE_LAYER {}\n", layer);
+        }
+        if let Some(layer) = self.material_layer() {
+            defines += &format!("#define GBUFFER_MATERIAL_LAYER {}\n", layer);
+        }
+        if let Some(layer) = self.motion_vector_layer() {
+            defines += &format!("#define GBUFFER_MOTION_LAYER {}\n", layer);
+        }
+        ShaderSegment::new("gbuffer_defines", &defines)
+    }
+
+    pub(crate) fn check_support(&self, _gl: &Gl) -> Result<(), Error>
+    {
+        #[cfg(target_arch = "wasm32")]
+        {
+            if self.high_precision && !_gl.supports_extension("EXT_color_buffer_float") {
+                Err(Error::FailedToCreateTexture {message: "A high precision G-buffer requires rendering to float textures (EXT_color_buffer_float), which is not supported".to_string()})?
+            }
+        }
+        Ok(())
+    }
+}
+
+// Sets the uniforms for the optional layers of the G-buffer which are used by the program
+pub(crate) fn use_optional_layer_uniforms(program: &Program, material: &PhongMaterial, camera: &Camera) -> Result<(), Error>
+{
+    if program.uniform_info("emissive").is_some() {
+        program.add_uniform_vec3("emissive", &material.emissive)?;
+    }
+    if program.uniform_info("metalness").is_some() {
+        program.add_uniform_float("metalness", &material.metalness)?;
+        program.add_uniform_float("roughness", &material.roughness)?;
+        program.add_uniform_int("materialId", &(material.material_id as i32))?;
+    }
+    if program.uniform_info("previousViewProjection").is_some() {
+        program.add_uniform_mat4("previousViewProjection", &camera.previous_view_projection())?;
+    }
+    Ok(())
+}
@@ -10,11 +10,24 @@ pub struct Imposter {
     positions_buffer: VertexBuffer,
     uvs_buffer: VertexBuffer,
     instance_count: u32,
-    texture: Texture2DArray
+    texture: Texture2DArray,
+    ///
+    /// The origin of the instance positions in world space, see [PhongForwardMesh::origin].
+    ///
+    pub origin: Vec3d
 }
 
 impl Imposter {
     pub fn new<F: Fn(&Camera) -> Result<(), Error>>(gl: &Gl, render: F, aabb: (Vec3, Vec3), max_texture_size: usize) -> Result<Self, Error>
+    {
+        Self::new_with_gbuffer_layout(gl, &GBufferLayout::default(), render, aabb, max_texture_size)
+    }
+
+    ///
+    /// Creates an imposter which is rendered into a G-buffer with the given layout, which must be the layout of the pipeline used for rendering the objects in the render function
+    /// and for rendering the imposter. Only the color, normal and specular layers are stored for the imposter, the optional layers are written as zero.
+    ///
+    pub fn new_with_gbuffer_layout<F: Fn(&Camera) -> Result<(), Error>>(gl: &Gl, gbuffer_layout: &GBufferLayout, render: F, aabb: (Vec3, Vec3), max_texture_size: usize) -> Result<Self, Error>
     {
         let (min, max) = aabb;
         let width = f32::sqrt(f32::powi(max.x - min.x, 2) + f32::powi(max.z - min.z, 2));
@@ -27,7 +40,7 @@ impl Imposter {
         let texture_height = (max_texture_size as f32 * (height / width).min(1.0)) as usize;
         let texture = Texture2DArray::new(gl, texture_width, texture_height, NO_VIEW_ANGLES*2,
                 Interpolation::Nearest, Interpolation::Nearest, None,
-                                                Wrapping::ClampToEdge,Wrapping::ClampToEdge, Format::RGBA8)?;
+                                                Wrapping::ClampToEdge,Wrapping::ClampToEdge, gbuffer_layout.format())?;
         let depth_texture = Texture2DArray::new(gl, texture_width, texture_height, NO_VIEW_ANGLES,
                 Interpolation::Nearest, Interpolation::Nearest, None,
                                                       Wrapping::ClampToEdge,Wrapping::ClampToEdge, Format::Depth32F)?;
@@ -71,14 +84,13 @@ impl Imposter {
         let positions_buffer = VertexBuffer::new_with_static_f32(&gl, &positions)?;
         let uvs_buffer = VertexBuffer::new_with_static_f32(&gl, &uvs)?;
 
-        let program = program::Program::from_source(gl,
-                                                    include_str!("shaders/imposter.vert"),
-                                                    include_str!("shaders/imposter.frag"))?;
+        let program = program::Program::from_shader_source(gl, &ShaderSource::from_segments(vec![shader_segment!("shaders/imposter.vert")]),
+                                                    &ShaderSource::from_segments([gbuffer_layout.shader_segments(), vec![shader_segment!("shaders/imposter.frag")]].concat()))?;
 
         let center_buffer = VertexBuffer::new_with_dynamic_f32(gl, &[])?;
         let rotation_buffer = VertexBuffer::new_with_dynamic_f32(gl, &[])?;
 
-        Ok(Imposter {texture, program, center_buffer, rotation_buffer, positions_buffer, uvs_buffer, instance_count:0 })
+        Ok(Imposter {texture, program, center_buffer, rotation_buffer, positions_buffer, uvs_buffer, instance_count:0, origin: vec3d(0.0, 0.0, 0.0) })
     }
 
     pub fn update_positions(&mut self, positions: &[f32], angles_in_radians: &[f32])
@@ -88,10 +100,19 @@ impl Imposter {
         self.instance_count = positions.len() as u32/3;
     }
 
+    ///
+    /// The texture atlas with the object rendered from different view angles (color layers followed by normal layers).
+    ///
+    pub fn texture(&self) -> &Texture2DArray
+    {
+        &self.texture
+    }
+
     pub fn render(&self, camera: &camera::Camera) -> Result<(), Error>
     {
         self.program.add_uniform_int("no_views", &(NO_VIEW_ANGLES as i32))?;
         self.program.use_uniform_block(camera.matrix_buffer(), "Camera");
+        self.program.add_uniform_vec3("translation", &camera.to_render_space(&self.origin))?;
 
         self.program.use_texture(&self.texture, "tex")?;
 
@@ -0,0 +1,277 @@
+use crate::*;
+
+pub(crate) const CLUSTER_COUNT_X: usize = 16;
+pub(crate) const CLUSTER_COUNT_Y: usize = 9;
+pub(crate) const CLUSTER_COUNT_Z: usize = 24;
+pub(crate) const INDEX_TEXTURE_WIDTH: usize = 1024;
+
+type ClusterIndex = (usize, usize, usize);
+
+///
+/// Point and spot lights binned into clusters, that is, screen tiles subdivided into depth slices, and stored in textures so that
+/// a single full-screen pass can look up the lights that affect each pixel without compute shaders.
+/// The light data texture contains four RGBA texels per light (one light per row): position and type, color and intensity, attenuation and influence radius, direction and cutoff.
+/// The cluster texture contains the offset and count into the light index texture for each cluster.
+///
+pub(crate) struct LightClusters {
+    gl: Gl,
+    light_texture: Texture2D,
+    cluster_texture: Texture2D,
+    index_texture: Texture2D,
+    z_far: f32
+}
+
+impl LightClusters {
+
+    pub fn new(gl: &Gl) -> Result<Self, Error>
+    {
+        Ok(Self { gl: gl.clone(),
+            light_texture: Self::new_texture(gl, 4, 16, Format::RGBA32F)?,
+            cluster_texture: Self::new_texture(gl, CLUSTER_COUNT_X * CLUSTER_COUNT_Y, CLUSTER_COUNT_Z, Format::RGBA32F)?,
+            index_texture: Self::new_texture(gl, INDEX_TEXTURE_WIDTH, 1, Format::R32F)?,
+            z_far: 0.0 })
+    }
+
+    ///
+    /// Bins the lights into the clusters of the view of the camera and uploads the result.
+    /// The light and index textures are only reallocated when the lights or light indices do not fit, in which case the capacity is doubled.
+    ///
+    pub fn update(&mut self, camera: &Camera, spot_lights: &[&SpotLight], point_lights: &[&PointLight]) -> Result<(), Error>
+    {
+        let mut light_data = Vec::with_capacity(16 * (spot_lights.len() + point_lights.len()));
+        let mut bounds = Vec::with_capacity(spot_lights.len() + point_lights.len());
+        for light in point_lights {
+            let (constant, linear, exponential) = light.attenuation();
+            let position = light.position();
+            let radius = light.influence_radius();
+            light_data.extend_from_slice(&[position.x, position.y, position.z, 0.0]);
+            light_data.extend(light.color().to_slice().iter().chain(&[light.intensity()]));
+            light_data.extend_from_slice(&[constant, linear, exponential, radius]);
+            light_data.extend_from_slice(&[0.0, 0.0, 0.0, 0.0]);
+            bounds.push((position, radius));
+        }
+        for light in spot_lights {
+            let (constant, linear, exponential) = light.attenuation();
+            let position = light.position();
+            let direction = light.direction();
+            let radius = light.influence_radius();
+            light_data.extend_from_slice(&[position.x, position.y, position.z, 1.0]);
+            light_data.extend(light.color().to_slice().iter().chain(&[light.intensity()]));
+            light_data.extend_from_slice(&[constant, linear, exponential, radius]);
+            light_data.extend_from_slice(&[direction.x, direction.y, direction.z, light.cutoff()]);
+            bounds.push((position, radius));
+        }
+
+        let frustum = ClusterFrustum::new(camera, &bounds);
+        let mut clusters = vec![Vec::new(); CLUSTER_COUNT_X * CLUSTER_COUNT_Y * CLUSTER_COUNT_Z];
+        for (light_index, (position, radius)) in bounds.iter().enumerate() {
+            if let Some((min, max)) = cluster_range(&frustum, position, *radius) {
+                for z in min.2..=max.2 {
+                    for y in min.1..=max.1 {
+                        for x in min.0..=max.0 {
+                            clusters[x + CLUSTER_COUNT_X * (y + CLUSTER_COUNT_Y * z)].push(light_index as f32);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut cluster_data = Vec::with_capacity(4 * clusters.len());
+        let mut indices = Vec::new();
+        for cluster in clusters.iter() {
+            cluster_data.extend_from_slice(&[indices.len() as f32, cluster.len() as f32, 0.0, 0.0]);
+            indices.extend_from_slice(cluster);
+        }
+        let index_rows = indices.len().div_ceil(INDEX_TEXTURE_WIDTH).max(1);
+        indices.resize(INDEX_TEXTURE_WIDTH * index_rows, 0.0);
+        let light_count = bounds.len().max(1);
+        light_data.resize(16 * light_count, 0.0);
+
+        if light_count > self.light_texture.height {
+            self.light_texture = Self::new_texture(&self.gl, 4, light_count.next_power_of_two(), Format::RGBA32F)?;
+        }
+        self.light_texture.fill_subset_with_f32(0, 0, 4, light_count, &light_data)?;
+        self.cluster_texture.fill_with_f32(&cluster_data)?;
+        if index_rows > self.index_texture.height {
+            self.index_texture = Self::new_texture(&self.gl, INDEX_TEXTURE_WIDTH, index_rows.next_power_of_two(), Format::R32F)?;
+        }
+        self.index_texture.fill_subset_with_f32(0, 0, INDEX_TEXTURE_WIDTH, index_rows, &indices)?;
+        self.z_far = frustum.z_far;
+        Ok(())
+    }
+
+    pub fn use_uniforms(&self, program: &Program, camera: &Camera) -> Result<(), Error>
+    {
+        program.use_texture(&self.light_texture, "lightData")?;
+        program.use_texture(&self.cluster_texture, "clusterData")?;
+        program.use_texture(&self.index_texture, "lightIndices")?;
+        program.add_uniform_mat4("view", camera.get_view())?;
+        program.add_uniform_float("zNear", &camera.z_near())?;
+        program.add_uniform_float("zFar", &self.z_far)?;
+        program.add_uniform_int("logarithmicSlicing", &(camera.is_perspective() as i32))?;
+        Ok(())
+    }
+
+    ///
+    /// The defines used by the clustered lighting shader.
+    ///
+    pub fn shader_defines() -> ShaderSegment
+    {
+        ShaderSegment::new("cluster_defines", &format!("#define CLUSTER_COUNT_X {}\n#define CLUSTER_COUNT_Y {}\n#define CLUSTER_COUNT_Z {}\n#define INDEX_TEXTURE_WIDTH {}\n",
+                                                       CLUSTER_COUNT_X, CLUSTER_COUNT_Y, CLUSTER_COUNT_Z, INDEX_TEXTURE_WIDTH))
+    }
+
+    fn new_texture(gl: &Gl, width: usize, height: usize, format: Format) -> Result<Texture2D, Error>
+    {
+        Texture2D::new(gl, width, height, Interpolation::Nearest, Interpolation::Nearest, None,
+                       Wrapping::ClampToEdge, Wrapping::ClampToEdge, format)
+    }
+}
+
+// The view of the camera used for binning the lights, where the depth range is sliced from z_near to z_far.
+// The far plane of a perspective camera can be at infinity, in which case the depth is sliced up to the farthest light instead.
+struct ClusterFrustum {
+    view: Mat4,
+    projection: Mat4,
+    perspective: bool,
+    z_near: f32,
+    z_far: f32
+}
+
+impl ClusterFrustum {
+    fn new(camera: &Camera, bounds: &[(Vec3, f32)]) -> Self
+    {
+        let view = *camera.get_view();
+        Self {view, projection: *camera.get_projection(), perspective: camera.is_perspective(), z_near: camera.z_near(),
+            z_far: slicing_far(&view, camera.z_near(), camera.z_far(), bounds)}
+    }
+}
+
+// The far plane if it is finite, otherwise the largest depth reached by any of the lights
+fn slicing_far(view: &Mat4, z_near: f32, z_far: f32, bounds: &[(Vec3, f32)]) -> f32
+{
+    if z_far.is_finite() {
+        return z_far;
+    }
+    let farthest = bounds.iter().map(|(position, radius)| radius - (view * position.extend(1.0)).z).fold(0.0, f32::max);
+    farthest.max(2.0 * z_near)
+}
+
+// Returns the minimum and maximum cluster that a sphere with the given center and radius overlaps, or None if it is outside the view
+fn cluster_range(frustum: &ClusterFrustum, position: &Vec3, radius: f32) -> Option<(ClusterIndex, ClusterIndex)>
+{
+    let (z_near, z_far) = (frustum.z_near, frustum.z_far);
+    let center = (frustum.view * position.extend(1.0)).truncate();
+    let min_depth = -center.z - radius;
+    let max_depth = -center.z + radius;
+    if max_depth < z_near || min_depth > z_far {
+        return None;
+    }
+    let slice = |depth: f32| {
+        let s = if frustum.perspective {
+            (depth.max(z_near) / z_near).ln() / (z_far / z_near).ln()
+        } else {
+            (depth - z_near) / (z_far - z_near)
+        };
+        ((s * CLUSTER_COUNT_Z as f32).max(0.0) as usize).min(CLUSTER_COUNT_Z - 1)
+    };
+
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (-1.0f32, -1.0f32, 1.0f32, 1.0f32);
+    if !frustum.perspective || min_depth > z_near {
+        // All corners of the bounding box of the sphere are in front of the camera, so the projection of the box is bounded by the projection of the corners
+        let (mut x0, mut y0, mut x1, mut y1) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for i in 0..8 {
+            let corner = center + radius * vec3(if i & 1 == 0 {-1.0} else {1.0}, if i & 2 == 0 {-1.0} else {1.0}, if i & 4 == 0 {-1.0} else {1.0});
+            let p = frustum.projection * corner.extend(1.0);
+            x0 = x0.min(p.x / p.w);
+            y0 = y0.min(p.y / p.w);
+            x1 = x1.max(p.x / p.w);
+            y1 = y1.max(p.y / p.w);
+        }
+        if x1 < -1.0 || x0 > 1.0 || y1 < -1.0 || y0 > 1.0 {
+            return None;
+        }
+        min_x = x0.max(-1.0);
+        min_y = y0.max(-1.0);
+        max_x = x1.min(1.0);
+        max_y = y1.min(1.0);
+    }
+    let tile = |ndc: f32, count: usize| (((0.5 * ndc + 0.5) * count as f32) as usize).min(count - 1);
+    Some(((tile(min_x, CLUSTER_COUNT_X), tile(min_y, CLUSTER_COUNT_Y), slice(min_depth)),
+          (tile(max_x, CLUSTER_COUNT_X), tile(max_y, CLUSTER_COUNT_Y), slice(max_depth))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A camera at the origin looking down the negative z axis
+    fn frustum(projection: Mat4, perspective: bool, z_near: f32, z_far: f32) -> ClusterFrustum
+    {
+        ClusterFrustum {view: Mat4::look_at(Point::new(0.0, 0.0, 0.0), Point::new(0.0, 0.0, -1.0), vec3(0.0, 1.0, 0.0)),
+            projection, perspective, z_near, z_far}
+    }
+
+    // One tile is one unit wide and high and one slice is one unit deep
+    fn orthographic() -> ClusterFrustum
+    {
+        frustum(ortho(-8.0, 8.0, -4.5, 4.5, 0.0, 24.0), false, 0.0, 24.0)
+    }
+
+    // The slice borders are at the depths 0.1 * 2^k
+    fn perspective_frustum(z_far: f32) -> ClusterFrustum
+    {
+        frustum(perspective(degrees(60.0), 16.0 / 9.0, 0.1, 1000.0), true, 0.1, z_far)
+    }
+
+    #[test]
+    fn light_straddling_tile_and_slice_borders()
+    {
+        let range = cluster_range(&orthographic(), &vec3(0.5, 0.25, -10.5), 1.0);
+        assert_eq!(range, Some(((7, 3, 9), (9, 5, 11))));
+
+        let range = cluster_range(&perspective_frustum(0.1 * 2.0f32.powi(24)), &vec3(0.0, 0.0, -6.0), 1.0);
+        assert_eq!(range, Some(((6, 2, 5), (9, 6, 6))));
+    }
+
+    #[test]
+    fn light_inside_one_cluster()
+    {
+        let range = cluster_range(&orthographic(), &vec3(0.5, 0.0, -10.5), 0.25);
+        assert_eq!(range, Some(((8, 4, 10), (8, 4, 10))));
+    }
+
+    #[test]
+    fn light_outside_view()
+    {
+        assert_eq!(cluster_range(&orthographic(), &vec3(20.0, 0.0, -10.0), 1.0), None);
+        assert_eq!(cluster_range(&orthographic(), &vec3(0.0, 0.0, -30.0), 1.0), None);
+        assert_eq!(cluster_range(&perspective_frustum(100.0), &vec3(100.0, 0.0, -5.0), 1.0), None);
+    }
+
+    #[test]
+    fn light_behind_camera()
+    {
+        assert_eq!(cluster_range(&perspective_frustum(100.0), &vec3(0.0, 0.0, 5.0), 1.0), None);
+        // A light which reaches in front of the camera covers the whole screen in the slices it reaches
+        let range = cluster_range(&perspective_frustum(100.0), &vec3(0.0, 0.0, 0.5), 1.0);
+        assert_eq!(range, Some(((0, 0, 0), (CLUSTER_COUNT_X - 1, CLUSTER_COUNT_Y - 1, 5))));
+    }
+
+    #[test]
+    fn infinite_far_plane_slices_up_to_the_farthest_light()
+    {
+        let bounds = [(vec3(0.0, 0.0, -10.0), 1.0), (vec3(0.0, 0.0, -100.0), 1.0)];
+        let view = perspective_frustum(f32::INFINITY).view;
+        let z_far = slicing_far(&view, 0.1, f32::INFINITY, &bounds);
+        assert_eq!(z_far, 101.0);
+        assert_eq!(slicing_far(&view, 0.1, 50.0, &bounds), 50.0);
+        assert_eq!(slicing_far(&view, 0.1, f32::INFINITY, &[]), 0.2);
+
+        let frustum = perspective_frustum(z_far);
+        let (near_min, near_max) = cluster_range(&frustum, &bounds[0].0, bounds[0].1).unwrap();
+        let (far_min, far_max) = cluster_range(&frustum, &bounds[1].0, bounds[1].1).unwrap();
+        assert!(near_min.2 > 0 && near_max.2 < far_min.2);
+        assert_eq!(far_max.2, CLUSTER_COUNT_Z - 1);
+    }
+}
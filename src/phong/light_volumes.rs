@@ -0,0 +1,148 @@
+use crate::*;
+use std::f32::consts::PI;
+
+const SEGMENTS: usize = 16;
+const RINGS: usize = 12;
+// Spot lights with a larger cutoff angle are rendered with a sphere, since the cone would be very wide
+const MAX_CONE_CUTOFF: f32 = 60.0;
+
+///
+/// Renders point and spot lights by rasterizing a bounding volume, a sphere for point lights and a cone for spot lights, sized by the influence radius of the light,
+/// so that only the pixels covered by the volume are shaded. Only the back faces of the volumes are rendered and without depth test,
+/// which means that each pixel covered by a volume is shaded exactly once, also when the camera is inside the volume.
+///
+pub(crate) struct LightVolumes {
+    point_light_program: Program,
+    spot_light_program: Program,
+    sphere_positions: VertexBuffer,
+    sphere_indices: ElementBuffer,
+    cone_positions: VertexBuffer,
+    cone_indices: ElementBuffer
+}
+
+impl LightVolumes {
+
+    pub fn new(gl: &Gl, gbuffer_layout: &GBufferLayout) -> Result<Self, Error>
+    {
+        let (positions, indices) = Self::sphere();
+        let sphere_positions = VertexBuffer::new_with_static_f32(gl, &positions)?;
+        let sphere_indices = ElementBuffer::new_with_u32(gl, &indices)?;
+        let (positions, indices) = Self::cone();
+        let cone_positions = VertexBuffer::new_with_static_f32(gl, &positions)?;
+        let cone_indices = ElementBuffer::new_with_u32(gl, &indices)?;
+        Ok(Self {
+            point_light_program: Self::program(gl, gbuffer_layout, shader_segment!("shaders/point_light.frag"))?,
+            spot_light_program: Self::program(gl, gbuffer_layout, shader_segment!("shaders/spot_light.frag"))?,
+            sphere_positions, sphere_indices, cone_positions, cone_indices
+        })
+    }
+
+    ///
+    /// Whether the light can be rendered with a light volume, which is not the case if it is not attenuated.
+    ///
+    pub fn has_volume(radius: f32) -> bool
+    {
+        radius.is_finite()
+    }
+
+    pub fn render_point_light(&self, camera: &Camera, gbuffer: &Texture2DArray, depth_map: &Texture2DArray, light: &PointLight) -> Result<(), Error>
+    {
+        let program = &self.point_light_program;
+        Self::use_common_uniforms(program, camera, gbuffer, depth_map)?;
+        program.use_uniform_block(light.buffer(), "PointLightUniform");
+        let radius = light.influence_radius();
+        let model = Mat4::from_translation(light.position()) * Mat4::from_scale(radius);
+        program.add_uniform_mat4("modelMatrix", &model)?;
+        program.use_attribute_vec3_float(&self.sphere_positions, "position")?;
+        program.draw_elements(&self.sphere_indices);
+        Ok(())
+    }
+
+    pub fn render_spot_light(&self, camera: &Camera, gbuffer: &Texture2DArray, depth_map: &Texture2DArray, light: &SpotLight) -> Result<(), Error>
+    {
+        let program = &self.spot_light_program;
+        Self::use_common_uniforms(program, camera, gbuffer, depth_map)?;
+        program.use_texture(light.shadow_map(), "shadowMap")?;
+        program.use_uniform_block(light.buffer(), "SpotLightUniform");
+        let radius = light.influence_radius();
+        if light.cutoff() > MAX_CONE_CUTOFF {
+            let model = Mat4::from_translation(light.position()) * Mat4::from_scale(radius);
+            program.add_uniform_mat4("modelMatrix", &model)?;
+            program.use_attribute_vec3_float(&self.sphere_positions, "position")?;
+            program.draw_elements(&self.sphere_indices);
+        } else {
+            let z = light.direction().normalize();
+            let x = if z.x.abs() > 0.9 { vec3(0.0, 1.0, 0.0).cross(z) } else { vec3(1.0, 0.0, 0.0).cross(z) }.normalize();
+            let y = z.cross(x);
+            let base_radius = radius * degrees(light.cutoff()).tan();
+            let model = Mat4::from_cols(x.extend(0.0), y.extend(0.0), z.extend(0.0), light.position().extend(1.0))
+                * Mat4::from_nonuniform_scale(base_radius, base_radius, radius);
+            program.add_uniform_mat4("modelMatrix", &model)?;
+            program.use_attribute_vec3_float(&self.cone_positions, "position")?;
+            program.draw_elements(&self.cone_indices);
+        }
+        Ok(())
+    }
+
+    fn use_common_uniforms(program: &Program, camera: &Camera, gbuffer: &Texture2DArray, depth_map: &Texture2DArray) -> Result<(), Error>
+    {
+        program.use_texture(gbuffer, "gbuffer")?;
+        program.use_texture(depth_map, "depthMap")?;
+        program.add_uniform_vec3("eyePosition", camera.position())?;
+        program.add_uniform_mat4("viewProjectionInverse", &camera.view_projection_inverse())?;
+        program.add_uniform_mat4("viewProjection", &(camera.get_projection() * camera.get_view()))?;
+        program.add_uniform_float("farClipDepth", &camera.far_clip_depth())?;
+        Ok(())
+    }
+
+    fn program(gl: &Gl, gbuffer_layout: &GBufferLayout, light_shader: ShaderSegment) -> Result<Program, Error>
+    {
+        Program::from_shader_source(gl, &ShaderSource::from_segments(vec![shader_segment!("shaders/light_volume.vert")]),
+                                    &ShaderSource::from_segments([gbuffer_layout.shader_segments(), vec![
+                                        ShaderSegment::new("defines", "#define LIGHT_VOLUME\n"),
+                                        shader_segment!("shaders/light_shared.frag"),
+                                        shader_segment!("shaders/deferred_light_shared.frag"),
+                                        light_shader]].concat()))
+    }
+
+    // A sphere which contains the unit sphere, that is, the faces are pushed out so that they do not cut into the unit sphere
+    fn sphere() -> (Vec<f32>, Vec<u32>)
+    {
+        let scale = 1.0 / ((PI / SEGMENTS as f32).cos() * (0.5 * PI / RINGS as f32).cos());
+        let mut positions = Vec::new();
+        for ring in 0..=RINGS {
+            let theta = PI * ring as f32 / RINGS as f32;
+            for segment in 0..SEGMENTS {
+                let phi = 2.0 * PI * segment as f32 / SEGMENTS as f32;
+                positions.extend_from_slice(&[scale * theta.sin() * phi.cos(), scale * theta.cos(), scale * theta.sin() * phi.sin()]);
+            }
+        }
+        let mut indices = Vec::new();
+        for ring in 0..RINGS {
+            for segment in 0..SEGMENTS {
+                let next = (segment + 1) % SEGMENTS;
+                let (a, b) = ((ring * SEGMENTS + segment) as u32, (ring * SEGMENTS + next) as u32);
+                let (c, d) = (((ring + 1) * SEGMENTS + segment) as u32, ((ring + 1) * SEGMENTS + next) as u32);
+                indices.extend_from_slice(&[a, b, c, b, d, c]);
+            }
+        }
+        (positions, indices)
+    }
+
+    // A cone with the apex at the origin and a base at z = 1 which contains the unit circle
+    fn cone() -> (Vec<f32>, Vec<u32>)
+    {
+        let scale = 1.0 / (PI / SEGMENTS as f32).cos();
+        let mut positions = vec![0.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+        for segment in 0..SEGMENTS {
+            let phi = 2.0 * PI * segment as f32 / SEGMENTS as f32;
+            positions.extend_from_slice(&[scale * phi.cos(), scale * phi.sin(), 1.0]);
+        }
+        let mut indices = Vec::new();
+        for segment in 0..SEGMENTS {
+            let (a, b) = (2 + segment as u32, 2 + ((segment + 1) % SEGMENTS) as u32);
+            indices.extend_from_slice(&[0, b, a, 1, a, b]);
+        }
+        (positions, indices)
+    }
+}
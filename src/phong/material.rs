@@ -14,13 +14,19 @@ pub struct PhongMaterial {
     pub color_source: ColorSource,
     pub diffuse_intensity: f32,
     pub specular_intensity: f32,
-    pub specular_power: f32
+    pub specular_power: f32,
+    /// The color emitted by the material, which is only used by the deferred pipeline if the G-buffer layout has an emissive layer.
+    pub emissive: Vec3,
+    /// The metalness, roughness and id of the material, which are only stored by the deferred pipeline if the G-buffer layout has a material layer.
+    pub metalness: f32,
+    pub roughness: f32,
+    pub material_id: u8
 }
 
 impl PhongMaterial {
     pub fn new(gl: &Gl, cpu_material: &CPUMaterial) -> Result<Self, Error> {
         let color_source = if let Some(ref image) = cpu_material.texture_image {
-            ColorSource::Texture(Rc::new(texture::Texture2D::new_with_u8(&gl, Interpolation::Linear, Interpolation::Linear,
+            ColorSource::Texture(Rc::new(texture::Texture2D::new_with_srgb_u8(&gl, Interpolation::Linear, Interpolation::Linear,
                                                                   Some(Interpolation::Linear), Wrapping::Repeat, Wrapping::Repeat, image)?))
         }
         else {
@@ -28,7 +34,7 @@ impl PhongMaterial {
         };
         Ok(Self {name: cpu_material.name.clone(), color_source, diffuse_intensity: cpu_material.diffuse_intensity.unwrap_or(0.5),
             specular_intensity: cpu_material.specular_intensity.unwrap_or(0.2),
-            specular_power: cpu_material.specular_power.unwrap_or(6.0)})
+            specular_power: cpu_material.specular_power.unwrap_or(6.0), ..Default::default()})
     }
 }
 
@@ -39,7 +45,11 @@ impl Default for PhongMaterial {
             color_source: ColorSource::Color(vec4(1.0, 1.0, 1.0, 1.0)),
             diffuse_intensity: 0.5,
             specular_intensity: 0.2,
-            specular_power: 6.0
+            specular_power: 6.0,
+            emissive: vec3(0.0, 0.0, 0.0),
+            metalness: 0.0,
+            roughness: 1.0,
+            material_id: 0
         }
      }
 }
\ No newline at end of file
@@ -4,25 +4,25 @@ use std::rc::Rc;
 
 pub struct PhongForwardMesh {
     pub name: String,
-    program_color_ambient: Rc<Program>,
-    program_color_ambient_directional: Rc<Program>,
-    program_texture_ambient: Rc<Program>,
-    program_texture_ambient_directional: Rc<Program>,
+    programs: Rc<PhongForwardPrograms>,
     position_buffer: VertexBuffer,
     normal_buffer: VertexBuffer,
     index_buffer: Option<ElementBuffer>,
     uv_buffer: Option<VertexBuffer>,
-    pub material: PhongMaterial
+    pub material: PhongMaterial,
+    ///
+    /// The origin of the mesh in world space, which the transformation given when rendering is relative to.
+    /// The translation from the origin to the [camera origin](crate::Camera::origin) is computed in double precision on the CPU,
+    /// so that meshes far from the world origin can be rendered without jitter, see [Camera::set_view_f64](crate::Camera::set_view_f64).
+    ///
+    pub origin: Vec3d
 }
 
 impl PhongForwardMesh
 {
     pub fn new(gl: &Gl, cpu_mesh: &CPUMesh, material: &PhongMaterial) -> Result<Self, Error>
     {
-        Self::new_with_programs(gl, Self::program_color_ambient(gl)?,
-                                Self::program_color_ambient_directional(gl)?,
-                                Self::program_texture_ambient(gl)?,
-                                Self::program_texture_ambient_directional(gl)?, cpu_mesh, material)
+        Self::new_with_programs(gl, Rc::new(PhongForwardPrograms::new(gl, &MaxLights::default())?), cpu_mesh, material)
     }
 
     pub fn name(&self) -> &str {
@@ -32,8 +32,8 @@ impl PhongForwardMesh
     pub fn render_with_ambient(&self, transformation: &Mat4, camera: &camera::Camera, ambient_light: &AmbientLight) -> Result<(), Error>
     {
         let program = match self.material.color_source {
-            ColorSource::Color(_) => self.program_color_ambient.as_ref(),
-            ColorSource::Texture(_) => self.program_texture_ambient.as_ref()
+            ColorSource::Color(_) => &self.programs.color_ambient,
+            ColorSource::Texture(_) => &self.programs.texture_ambient
         };
         program.add_uniform_vec3("ambientLight.color", &ambient_light.color())?;
         program.add_uniform_float("ambientLight.intensity", &ambient_light.intensity())?;
@@ -44,8 +44,8 @@ impl PhongForwardMesh
     pub fn render_with_ambient_and_directional(&self, transformation: &Mat4, camera: &camera::Camera, ambient_light: &AmbientLight, directional_light: &DirectionalLight) -> Result<(), Error>
     {
         let program = match self.material.color_source {
-            ColorSource::Color(_) => self.program_color_ambient_directional.as_ref(),
-            ColorSource::Texture(_) => self.program_texture_ambient_directional.as_ref()
+            ColorSource::Color(_) => &self.programs.color_ambient_directional,
+            ColorSource::Texture(_) => &self.programs.texture_ambient_directional
         };
         program.add_uniform_vec3("ambientLight.color", &ambient_light.color())?;
         program.add_uniform_float("ambientLight.intensity", &ambient_light.intensity())?;
@@ -56,43 +56,152 @@ impl PhongForwardMesh
         Ok(())
     }
 
-    pub(crate) fn program_color_ambient(gl: &Gl) -> Result<Rc<Program>, Error>
+    ///
+    /// Renders the mesh lit by the environment light, that is, image-based diffuse and specular lighting, instead of an ambient light.
+    ///
+    pub fn render_with_environment(&self, transformation: &Mat4, camera: &camera::Camera, environment_light: &EnvironmentLight) -> Result<(), Error>
     {
-        Ok(Rc::new(Program::from_source(gl, include_str!("shaders/mesh.vert"),
-                                                                     &format!("{}\n{}",
-                                                                              &include_str!("shaders/light_shared.frag"),
-                                                                              &include_str!("shaders/colored_forward_ambient.frag")))?))
+        let program = match self.material.color_source {
+            ColorSource::Color(_) => &self.programs.color_environment,
+            ColorSource::Texture(_) => &self.programs.texture_environment
+        };
+        environment_light.use_uniforms(program)?;
+        program.add_uniform_vec3("eyePosition", camera.position())?;
+        self.render_internal(program, transformation, camera)?;
+        Ok(())
+    }
+
+    ///
+    /// Renders the mesh lit by the environment light and the directional light.
+    ///
+    pub fn render_with_environment_and_directional(&self, transformation: &Mat4, camera: &camera::Camera, environment_light: &EnvironmentLight, directional_light: &DirectionalLight) -> Result<(), Error>
+    {
+        let program = match self.material.color_source {
+            ColorSource::Color(_) => &self.programs.color_environment_directional,
+            ColorSource::Texture(_) => &self.programs.texture_environment_directional
+        };
+        environment_light.use_uniforms(program)?;
+        program.add_uniform_vec3("eyePosition", camera.position())?;
+        program.use_texture(directional_light.shadow_map(), "shadowMap")?;
+        program.use_uniform_block(directional_light.buffer(), "DirectionalLightUniform");
+        self.render_internal(program, transformation, camera)?;
+        Ok(())
+    }
+
+    ///
+    /// Renders the mesh lit by the hemisphere light, that is, an ambient light interpolated between a sky and a ground color.
+    ///
+    pub fn render_with_hemisphere(&self, transformation: &Mat4, camera: &camera::Camera, hemisphere_light: &HemisphereLight) -> Result<(), Error>
+    {
+        let program = match self.material.color_source {
+            ColorSource::Color(_) => &self.programs.color_hemisphere,
+            ColorSource::Texture(_) => &self.programs.texture_hemisphere
+        };
+        hemisphere_light.use_uniforms(program)?;
+        self.render_internal(program, transformation, camera)?;
+        Ok(())
     }
 
-    pub(crate) fn program_color_ambient_directional(gl: &Gl) -> Result<Rc<Program>, Error>
+    ///
+    /// Renders the mesh lit by the hemisphere light and the directional light.
+    ///
+    pub fn render_with_hemisphere_and_directional(&self, transformation: &Mat4, camera: &camera::Camera, hemisphere_light: &HemisphereLight, directional_light: &DirectionalLight) -> Result<(), Error>
     {
-        Ok(Rc::new(Program::from_source(gl, include_str!("shaders/mesh.vert"),
-                                                                     &format!("{}\n{}",
-                                                                              &include_str!("shaders/light_shared.frag"),
-                                                                              &include_str!("shaders/colored_forward_ambient_directional.frag")))?))
+        let program = match self.material.color_source {
+            ColorSource::Color(_) => &self.programs.color_hemisphere_directional,
+            ColorSource::Texture(_) => &self.programs.texture_hemisphere_directional
+        };
+        hemisphere_light.use_uniforms(program)?;
+        program.add_uniform_vec3("eyePosition", camera.position())?;
+        program.use_texture(directional_light.shadow_map(), "shadowMap")?;
+        program.use_uniform_block(directional_light.buffer(), "DirectionalLightUniform");
+        self.render_internal(program, transformation, camera)?;
+        Ok(())
     }
 
-    pub(crate) fn program_texture_ambient(gl: &Gl) -> Result<Rc<Program>, Error>
+    ///
+    /// Renders the mesh lit by the spherical harmonics light.
+    ///
+    pub fn render_with_sh(&self, transformation: &Mat4, camera: &camera::Camera, sh_light: &SHLight) -> Result<(), Error>
     {
-        Ok(Rc::new(Program::from_source(gl, include_str!("shaders/mesh.vert"),
-                                                                       &format!("{}\n{}\n{}",
-                                                                                include_str!("shaders/light_shared.frag"),
-                                                                                include_str!("shaders/triplanar_mapping.frag"),
-                                                                                include_str!("shaders/textured_forward_ambient.frag")))?))
+        let program = match self.material.color_source {
+            ColorSource::Color(_) => &self.programs.color_sh,
+            ColorSource::Texture(_) => &self.programs.texture_sh
+        };
+        sh_light.use_uniforms(program)?;
+        self.render_internal(program, transformation, camera)?;
+        Ok(())
     }
 
-    pub(crate) fn program_texture_ambient_directional(gl: &Gl) -> Result<Rc<Program>, Error>
+    ///
+    /// Renders the mesh lit by the spherical harmonics light and the directional light.
+    ///
+    pub fn render_with_sh_and_directional(&self, transformation: &Mat4, camera: &camera::Camera, sh_light: &SHLight, directional_light: &DirectionalLight) -> Result<(), Error>
     {
-        Ok(Rc::new(Program::from_source(gl, include_str!("shaders/mesh.vert"),
-                                                                       &format!("{}\n{}\n{}",
-                                                                                include_str!("shaders/light_shared.frag"),
-                                                                                include_str!("shaders/triplanar_mapping.frag"),
-                                                                                include_str!("shaders/textured_forward_ambient_directional.frag")))?))
+        let program = match self.material.color_source {
+            ColorSource::Color(_) => &self.programs.color_sh_directional,
+            ColorSource::Texture(_) => &self.programs.texture_sh_directional
+        };
+        sh_light.use_uniforms(program)?;
+        program.add_uniform_vec3("eyePosition", camera.position())?;
+        program.use_texture(directional_light.shadow_map(), "shadowMap")?;
+        program.use_uniform_block(directional_light.buffer(), "DirectionalLightUniform");
+        self.render_internal(program, transformation, camera)?;
+        Ok(())
     }
 
-    pub(crate) fn new_with_programs(gl: &Gl, program_color_ambient: Rc<Program>, program_color_ambient_directional: Rc<Program>,
-                                    program_texture_ambient: Rc<Program>, program_texture_ambient_directional: Rc<Program>,
-                   cpu_mesh: &CPUMesh, material: &PhongMaterial) -> Result<Self, Error>
+    ///
+    /// Renders the mesh lit by an optional ambient light and any number of directional, spot and point lights up to the maximum light count given when creating the pipeline,
+    /// see [PhongForwardPipeline::new_with_max_lights](crate::PhongForwardPipeline::new_with_max_lights). The shadow maps of directional and spot lights are used if they have been generated.
+    ///
+    pub fn render_with_lights(&self, transformation: &Mat4, camera: &camera::Camera, ambient_light: Option<&AmbientLight>, directional_lights: &[&DirectionalLight],
+                              spot_lights: &[&SpotLight], point_lights: &[&PointLight]) -> Result<(), Error>
+    {
+        let max_lights = &self.programs.max_lights;
+        if directional_lights.len() > max_lights.directional || spot_lights.len() > max_lights.spot || point_lights.len() > max_lights.point {
+            Err(Error::FailedToRender {message: format!("Cannot render with {} directional, {} spot and {} point lights, the maximum is {}, {} and {}",
+                    directional_lights.len(), spot_lights.len(), point_lights.len(), max_lights.directional, max_lights.spot, max_lights.point)})?;
+        }
+        let program = match self.material.color_source {
+            ColorSource::Color(_) => &self.programs.color_lights,
+            ColorSource::Texture(_) => &self.programs.texture_lights
+        };
+        let (color, intensity) = ambient_light.map(|light| (light.color(), light.intensity())).unwrap_or((vec3(0.0, 0.0, 0.0), 0.0));
+        program.add_uniform_vec3("ambientLight.color", &color)?;
+        program.add_uniform_float("ambientLight.intensity", &intensity)?;
+        program.add_uniform_vec3("eyePosition", camera.position())?;
+        if max_lights.directional > 0 {
+            program.add_uniform_int("directionalLightCount", &(directional_lights.len() as i32))?;
+        }
+        for (i, light) in directional_lights.iter().enumerate() {
+            light.use_uniforms(program, &format!("directionalLights[{}]", i))?;
+            program.use_texture(light.shadow_map(), &format!("directionalShadowMap{}", i))?;
+        }
+        // The samplers of the unused lights are never sampled, but they are bound to a depth texture anyway, since all samplers default to texture unit 0
+        for i in directional_lights.len()..max_lights.directional {
+            program.use_texture(&self.programs.unused_shadow_map, &format!("directionalShadowMap{}", i))?;
+        }
+        if max_lights.spot > 0 {
+            program.add_uniform_int("spotLightCount", &(spot_lights.len() as i32))?;
+        }
+        for (i, light) in spot_lights.iter().enumerate() {
+            light.use_uniforms(program, &format!("spotLights[{}]", i))?;
+            program.use_texture(light.shadow_map(), &format!("spotShadowMap{}", i))?;
+        }
+        for i in spot_lights.len()..max_lights.spot {
+            program.use_texture(&self.programs.unused_shadow_map, &format!("spotShadowMap{}", i))?;
+        }
+        if max_lights.point > 0 {
+            program.add_uniform_int("pointLightCount", &(point_lights.len() as i32))?;
+        }
+        for (i, light) in point_lights.iter().enumerate() {
+            light.use_uniforms(program, &format!("pointLights[{}]", i))?;
+        }
+        self.render_internal(program, transformation, camera)?;
+        Ok(())
+    }
+
+    pub(crate) fn new_with_programs(gl: &Gl, programs: Rc<PhongForwardPrograms>, cpu_mesh: &CPUMesh, material: &PhongMaterial) -> Result<Self, Error>
     {
         let position_buffer = VertexBuffer::new_with_static_f32(gl, &cpu_mesh.positions)?;
         let normal_buffer = VertexBuffer::new_with_static_f32(gl,
@@ -101,8 +210,7 @@ impl PhongForwardMesh
         let index_buffer = if let Some(ref ind) = cpu_mesh.indices { Some(ElementBuffer::new_with_u32(gl, ind)?) } else {None};
         let uv_buffer = if let Some(ref uvs) = cpu_mesh.uvs { Some(VertexBuffer::new_with_static_f32(gl, uvs)?) } else {None};
 
-        Ok(Self { name: cpu_mesh.name.clone(), index_buffer, uv_buffer, position_buffer, normal_buffer,
-            program_color_ambient, program_color_ambient_directional, program_texture_ambient, program_texture_ambient_directional, material: material.clone() })
+        Ok(Self { name: cpu_mesh.name.clone(), index_buffer, uv_buffer, position_buffer, normal_buffer, programs, material: material.clone(), origin: vec3d(0.0, 0.0, 0.0) })
     }
 
     fn render_internal(&self, program: &Program, transformation: &Mat4, camera: &camera::Camera) -> Result<(), Error>
@@ -111,7 +219,7 @@ impl PhongForwardMesh
         program.add_uniform_float("specular_intensity", &self.material.specular_intensity)?;
         program.add_uniform_float("specular_power", &self.material.specular_power)?;
 
-        program.add_uniform_mat4("modelMatrix", &transformation)?;
+        program.add_uniform_mat4("modelMatrix", &camera.render_space_transformation(&self.origin, transformation))?;
         program.use_uniform_block(camera.matrix_buffer(), "Camera");
         program.add_uniform_mat4("normalMatrix", &transformation.invert().unwrap().transpose())?;
 
@@ -142,6 +250,123 @@ impl PhongForwardMesh
     }
 }
 
+///
+/// The programs used by the forward meshes, shared between all meshes created by the same pipeline.
+///
+pub(crate) struct PhongForwardPrograms {
+    color_ambient: Program,
+    color_ambient_directional: Program,
+    texture_ambient: Program,
+    texture_ambient_directional: Program,
+    color_environment: Program,
+    color_environment_directional: Program,
+    texture_environment: Program,
+    texture_environment_directional: Program,
+    color_hemisphere: Program,
+    color_hemisphere_directional: Program,
+    texture_hemisphere: Program,
+    texture_hemisphere_directional: Program,
+    color_sh: Program,
+    color_sh_directional: Program,
+    texture_sh: Program,
+    texture_sh_directional: Program,
+    color_lights: Program,
+    texture_lights: Program,
+    max_lights: MaxLights,
+    unused_shadow_map: Texture2D
+}
+
+impl PhongForwardPrograms {
+    pub fn new(gl: &Gl, max_lights: &MaxLights) -> Result<Self, Error>
+    {
+        let environment = || vec![ShaderSegment::new("defines", "#define USE_ENVIRONMENT\n")];
+        let hemisphere = || vec![ShaderSegment::new("defines", "#define USE_HEMISPHERE\n")];
+        let sh = || vec![ShaderSegment::new("defines", "#define USE_SH\n")];
+        let lights = || vec![Self::lights_segment(max_lights)];
+        Ok(Self {
+            color_ambient: Self::program(gl, vec![], vec![], false, shader_segment!("shaders/colored_forward_ambient.frag"))?,
+            color_ambient_directional: Self::program(gl, vec![], vec![], false, shader_segment!("shaders/colored_forward_ambient_directional.frag"))?,
+            texture_ambient: Self::program(gl, vec![], vec![], true, shader_segment!("shaders/textured_forward_ambient.frag"))?,
+            texture_ambient_directional: Self::program(gl, vec![], vec![], true, shader_segment!("shaders/textured_forward_ambient_directional.frag"))?,
+            color_environment: Self::program(gl, environment(), vec![shader_segment!("shaders/environment_light_shared.frag")], false, shader_segment!("shaders/colored_forward_ambient.frag"))?,
+            color_environment_directional: Self::program(gl, environment(), vec![shader_segment!("shaders/environment_light_shared.frag")], false, shader_segment!("shaders/colored_forward_ambient_directional.frag"))?,
+            texture_environment: Self::program(gl, environment(), vec![shader_segment!("shaders/environment_light_shared.frag")], true, shader_segment!("shaders/textured_forward_ambient.frag"))?,
+            texture_environment_directional: Self::program(gl, environment(), vec![shader_segment!("shaders/environment_light_shared.frag")], true, shader_segment!("shaders/textured_forward_ambient_directional.frag"))?,
+            color_hemisphere: Self::program(gl, hemisphere(), vec![], false, shader_segment!("shaders/colored_forward_ambient.frag"))?,
+            color_hemisphere_directional: Self::program(gl, hemisphere(), vec![], false, shader_segment!("shaders/colored_forward_ambient_directional.frag"))?,
+            texture_hemisphere: Self::program(gl, hemisphere(), vec![], true, shader_segment!("shaders/textured_forward_ambient.frag"))?,
+            texture_hemisphere_directional: Self::program(gl, hemisphere(), vec![], true, shader_segment!("shaders/textured_forward_ambient_directional.frag"))?,
+            color_sh: Self::program(gl, sh(), vec![], false, shader_segment!("shaders/colored_forward_ambient.frag"))?,
+            color_sh_directional: Self::program(gl, sh(), vec![], false, shader_segment!("shaders/colored_forward_ambient_directional.frag"))?,
+            texture_sh: Self::program(gl, sh(), vec![], true, shader_segment!("shaders/textured_forward_ambient.frag"))?,
+            texture_sh_directional: Self::program(gl, sh(), vec![], true, shader_segment!("shaders/textured_forward_ambient_directional.frag"))?,
+            color_lights: Self::program(gl, vec![], lights(), false, shader_segment!("shaders/colored_forward_ambient.frag"))?,
+            texture_lights: Self::program(gl, vec![], lights(), true, shader_segment!("shaders/textured_forward_ambient.frag"))?,
+            max_lights: max_lights.clone(),
+            unused_shadow_map: Texture2D::new(gl, 1, 1, Interpolation::Nearest, Interpolation::Nearest, None,
+                                              Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::Depth32F)?
+        })
+    }
+
+    // The segments are composed as defines, light_shared.frag, shared segments, triplanar_mapping.frag if textured and finally the main segment
+    fn program(gl: &Gl, defines: Vec<ShaderSegment>, shared: Vec<ShaderSegment>, textured: bool, main: ShaderSegment) -> Result<Program, Error>
+    {
+        let mut segments = defines;
+        segments.push(shader_segment!("shaders/light_shared.frag"));
+        segments.extend(shared);
+        if textured {
+            segments.push(shader_segment!("shaders/triplanar_mapping.frag"));
+        }
+        segments.push(main);
+        Program::from_shader_source(gl, &ShaderSource::from_segments(vec![shader_segment!("shaders/mesh.vert")]),
+                                    &ShaderSource::from_segments(segments))
+    }
+
+    // Declares the light arrays and the function calculate_lights, which sums the contribution of all lights.
+    // Samplers can only be indexed by constant expressions, so the directional and spot lights, which have shadow maps, are unrolled.
+    fn lights_segment(max_lights: &MaxLights) -> ShaderSegment
+    {
+        let mut declarations = String::from("#define USE_LIGHTS\n");
+        let mut body = String::new();
+        if max_lights.directional > 0 {
+            declarations += &format!("uniform int directionalLightCount;\nuniform DirectionalLight directionalLights[{}];\n", max_lights.directional);
+            for i in 0..max_lights.directional {
+                declarations += &format!("uniform sampler2D directionalShadowMap{};\n", i);
+                body += &format!("    if({0} < directionalLightCount) light += calculate_directional_light(directionalLights[{0}], directionalShadowMap{0}, surface);\n", i);
+            }
+        }
+        if max_lights.spot > 0 {
+            declarations += &format!("uniform int spotLightCount;\nuniform SpotLight spotLights[{}];\n", max_lights.spot);
+            for i in 0..max_lights.spot {
+                declarations += &format!("uniform sampler2D spotShadowMap{};\n", i);
+                body += &format!("    if({0} < spotLightCount) light += calculate_spot_light(spotLights[{0}], spotShadowMap{0}, surface);\n", i);
+            }
+        }
+        if max_lights.point > 0 {
+            declarations += &format!("uniform int pointLightCount;\nuniform PointLight pointLights[{}];\n", max_lights.point);
+            body += "    for(int i = 0; i < pointLightCount; i++) light += calculate_point_light(pointLights[i], surface);\n";
+        }
+        ShaderSegment::new("lights", &format!("{}\nvec3 calculate_lights(Surface surface)\n{{\n    vec3 light = vec3(0.0);\n{}    return light;\n}}\n", declarations, body))
+    }
+}
+
+///
+/// The maximum number of each type of light that a forward mesh can be rendered with in one pass, see [PhongForwardMesh::render_with_lights].
+/// Each directional and spot light uses a texture unit for its shadow map, so the total number of those should be kept below the number of available texture units.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxLights {
+    pub directional: usize,
+    pub spot: usize,
+    pub point: usize
+}
+
+impl Default for MaxLights {
+    fn default() -> Self {
+        Self { directional: 2, spot: 4, point: 8 }
+    }
+}
+
 pub struct PhongDeferredMesh {
     mesh: PhongForwardMesh,
     program_deferred_color: Rc<Program>,
@@ -153,8 +378,8 @@ impl PhongDeferredMesh {
     pub fn new(gl: &Gl, cpu_mesh: &CPUMesh, cpu_material: &PhongMaterial) -> Result<Self, Error>
     {
         Ok(Self::new_with_programs(PhongForwardMesh::new(gl, cpu_mesh, cpu_material)?,
-                                Self::program_color(gl)?,
-                                Self::program_textured(gl)?))
+                                Self::program_color(gl, &GBufferLayout::default())?,
+                                Self::program_textured(gl, &GBufferLayout::default())?))
     }
 
     pub fn name(&self) -> &str {
@@ -175,25 +400,26 @@ impl PhongDeferredMesh {
             ColorSource::Color(_) => self.program_deferred_color.as_ref(),
             ColorSource::Texture(_) => self.program_deferred_texture.as_ref()
         };
+        use_optional_layer_uniforms(program, &self.mesh.material, camera)?;
         self.mesh.render_internal(program, transformation, camera)?;
         Ok(())
     }
 
-    pub(crate) fn program_color(gl: &Gl) -> Result<Rc<Program>, Error>
+    pub(crate) fn program_color(gl: &Gl, gbuffer_layout: &GBufferLayout) -> Result<Rc<Program>, Error>
     {
-        Ok(Rc::new(Program::from_source(&gl,include_str!("shaders/mesh.vert"),
-                                                              &format!("{}\n{}",
-                                                             include_str!("shaders/deferred_objects_shared.frag"),
-                                                             include_str!("shaders/colored_deferred.frag")))?))
+        Ok(Rc::new(Program::from_shader_source(&gl, &ShaderSource::from_segments(vec![gbuffer_layout.shader_defines(), shader_segment!("shaders/mesh.vert")]),
+                                                    &ShaderSource::from_segments([gbuffer_layout.shader_segments(), vec![
+                                                                          shader_segment!("shaders/deferred_objects_shared.frag"),
+                                                                          shader_segment!("shaders/colored_deferred.frag")]].concat()))?))
     }
 
-    pub(crate) fn program_textured(gl: &Gl) -> Result<Rc<Program>, Error>
+    pub(crate) fn program_textured(gl: &Gl, gbuffer_layout: &GBufferLayout) -> Result<Rc<Program>, Error>
     {
-        Ok(Rc::new(Program::from_source(&gl,include_str!("shaders/mesh.vert"),
-                                                    &format!("{}\n{}\n{}",
-                                                             include_str!("shaders/deferred_objects_shared.frag"),
-                                                             include_str!("shaders/triplanar_mapping.frag"),
-                                                             include_str!("shaders/textured_deferred.frag")))?))
+        Ok(Rc::new(Program::from_shader_source(&gl, &ShaderSource::from_segments(vec![gbuffer_layout.shader_defines(), shader_segment!("shaders/mesh.vert")]),
+                                                    &ShaderSource::from_segments([gbuffer_layout.shader_segments(), vec![
+                                                                          shader_segment!("shaders/deferred_objects_shared.frag"),
+                                                                          shader_segment!("shaders/triplanar_mapping.frag"),
+                                                                          shader_segment!("shaders/textured_deferred.frag")]].concat()))?))
     }
 
     pub(crate) fn new_with_programs(mesh: PhongForwardMesh, program_deferred_color: Rc<Program>, program_deferred_texture: Rc<Program>) -> Self
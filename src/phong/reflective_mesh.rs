@@ -0,0 +1,110 @@
+use crate::*;
+use std::rc::Rc;
+
+///
+/// A material for reflective surfaces like water or a polished floor, which reflects the scene rendered by a [PlanarReflection].
+///
+#[derive(Clone)]
+pub struct PhongReflectiveMaterial {
+    /// The color of the surface itself, which is lit by the ambient light and seen where the surface does not reflect.
+    pub color: Vec4,
+    /// The fraction of the light which is reflected when looking straight at the surface, the reflection increases towards grazing angles following the Fresnel equations.
+    pub reflectivity: f32,
+    /// An optional normal map, which is mapped onto the xz plane in world space and distorts the reflection, for example to simulate waves.
+    pub normal_map: Option<Rc<Texture2D>>,
+    /// The number of times the normal map is repeated per unit in world space.
+    pub normal_map_scale: f32,
+    /// An offset of the normal map, which can be animated to make the waves move.
+    pub normal_map_offset: Vec2,
+    /// How much the normal map distorts the reflection, in screen space.
+    pub distortion: f32
+}
+
+impl Default for PhongReflectiveMaterial {
+    fn default() -> Self {
+        Self {
+            color: vec4(0.1, 0.2, 0.3, 1.0),
+            reflectivity: 0.02,
+            normal_map: None,
+            normal_map_scale: 1.0,
+            normal_map_offset: vec2(0.0, 0.0),
+            distortion: 0.02
+        }
+    }
+}
+
+///
+/// A mesh which reflects the scene rendered by a [PlanarReflection], blended with the color of the material using a Fresnel term.
+/// The mesh is assumed to lie in the plane of the reflection and should be rendered in the forward pass.
+///
+pub struct PhongReflectiveMesh {
+    pub name: String,
+    program: Program,
+    position_buffer: VertexBuffer,
+    normal_buffer: VertexBuffer,
+    index_buffer: Option<ElementBuffer>,
+    pub material: PhongReflectiveMaterial,
+    ///
+    /// The origin of the mesh in world space, which the transformation given when rendering is relative to, see [PhongForwardMesh::origin].
+    ///
+    pub origin: Vec3d
+}
+
+impl PhongReflectiveMesh
+{
+    pub fn new(gl: &Gl, cpu_mesh: &CPUMesh, material: &PhongReflectiveMaterial) -> Result<Self, Error>
+    {
+        let position_buffer = VertexBuffer::new_with_static_f32(gl, &cpu_mesh.positions)?;
+        let normal_buffer = VertexBuffer::new_with_static_f32(gl,
+              cpu_mesh.normals.as_ref().ok_or(Error::FailedToCreateMesh {message:
+              "Cannot create a mesh without normals. Consider calling compute_normals on the CPUMesh before creating the mesh.".to_string()})?)?;
+        let index_buffer = if let Some(ref ind) = cpu_mesh.indices { Some(ElementBuffer::new_with_u32(gl, ind)?) } else {None};
+        let program = Program::from_shader_source(gl, &ShaderSource::from_segments(vec![shader_segment!("shaders/mesh.vert")]),
+                                                  &ShaderSource::from_segments(vec![
+                                                      shader_segment!("shaders/light_shared.frag"),
+                                                      shader_segment!("shaders/reflective.frag")]))?;
+        Ok(Self { name: cpu_mesh.name.clone(), program, position_buffer, normal_buffer, index_buffer, material: material.clone(), origin: vec3d(0.0, 0.0, 0.0) })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn render(&self, transformation: &Mat4, camera: &camera::Camera, ambient_light: &AmbientLight, reflection: &PlanarReflection) -> Result<(), Error>
+    {
+        let program = &self.program;
+        program.add_uniform_vec3("ambientLight.color", &ambient_light.color())?;
+        program.add_uniform_float("ambientLight.intensity", &ambient_light.intensity())?;
+        program.add_uniform_vec3("eyePosition", camera.position())?;
+        program.use_texture(reflection.color_texture(), "reflectionMap")?;
+
+        program.add_uniform_vec4("color", &self.material.color)?;
+        program.add_uniform_float("reflectivity", &self.material.reflectivity)?;
+        if let Some(ref normal_map) = self.material.normal_map {
+            program.add_uniform_int("use_normal_map", &1)?;
+            program.use_texture(normal_map.as_ref(), "normalMap")?;
+            program.add_uniform_float("normalMapScale", &self.material.normal_map_scale)?;
+            // The normal map is mapped in world space, but the positions in the shader are relative to the camera origin, so the origin is added to the offset.
+            // Only the fractional part is needed since the normal map repeats, which keeps the offset accurate when the origin is far away.
+            let origin = camera.origin() * self.material.normal_map_scale as f64;
+            let offset = self.material.normal_map_offset + vec2(origin.x.rem_euclid(1.0) as f32, origin.z.rem_euclid(1.0) as f32);
+            program.add_uniform_vec2("normalMapOffset", &offset)?;
+            program.add_uniform_float("distortion", &self.material.distortion)?;
+        } else {
+            program.add_uniform_int("use_normal_map", &0)?;
+        }
+
+        program.add_uniform_mat4("modelMatrix", &camera.render_space_transformation(&self.origin, transformation))?;
+        program.use_uniform_block(camera.matrix_buffer(), "Camera");
+        program.add_uniform_mat4("normalMatrix", &transformation.invert().unwrap().transpose())?;
+        program.use_attribute_vec3_float(&self.position_buffer, "position")?;
+        program.use_attribute_vec3_float(&self.normal_buffer, "normal")?;
+
+        if let Some(ref index_buffer) = self.index_buffer {
+            program.draw_elements(index_buffer);
+        } else {
+            program.draw_arrays(self.position_buffer.count() as u32/3);
+        }
+        Ok(())
+    }
+}
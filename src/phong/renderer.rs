@@ -1,35 +1,110 @@
 
 use crate::*;
 use std::rc::Rc;
-use crate::PhongForwardMesh;
+use std::cell::RefCell;
+use crate::{PhongForwardMesh, PhongForwardPrograms};
+use crate::phong::light_clusters::LightClusters;
+use crate::phong::light_volumes::LightVolumes;
+use crate::phong::area_lights::AreaLights;
 
 pub struct PhongForwardPipeline {
     gl: Gl,
-    mesh_color_ambient_program: Rc<Program>,
-    mesh_color_ambient_directional_program: Rc<Program>,
-    mesh_texture_ambient_program: Rc<Program>,
-    mesh_texture_ambient_directional_program: Rc<Program>,
+    mesh_programs: Rc<PhongForwardPrograms>,
+    srgb_encode_effect: ImageEffect,
+    linear_textures: RefCell<Option<(Texture2D, Texture2D)>>
 }
 
 impl PhongForwardPipeline {
 
     pub fn new(gl: &Gl) -> Result<Self, Error>
+    {
+        Self::new_with_max_lights(gl, &MaxLights::default())
+    }
+
+    ///
+    /// Creates a pipeline where the meshes can be rendered with up to the given number of lights, see [PhongForwardMesh::render_with_lights].
+    ///
+    pub fn new_with_max_lights(gl: &Gl, max_lights: &MaxLights) -> Result<Self, Error>
     {
         Ok(Self {
             gl: gl.clone(),
-            mesh_color_ambient_program: PhongForwardMesh::program_color_ambient(gl)?,
-            mesh_color_ambient_directional_program: PhongForwardMesh::program_color_ambient_directional(gl)?,
-            mesh_texture_ambient_program: PhongForwardMesh::program_texture_ambient(gl)?,
-            mesh_texture_ambient_directional_program: PhongForwardMesh::program_texture_ambient_directional(gl)?
+            mesh_programs: Rc::new(PhongForwardPrograms::new(gl, max_lights)?),
+            srgb_encode_effect: ImageEffect::from_shader_source(gl, &ShaderSource::from_segments(vec![shader_segment!("shaders/srgb_encode.frag")]))?,
+            linear_textures: RefCell::new(None)
         })
     }
 
+    ///
+    /// Renders the scene in linear color space to an sRGB render target, which means that blending happens in linear space,
+    /// and then copies the result to the screen, encoded in the sRGB color space, together with the depth.
+    ///
     pub fn render_to_screen<F: FnOnce() -> Result<(), Error>>(&self, width: usize, height: usize, render_scene: F) -> Result<(), Error>
     {
-        Ok(Screen::write(&self.gl, 0, 0, width, height,
-                         Some(&vec4(0.0, 0.0, 0.0, 1.0)),
-                         Some(1.0),
-                         render_scene)?)
+        let (color_texture, depth_texture) = self.render_to_linear_textures(width, height, render_scene)?;
+        Screen::write(&self.gl, 0, 0, width, height, None, None, || {
+            self.copy_encoded(&color_texture, &depth_texture)
+        })?;
+        self.linear_textures.replace(Some((color_texture, depth_texture)));
+        Ok(())
+    }
+
+    ///
+    /// Same as [render_to_screen](Self::render_to_screen), except that the result is copied to the given color and depth textures
+    /// within the viewport rectangle starting at the pixel (x, y) with the given width and height.
+    /// The content of the textures outside the viewport is left untouched, so several render calls can write to different parts of the same textures.
+    /// The colors are written encoded in the sRGB color space, like on the screen, so the color texture should have a linear format, for example [Format::RGBA8].
+    /// At least one of the textures must be given and the depth texture must have a depth format.
+    ///
+    pub fn render_to_target<F: FnOnce() -> Result<(), Error>>(&self, color_texture: Option<&Texture2D>, depth_texture: Option<&Texture2D>,
+                                                              x: i32, y: i32, width: usize, height: usize, render_scene: F) -> Result<(), Error>
+    {
+        let (linear_color_texture, linear_depth_texture) = self.render_to_linear_textures(width, height, render_scene)?;
+        RenderTarget::write(&self.gl, x, y, width, height, None, None, color_texture, depth_texture, || {
+            self.copy_encoded(&linear_color_texture, &linear_depth_texture)
+        })?;
+        self.linear_textures.replace(Some((linear_color_texture, linear_depth_texture)));
+        Ok(())
+    }
+
+    ///
+    /// Same as [render_to_screen](Self::render_to_screen), except that the scene is rendered once for each of the given cameras,
+    /// in the viewport of the camera (see [Camera::set_viewport]) or in the whole frame if the camera has no viewport.
+    /// The rendering is restricted to the viewport with the scissor test.
+    ///
+    pub fn render_cameras_to_screen<F: FnMut(&Camera) -> Result<(), Error>>(&self, width: usize, height: usize, cameras: &[&Camera], render_scene: F) -> Result<(), Error>
+    {
+        self.render_to_screen(width, height, || render_viewports(&self.gl, width, height, cameras, render_scene))
+    }
+
+    // The textures are reused when the size is unchanged. They are taken out of the pipeline while rendering and put back by the caller afterwards,
+    // so that rendering with the same pipeline inside render_scene, for example to a texture, gets its own textures.
+    fn render_to_linear_textures<F: FnOnce() -> Result<(), Error>>(&self, width: usize, height: usize, render_scene: F) -> Result<(Texture2D, Texture2D), Error>
+    {
+        let (color_texture, depth_texture) = match self.linear_textures.take() {
+            Some((color_texture, depth_texture)) if color_texture.width == width && color_texture.height == height => (color_texture, depth_texture),
+            _ => (Texture2D::new(&self.gl, width, height, Interpolation::Nearest, Interpolation::Nearest, None,
+                                 Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::SRGB8Alpha8)?,
+                  Texture2D::new(&self.gl, width, height, Interpolation::Nearest, Interpolation::Nearest, None,
+                                 Wrapping::ClampToEdge, Wrapping::ClampToEdge, Format::Depth32F)?)
+        };
+        RenderTarget::write(&self.gl, 0, 0, width, height,
+                            Some(&vec4(0.0, 0.0, 0.0, 1.0)),
+                            Some(1.0),
+                            Some(&color_texture), Some(&depth_texture),
+                            render_scene)?;
+        Ok((color_texture, depth_texture))
+    }
+
+    fn copy_encoded(&self, color_texture: &Texture2D, depth_texture: &Texture2D) -> Result<(), Error>
+    {
+        state::depth_write(&self.gl, true);
+        state::depth_test(&self.gl, state::DepthTestType::Always);
+        state::blend(&self.gl, state::BlendType::None);
+        self.srgb_encode_effect.program().use_texture(color_texture, "colorMap")?;
+        self.srgb_encode_effect.program().use_texture(depth_texture, "depthMap")?;
+        self.srgb_encode_effect.apply();
+        state::depth_test(&self.gl, state::DepthTestType::LessOrEqual);
+        Ok(())
     }
 
     pub fn new_material(&self, cpu_material: &CPUMaterial) -> Result<PhongMaterial, Error>
@@ -39,11 +114,7 @@ impl PhongForwardPipeline {
 
     pub fn new_mesh(&self, cpu_mesh: &CPUMesh, material: &PhongMaterial) -> Result<PhongForwardMesh, Error>
     {
-        Ok(PhongForwardMesh::new_with_programs(&self.gl,
-                  self.mesh_color_ambient_program.clone(),
-                  self.mesh_color_ambient_directional_program.clone(),
-                  self.mesh_texture_ambient_program.clone(),
-                  self.mesh_texture_ambient_directional_program.clone(), cpu_mesh, material)?)
+        Ok(PhongForwardMesh::new_with_programs(&self.gl, self.mesh_programs.clone(), cpu_mesh, material)?)
     }
 
     pub fn new_meshes(&self, cpu_meshes: &Vec<CPUMesh>, cpu_materials: &Vec<CPUMaterial>) -> Result<Vec<PhongForwardMesh>, Error>
@@ -61,57 +132,144 @@ impl PhongForwardPipeline {
     }
 }
 
+///
+/// Defines how the point and spot lights are applied in the deferred light pass.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LightPassType {
+    /// One full-screen pass per light, which is fast for a few lights.
+    PerLight,
+    /// The lights are binned into clusters, that is, screen tiles subdivided into depth slices, on the CPU,
+    /// and all lights are applied in a single full-screen pass which only evaluates the lights affecting each cluster.
+    /// This is fast for many lights with a limited influence radius. Spot lights with shadows are still applied one at a time.
+    Clustered,
+    /// Each light is applied by rendering a bounding volume, a sphere for point lights and a cone for spot lights, sized by the influence radius of the light,
+    /// so that only the pixels covered by the volume are shaded. Lights without attenuation are applied with a full-screen pass.
+    Volumes
+}
+
+///
+/// The lights applied in the light pass of the deferred pipeline, see for example [PhongDeferredPipeline::render_to_screen].
+/// All lights are optional, so only the lights in the scene have to be given, for example
+/// `Lights {ambient: Some(Ambient::Light(&ambient_light)), directional_lights: &[&directional_light], ..Default::default()}`.
+///
+#[derive(Clone, Copy, Default)]
+pub struct Lights<'a> {
+    pub ambient: Option<Ambient<'a>>,
+    pub directional_lights: &'a [&'a DirectionalLight],
+    pub spot_lights: &'a [&'a SpotLight],
+    pub point_lights: &'a [&'a PointLight],
+    pub rect_lights: &'a [&'a RectLight],
+    pub disk_lights: &'a [&'a DiskLight]
+}
+
+///
+/// The light which reaches all surfaces independently of the position of any light source, see [Lights].
+///
+#[derive(Clone, Copy)]
+pub enum Ambient<'a> {
+    /// The same color everywhere.
+    Light(&'a AmbientLight),
+    /// Image-based diffuse and specular lighting from an environment map.
+    Environment(&'a EnvironmentLight),
+    /// A color interpolated between a sky and a ground color depending on the surface normal.
+    Hemisphere(&'a HemisphereLight),
+    /// Diffuse lighting from spherical harmonics.
+    SH(&'a SHLight)
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum DebugType {POSITION, NORMAL, COLOR, DEPTH, DIFFUSE, SPECULAR, POWER, NONE}
+pub enum DebugType {POSITION, NORMAL, COLOR, DEPTH, DIFFUSE, SPECULAR, POWER, EMISSIVE, MATERIAL, MOTION, NONE}
 
 pub struct PhongDeferredPipeline {
     gl: Gl,
     forward_pipeline: PhongForwardPipeline,
     ambient_light_effect: ImageEffect,
+    environment_light_effect: ImageEffect,
+    hemisphere_light_effect: ImageEffect,
+    sh_light_effect: ImageEffect,
     directional_light_effect: ImageEffect,
     point_light_effect: ImageEffect,
     spot_light_effect: ImageEffect,
+    emissive_effect: Option<ImageEffect>,
     debug_effect: Option<ImageEffect>,
     debug_type: DebugType,
+    clustered_light_effect: Option<ImageEffect>,
+    light_clusters: Option<RefCell<LightClusters>>,
+    light_volumes: Option<LightVolumes>,
+    area_lights: AreaLights,
+    light_pass_type: LightPassType,
     geometry_pass_texture: Option<Texture2DArray>,
     geometry_pass_depth_texture: Option<Texture2DArray>,
     mesh_color_program: Rc<Program>,
     mesh_texture_program: Rc<Program>,
+    gbuffer_layout: GBufferLayout
 }
 
 impl PhongDeferredPipeline
 {
     pub fn new(gl: &Gl) -> Result<Self, Error>
     {
+        Self::new_with_gbuffer_layout(gl, &GBufferLayout::default())
+    }
+
+    ///
+    /// Creates a pipeline where the G-buffer written in the geometry pass has the given layout.
+    /// The shaders used in the geometry pass and the light pass are adapted to the layout.
+    ///
+    pub fn new_with_gbuffer_layout(gl: &Gl, gbuffer_layout: &GBufferLayout) -> Result<Self, Error>
+    {
+        gbuffer_layout.check_support(gl)?;
+        let light_effect = |segments: Vec<ShaderSegment>| ImageEffect::from_shader_source(gl,
+                                         &ShaderSource::from_segments([gbuffer_layout.shader_segments(), segments].concat()));
         let renderer = Self {
             gl: gl.clone(),
             forward_pipeline: PhongForwardPipeline::new(gl)?,
-            mesh_color_program: PhongDeferredMesh::program_color(gl)?,
-            mesh_texture_program: PhongDeferredMesh::program_textured(gl)?,
-            ambient_light_effect: ImageEffect::new(gl, &format!("{}\n{}\n{}",
-                                                                       &include_str!("shaders/light_shared.frag"),
-                                                                       &include_str!("shaders/deferred_light_shared.frag"),
-                                                                       &include_str!("shaders/ambient_light.frag")))?,
-            directional_light_effect: ImageEffect::new(gl, &format!("{}\n{}\n{}",
-                                                                       &include_str!("shaders/light_shared.frag"),
-                                                                       &include_str!("shaders/deferred_light_shared.frag"),
-                                                                       &include_str!("shaders/directional_light.frag")))?,
-            point_light_effect: ImageEffect::new(gl, &format!("{}\n{}\n{}",
-                                                                       &include_str!("shaders/light_shared.frag"),
-                                                                       &include_str!("shaders/deferred_light_shared.frag"),
-                                                                       &include_str!("shaders/point_light.frag")))?,
-            spot_light_effect: ImageEffect::new(gl, &format!("{}\n{}\n{}",
-                                                                       &include_str!("shaders/light_shared.frag"),
-                                                                       &include_str!("shaders/deferred_light_shared.frag"),
-                                                                       &include_str!("shaders/spot_light.frag")))?,
+            mesh_color_program: PhongDeferredMesh::program_color(gl, gbuffer_layout)?,
+            mesh_texture_program: PhongDeferredMesh::program_textured(gl, gbuffer_layout)?,
+            ambient_light_effect: light_effect(vec![shader_segment!("shaders/light_shared.frag"),
+                                                    shader_segment!("shaders/deferred_light_shared.frag"),
+                                                    shader_segment!("shaders/ambient_light.frag")])?,
+            hemisphere_light_effect: light_effect(vec![ShaderSegment::new("defines", "#define USE_HEMISPHERE\n"),
+                                                       shader_segment!("shaders/light_shared.frag"),
+                                                       shader_segment!("shaders/deferred_light_shared.frag"),
+                                                       shader_segment!("shaders/ambient_light.frag")])?,
+            sh_light_effect: light_effect(vec![ShaderSegment::new("defines", "#define USE_SH\n"),
+                                               shader_segment!("shaders/light_shared.frag"),
+                                               shader_segment!("shaders/deferred_light_shared.frag"),
+                                               shader_segment!("shaders/ambient_light.frag")])?,
+            environment_light_effect: light_effect(vec![shader_segment!("shaders/light_shared.frag"),
+                                                        shader_segment!("shaders/deferred_light_shared.frag"),
+                                                        shader_segment!("shaders/environment_light_shared.frag"),
+                                                        shader_segment!("shaders/environment_light.frag")])?,
+            directional_light_effect: light_effect(vec![shader_segment!("shaders/light_shared.frag"),
+                                                        shader_segment!("shaders/deferred_light_shared.frag"),
+                                                        shader_segment!("shaders/directional_light.frag")])?,
+            point_light_effect: light_effect(vec![shader_segment!("shaders/light_shared.frag"),
+                                                  shader_segment!("shaders/deferred_light_shared.frag"),
+                                                  shader_segment!("shaders/point_light.frag")])?,
+            spot_light_effect: light_effect(vec![shader_segment!("shaders/light_shared.frag"),
+                                                 shader_segment!("shaders/deferred_light_shared.frag"),
+                                                 shader_segment!("shaders/spot_light.frag")])?,
+            emissive_effect: if gbuffer_layout.emissive {
+                Some(light_effect(vec![shader_segment!("shaders/light_shared.frag"),
+                                       shader_segment!("shaders/deferred_light_shared.frag"),
+                                       shader_segment!("shaders/deferred_emissive.frag")])?)
+            } else { None },
             debug_effect: None,
             debug_type: DebugType::NONE,
-            geometry_pass_texture: Some(Texture2DArray::new(gl, 1, 1, 2,
+            clustered_light_effect: None,
+            light_clusters: None,
+            light_volumes: None,
+            area_lights: AreaLights::new(gl, gbuffer_layout)?,
+            light_pass_type: LightPassType::PerLight,
+            geometry_pass_texture: Some(Texture2DArray::new(gl, 1, 1, gbuffer_layout.layer_count(),
                   Interpolation::Nearest, Interpolation::Nearest, None, Wrapping::ClampToEdge,
-                  Wrapping::ClampToEdge, Format::RGBA8)?),
+                  Wrapping::ClampToEdge, gbuffer_layout.format())?),
             geometry_pass_depth_texture: Some(Texture2DArray::new(gl, 1, 1, 1,
                     Interpolation::Nearest, Interpolation::Nearest, None,Wrapping::ClampToEdge,
-                    Wrapping::ClampToEdge, Format::Depth32F)?)
+                    Wrapping::ClampToEdge, Format::Depth32F)?),
+            gbuffer_layout: gbuffer_layout.clone()
         };
 
         renderer.ambient_light_effect.program().use_texture(renderer.geometry_pass_texture(), "gbuffer")?;
@@ -126,30 +284,48 @@ impl PhongDeferredPipeline
         state::cull(&self.gl, state::CullType::None);
         state::blend(&self.gl, state::BlendType::None);
 
-        self.geometry_pass_texture = Some(Texture2DArray::new(&self.gl, width, height, 2,
-                  Interpolation::Nearest, Interpolation::Nearest, None, Wrapping::ClampToEdge,
-                  Wrapping::ClampToEdge, Format::RGBA8)?);
-        self.geometry_pass_depth_texture = Some(Texture2DArray::new(&self.gl, width, height, 1,
-                    Interpolation::Nearest, Interpolation::Nearest, None, Wrapping::ClampToEdge,
-                    Wrapping::ClampToEdge, Format::Depth32F)?);
+        // The G-buffer is reused when the size is unchanged
+        if self.geometry_pass_texture.as_ref().map(|texture| texture.width != width || texture.height != height).unwrap_or(true) {
+            self.geometry_pass_texture = Some(Texture2DArray::new(&self.gl, width, height, self.gbuffer_layout.layer_count(),
+                      Interpolation::Nearest, Interpolation::Nearest, None, Wrapping::ClampToEdge,
+                      Wrapping::ClampToEdge, self.gbuffer_layout.format())?);
+            self.geometry_pass_depth_texture = Some(Texture2DArray::new(&self.gl, width, height, 1,
+                        Interpolation::Nearest, Interpolation::Nearest, None, Wrapping::ClampToEdge,
+                        Wrapping::ClampToEdge, Format::Depth32F)?);
+        }
         RenderTarget::write_array(&self.gl,0, 0, width, height,
             Some(&vec4(0.0, 0.0, 0.0, 0.0)), Some(1.0),
             self.geometry_pass_texture.as_ref(), self.geometry_pass_depth_texture.as_ref(),
-            2, &|channel| {channel},
+            self.gbuffer_layout.layer_count(), &|channel| {channel},
             0, render_scene)?;
         Ok(())
     }
 
-    pub fn light_pass(&self, camera: &Camera, ambient_light: Option<&AmbientLight>, directional_lights: &[&DirectionalLight],
-                      spot_lights: &[&SpotLight], point_lights: &[&PointLight]) -> Result<(), Error>
+    ///
+    /// Same as [geometry_pass](Self::geometry_pass), except that the scene is rendered once for each of the given cameras into one G-buffer of the given width and height,
+    /// in the viewport of the camera (see [Camera::set_viewport]) or in the whole G-buffer if the camera has no viewport.
+    /// The light pass should be applied with the same cameras, see [render_cameras_to_screen](Self::render_cameras_to_screen).
+    ///
+    pub fn geometry_pass_with_cameras<F: FnMut(&Camera) -> Result<(), Error>>(&mut self, width: usize, height: usize, cameras: &[&Camera], render_scene: F) -> Result<(), Error>
+    {
+        let gl = self.gl.clone();
+        self.geometry_pass(width, height, || render_viewports(&gl, width, height, cameras, render_scene))
+    }
+
+    ///
+    /// Applies the lights to the geometry written in the geometry pass. Rectangular and disk lights are applied last,
+    /// after which the shapes of these lights are rendered with the color of the light, so that the lights themselves are visible.
+    ///
+    pub fn light_pass(&self, camera: &Camera, lights: &Lights) -> Result<(), Error>
     {
+        let Lights {ambient, directional_lights, spot_lights, point_lights, rect_lights, disk_lights} = *lights;
         state::depth_write(&self.gl,true);
         state::depth_test(&self.gl, state::DepthTestType::LessOrEqual);
         state::cull(&self.gl, state::CullType::Back);
         state::blend(&self.gl, state::BlendType::None);
 
         if self.debug_type != DebugType::NONE {
-            self.debug_effect.as_ref().unwrap().program().add_uniform_mat4("viewProjectionInverse", &(camera.get_projection() * camera.get_view()).invert().unwrap())?;
+            self.debug_effect.as_ref().unwrap().program().add_uniform_mat4("viewProjectionInverse", &camera.view_projection_inverse())?;
             self.debug_effect.as_ref().unwrap().program().use_texture(self.geometry_pass_texture(), "gbuffer")?;
             self.debug_effect.as_ref().unwrap().program().use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
             self.debug_effect.as_ref().unwrap().program().add_uniform_int("type", &(self.debug_type as i32))?;
@@ -157,13 +333,48 @@ impl PhongDeferredPipeline
             return Ok(());
         }
 
+        // Emissive
+        if let Some(ref effect) = self.emissive_effect {
+            effect.program().use_texture(self.geometry_pass_texture(), "gbuffer")?;
+            effect.program().use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
+            effect.program().add_uniform_mat4("viewProjectionInverse", &camera.view_projection_inverse())?;
+            effect.apply();
+            state::blend(&self.gl, state::BlendType::OneOne);
+        }
+
         // Ambient light
-        if let Some(light) = ambient_light {
-            self.ambient_light_effect.program().use_texture(self.geometry_pass_texture(), "gbuffer")?;
-            self.ambient_light_effect.program().use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
-            self.ambient_light_effect.program().add_uniform_vec3("ambientLight.color", &light.color())?;
-            self.ambient_light_effect.program().add_uniform_float("ambientLight.intensity", &light.intensity())?;
-            self.ambient_light_effect.apply();
+        if let Some(ambient) = ambient {
+            match ambient {
+                Ambient::Light(light) => {
+                    self.ambient_light_effect.program().use_texture(self.geometry_pass_texture(), "gbuffer")?;
+                    self.ambient_light_effect.program().use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
+                    self.ambient_light_effect.program().add_uniform_vec3("ambientLight.color", &light.color())?;
+                    self.ambient_light_effect.program().add_uniform_float("ambientLight.intensity", &light.intensity())?;
+                    self.ambient_light_effect.apply();
+                },
+                Ambient::Environment(light) => {
+                    self.environment_light_effect.program().use_texture(self.geometry_pass_texture(), "gbuffer")?;
+                    self.environment_light_effect.program().use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
+                    self.environment_light_effect.program().add_uniform_vec3("eyePosition", &camera.position())?;
+                    self.environment_light_effect.program().add_uniform_mat4("viewProjectionInverse", &camera.view_projection_inverse())?;
+                    light.use_uniforms(self.environment_light_effect.program())?;
+                    self.environment_light_effect.apply();
+                },
+                Ambient::Hemisphere(light) => {
+                    self.hemisphere_light_effect.program().use_texture(self.geometry_pass_texture(), "gbuffer")?;
+                    self.hemisphere_light_effect.program().use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
+                    self.hemisphere_light_effect.program().add_uniform_mat4("viewProjectionInverse", &camera.view_projection_inverse())?;
+                    light.use_uniforms(self.hemisphere_light_effect.program())?;
+                    self.hemisphere_light_effect.apply();
+                },
+                Ambient::SH(light) => {
+                    self.sh_light_effect.program().use_texture(self.geometry_pass_texture(), "gbuffer")?;
+                    self.sh_light_effect.program().use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
+                    self.sh_light_effect.program().add_uniform_mat4("viewProjectionInverse", &camera.view_projection_inverse())?;
+                    light.use_uniforms(self.sh_light_effect.program())?;
+                    self.sh_light_effect.apply();
+                }
+            }
             state::blend(&self.gl, state::BlendType::OneOne);
         }
 
@@ -172,19 +383,66 @@ impl PhongDeferredPipeline
             self.directional_light_effect.program().use_texture(self.geometry_pass_texture(), "gbuffer")?;
             self.directional_light_effect.program().use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
             self.directional_light_effect.program().add_uniform_vec3("eyePosition", &camera.position())?;
-            self.directional_light_effect.program().add_uniform_mat4("viewProjectionInverse", &(camera.get_projection() * camera.get_view()).invert().unwrap())?;
+            self.directional_light_effect.program().add_uniform_mat4("viewProjectionInverse", &camera.view_projection_inverse())?;
             self.directional_light_effect.program().use_texture(light.shadow_map(), "shadowMap")?;
             self.directional_light_effect.program().use_uniform_block(light.buffer(), "DirectionalLightUniform");
             self.directional_light_effect.apply();
             state::blend(&self.gl, state::BlendType::OneOne);
         }
 
+        let (spot_lights, point_lights) = match self.light_pass_type {
+            LightPassType::PerLight => (spot_lights.to_vec(), point_lights.to_vec()),
+            LightPassType::Clustered => {
+                let clustered_spot_lights = spot_lights.iter().filter(|light| !light.has_shadows()).copied().collect::<Vec<&SpotLight>>();
+                if !clustered_spot_lights.is_empty() || !point_lights.is_empty() {
+                    let effect = self.clustered_light_effect.as_ref().unwrap();
+                    let mut clusters = self.light_clusters.as_ref().unwrap().borrow_mut();
+                    clusters.update(camera, &clustered_spot_lights, point_lights)?;
+                    effect.program().use_texture(self.geometry_pass_texture(), "gbuffer")?;
+                    effect.program().use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
+                    effect.program().add_uniform_vec3("eyePosition", camera.position())?;
+                    effect.program().add_uniform_mat4("viewProjectionInverse", &camera.view_projection_inverse())?;
+                    clusters.use_uniforms(effect.program(), camera)?;
+                    effect.apply();
+                    state::blend(&self.gl, state::BlendType::OneOne);
+                }
+                (spot_lights.iter().filter(|light| light.has_shadows()).copied().collect(), Vec::new())
+            },
+            LightPassType::Volumes => {
+                let (spot_volumes, spot_lights): (Vec<&SpotLight>, Vec<&SpotLight>) = spot_lights.iter().partition(|light| LightVolumes::has_volume(light.influence_radius()));
+                let (point_volumes, point_lights): (Vec<&PointLight>, Vec<&PointLight>) = point_lights.iter().partition(|light| LightVolumes::has_volume(light.influence_radius()));
+                if !spot_volumes.is_empty() || !point_volumes.is_empty() {
+                    if self.emissive_effect.is_none() && ambient.is_none() && directional_lights.is_empty() {
+                        // The light volumes do not cover the whole screen and therefore do not write the depth, so the depth is written with a black ambient light first
+                        self.ambient_light_effect.program().use_texture(self.geometry_pass_texture(), "gbuffer")?;
+                        self.ambient_light_effect.program().use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
+                        self.ambient_light_effect.program().add_uniform_vec3("ambientLight.color", &vec3(0.0, 0.0, 0.0))?;
+                        self.ambient_light_effect.program().add_uniform_float("ambientLight.intensity", &0.0)?;
+                        self.ambient_light_effect.apply();
+                    }
+                    let volumes = self.light_volumes.as_ref().unwrap();
+                    state::depth_test(&self.gl, state::DepthTestType::None);
+                    state::cull(&self.gl, state::CullType::Front);
+                    state::blend(&self.gl, state::BlendType::OneOne);
+                    for light in spot_volumes {
+                        volumes.render_spot_light(camera, self.geometry_pass_texture(), self.geometry_pass_depth_texture(), light)?;
+                    }
+                    for light in point_volumes {
+                        volumes.render_point_light(camera, self.geometry_pass_texture(), self.geometry_pass_depth_texture(), light)?;
+                    }
+                    state::depth_test(&self.gl, state::DepthTestType::LessOrEqual);
+                    state::cull(&self.gl, state::CullType::Back);
+                }
+                (spot_lights, point_lights)
+            }
+        };
+
         // Spot lights
         for light in spot_lights {
             self.spot_light_effect.program().use_texture(self.geometry_pass_texture(), "gbuffer")?;
             self.spot_light_effect.program().use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
             self.spot_light_effect.program().add_uniform_vec3("eyePosition", &camera.position())?;
-            self.spot_light_effect.program().add_uniform_mat4("viewProjectionInverse", &(camera.get_projection() * camera.get_view()).invert().unwrap())?;
+            self.spot_light_effect.program().add_uniform_mat4("viewProjectionInverse", &camera.view_projection_inverse())?;
             self.spot_light_effect.program().use_texture(light.shadow_map(), "shadowMap")?;
             self.spot_light_effect.program().use_uniform_block(light.buffer(), "SpotLightUniform");
             self.spot_light_effect.apply();
@@ -196,34 +454,92 @@ impl PhongDeferredPipeline
             self.point_light_effect.program().use_texture(self.geometry_pass_texture(), "gbuffer")?;
             self.point_light_effect.program().use_texture(self.geometry_pass_depth_texture(), "depthMap")?;
             self.point_light_effect.program().add_uniform_vec3("eyePosition", &camera.position())?;
-            self.point_light_effect.program().add_uniform_mat4("viewProjectionInverse", &(camera.get_projection() * camera.get_view()).invert().unwrap())?;
+            self.point_light_effect.program().add_uniform_mat4("viewProjectionInverse", &camera.view_projection_inverse())?;
             self.point_light_effect.program().use_uniform_block(light.buffer(), "PointLightUniform");
             self.point_light_effect.apply();
             state::blend(&self.gl, state::BlendType::OneOne);
         }
+
+        // Area lights
+        for light in rect_lights {
+            self.area_lights.render_rect_light(camera, self.geometry_pass_texture(), self.geometry_pass_depth_texture(), light)?;
+        }
+        for light in disk_lights {
+            self.area_lights.render_disk_light(camera, self.geometry_pass_texture(), self.geometry_pass_depth_texture(), light)?;
+        }
         state::blend(&self.gl, state::BlendType::None);
+        self.area_lights.render_emissive_shapes(camera, rect_lights, disk_lights)?;
 
         Ok(())
     }
 
-    pub fn render_to_screen(&self, camera: &Camera, ambient_light: Option<&AmbientLight>, directional_lights: &[&DirectionalLight],
-                       spot_lights: &[&SpotLight], point_lights: &[&PointLight], width: usize, height: usize) -> Result<(), Error>
+    pub fn render_to_screen(&self, camera: &Camera, lights: &Lights, width: usize, height: usize) -> Result<(), Error>
     {
-        Ok(self.render_to_screen_with_forward_pass(camera, ambient_light, directional_lights, spot_lights, point_lights, width, height, || {Ok(())})?)
+        Ok(self.render_to_screen_with_forward_pass(camera, lights, width, height, || {Ok(())})?)
     }
 
     pub fn render_to_screen_with_forward_pass<F: FnOnce() -> Result<(), Error>>(&self, camera: &Camera,
-                       ambient_light: Option<&AmbientLight>, directional_lights: &[&DirectionalLight],
-                       spot_lights: &[&SpotLight], point_lights: &[&PointLight], width: usize, height: usize,
+                       lights: &Lights, width: usize, height: usize,
                        forward_pass: F) -> Result<(), Error>
     {
         Ok(self.forward_pipeline.render_to_screen(width, height, || {
-            self.light_pass(camera, ambient_light, directional_lights, spot_lights, point_lights)?;
+            self.light_pass(camera, lights)?;
             forward_pass()?;
             Ok(())
         })?)
     }
 
+    ///
+    /// Same as [render_to_screen](Self::render_to_screen), except that the result is written to the given color and depth textures
+    /// within the viewport rectangle starting at the pixel (x, y) with the given width and height, see [PhongForwardPipeline::render_to_target].
+    /// The geometry pass must have been called with the same width and height.
+    ///
+    pub fn render_to_target(&self, camera: &Camera, lights: &Lights,
+                       color_texture: Option<&Texture2D>, depth_texture: Option<&Texture2D>, x: i32, y: i32, width: usize, height: usize) -> Result<(), Error>
+    {
+        Ok(self.render_to_target_with_forward_pass(camera, lights,
+                                                   color_texture, depth_texture, x, y, width, height, || {Ok(())})?)
+    }
+
+    ///
+    /// Same as [render_to_screen_with_forward_pass](Self::render_to_screen_with_forward_pass), except that the result is written to the given color and depth textures
+    /// within the viewport rectangle starting at the pixel (x, y) with the given width and height, see [PhongForwardPipeline::render_to_target].
+    ///
+    pub fn render_to_target_with_forward_pass<F: FnOnce() -> Result<(), Error>>(&self, camera: &Camera,
+                       lights: &Lights,
+                       color_texture: Option<&Texture2D>, depth_texture: Option<&Texture2D>, x: i32, y: i32, width: usize, height: usize,
+                       forward_pass: F) -> Result<(), Error>
+    {
+        Ok(self.forward_pipeline.render_to_target(color_texture, depth_texture, x, y, width, height, || {
+            self.light_pass(camera, lights)?;
+            forward_pass()?;
+            Ok(())
+        })?)
+    }
+
+    ///
+    /// Applies the light pass for each of the given cameras in the viewport of the camera, after the geometry is written with [geometry_pass_with_cameras](Self::geometry_pass_with_cameras),
+    /// and writes the result to the screen.
+    ///
+    pub fn render_cameras_to_screen(&self, cameras: &[&Camera], lights: &Lights, width: usize, height: usize) -> Result<(), Error>
+    {
+        Ok(self.render_cameras_to_screen_with_forward_pass(cameras, lights, width, height, |_| {Ok(())})?)
+    }
+
+    ///
+    /// Same as [render_cameras_to_screen](Self::render_cameras_to_screen), followed by the forward pass for each camera in the viewport of the camera.
+    ///
+    pub fn render_cameras_to_screen_with_forward_pass<F: FnMut(&Camera) -> Result<(), Error>>(&self, cameras: &[&Camera],
+                       lights: &Lights, width: usize, height: usize,
+                       mut forward_pass: F) -> Result<(), Error>
+    {
+        Ok(self.forward_pipeline.render_cameras_to_screen(width, height, cameras, |camera| {
+            self.light_pass(camera, lights)?;
+            forward_pass(camera)?;
+            Ok(())
+        })?)
+    }
+
     pub fn geometry_pass_texture(&self) -> &Texture2DArray
     {
         &self.geometry_pass_texture.as_ref().unwrap()
@@ -233,6 +549,27 @@ impl PhongDeferredPipeline
         &self.geometry_pass_depth_texture.as_ref().unwrap()
     }
 
+    pub fn light_pass_type(&self) -> LightPassType
+    {
+        self.light_pass_type
+    }
+
+    pub fn set_light_pass_type(&mut self, light_pass_type: LightPassType)
+    {
+        self.light_pass_type = light_pass_type;
+        if light_pass_type == LightPassType::Volumes && self.light_volumes.is_none() {
+            self.light_volumes = Some(LightVolumes::new(&self.gl, &self.gbuffer_layout).unwrap());
+        }
+        if light_pass_type == LightPassType::Clustered && self.clustered_light_effect.is_none() {
+            self.clustered_light_effect = Some(ImageEffect::from_shader_source(&self.gl, &ShaderSource::from_segments([self.gbuffer_layout.shader_segments(), vec![
+                                                                       LightClusters::shader_defines(),
+                                                                       shader_segment!("shaders/light_shared.frag"),
+                                                                       shader_segment!("shaders/deferred_light_shared.frag"),
+                                                                       shader_segment!("shaders/clustered_light.frag")]].concat())).unwrap());
+            self.light_clusters = Some(RefCell::new(LightClusters::new(&self.gl).unwrap()));
+        }
+    }
+
     pub fn debug_type(&self) -> DebugType
     {
         self.debug_type
@@ -242,23 +579,42 @@ impl PhongDeferredPipeline
     {
         self.debug_type = debug_type;
         if self.debug_effect.is_none() {
-            self.debug_effect = Some(ImageEffect::new(&self.gl, include_str!("shaders/debug.frag")).unwrap());
+            self.debug_effect = Some(ImageEffect::from_shader_source(&self.gl, &ShaderSource::from_segments([self.gbuffer_layout.shader_segments(),
+                                                                       vec![shader_segment!("shaders/debug.frag")]].concat())).unwrap());
         }
     }
 
+    ///
+    /// Changes to the next debug type, skipping the types showing layers which are not in the G-buffer layout.
+    ///
     pub fn next_debug_type(&mut self)
     {
-        let debug_type =
-            match self.debug_type {
-                DebugType::NONE => DebugType::POSITION,
-                DebugType::POSITION => DebugType::NORMAL,
-                DebugType::NORMAL => DebugType::COLOR,
-                DebugType::COLOR => DebugType::DEPTH,
-                DebugType::DEPTH => DebugType::DIFFUSE,
-                DebugType::DIFFUSE => DebugType::SPECULAR,
-                DebugType::SPECULAR => DebugType::POWER,
-                DebugType::POWER => DebugType::NONE,
+        let mut debug_type = self.debug_type;
+        loop {
+            debug_type =
+                match debug_type {
+                    DebugType::NONE => DebugType::POSITION,
+                    DebugType::POSITION => DebugType::NORMAL,
+                    DebugType::NORMAL => DebugType::COLOR,
+                    DebugType::COLOR => DebugType::DEPTH,
+                    DebugType::DEPTH => DebugType::DIFFUSE,
+                    DebugType::DIFFUSE => DebugType::SPECULAR,
+                    DebugType::SPECULAR => DebugType::POWER,
+                    DebugType::POWER => DebugType::EMISSIVE,
+                    DebugType::EMISSIVE => DebugType::MATERIAL,
+                    DebugType::MATERIAL => DebugType::MOTION,
+                    DebugType::MOTION => DebugType::NONE,
+                };
+            let available = match debug_type {
+                DebugType::EMISSIVE => self.gbuffer_layout.emissive,
+                DebugType::MATERIAL => self.gbuffer_layout.material,
+                DebugType::MOTION => self.gbuffer_layout.motion_vectors,
+                _ => true
             };
+            if available {
+                break;
+            }
+        }
         self.set_debug_type(debug_type);
     }
 
@@ -290,16 +646,43 @@ impl PhongDeferredPipeline
 
     pub fn new_sphere_instances(&self, centers: &[f32], sphere_radius: f32, material: &PhongMaterial) -> Result<SphereInstances, Error>
     {
-        SphereInstances::new(&self.gl, centers, sphere_radius, material)
+        SphereInstances::new(&self.gl, &self.gbuffer_layout, centers, sphere_radius, material)
     }
 
     pub fn new_cylinder_instances(&self, indices: &[u32], end_points: &[f32], cylinder_radius: f32, material: &PhongMaterial) -> Result<CylinderInstances, Error>
     {
-        CylinderInstances::new(&self.gl, indices, end_points, cylinder_radius, material)
+        CylinderInstances::new(&self.gl, &self.gbuffer_layout, indices, end_points, cylinder_radius, material)
+    }
+
+    ///
+    /// Creates an imposter which is rendered into a G-buffer with the layout of this pipeline, see [Imposter::new_with_gbuffer_layout].
+    ///
+    pub fn new_imposter<F: Fn(&Camera) -> Result<(), Error>>(&self, render: F, aabb: (Vec3, Vec3), max_texture_size: usize) -> Result<Imposter, Error>
+    {
+        Imposter::new_with_gbuffer_layout(&self.gl, &self.gbuffer_layout, render, aabb, max_texture_size)
+    }
+
+    pub fn gbuffer_layout(&self) -> &GBufferLayout
+    {
+        &self.gbuffer_layout
     }
 
     pub fn forward_pipeline(&self) -> &PhongForwardPipeline
     {
         &self.forward_pipeline
     }
-}
\ No newline at end of file
+}
+
+// Renders each camera in its viewport, or in the whole frame if it has no viewport, with the scissor test restricting the rendering to the viewport
+fn render_viewports<F: FnMut(&Camera) -> Result<(), Error>>(gl: &Gl, width: usize, height: usize, cameras: &[&Camera], mut render: F) -> Result<(), Error>
+{
+    let result = cameras.iter().try_for_each(|camera| {
+        let viewport = camera.viewport().unwrap_or(Viewport::new_at_origo(width, height));
+        gl.viewport(viewport.x, viewport.y, viewport.width, viewport.height);
+        state::scissor(gl, Some(&viewport));
+        render(camera)
+    });
+    state::scissor(gl, None);
+    gl.viewport(0, 0, width, height);
+    result
+}
@@ -1,9 +1,21 @@
 
 layout (location = 0) out vec4 color;
 
+#if defined(USE_HEMISPHERE)
+uniform HemisphereLight hemisphereLight;
+#elif defined(USE_SH)
+uniform SHLight shLight;
+#else
 uniform BaseLight ambientLight;
+#endif
 
 void main()
 {
+#if defined(USE_HEMISPHERE)
+    color = vec4(calculate_hemisphere_light(hemisphereLight, get_surface()), 1.0);
+#elif defined(USE_SH)
+    color = vec4(calculate_sh_light(shLight, get_surface()), 1.0);
+#else
     color = vec4(calculate_ambient_light(ambientLight, get_surface()), 1.0);
+#endif
 }
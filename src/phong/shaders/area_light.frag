@@ -0,0 +1,92 @@
+uniform sampler2D ltcMatrix;
+uniform sampler2D ltcMagnitude;
+uniform vec3 areaLightVertices[MAX_AREA_LIGHT_VERTICES];
+uniform int areaLightVertexCount;
+uniform vec3 areaLightColor;
+uniform float areaLightIntensity;
+uniform vec3 areaLightNormal;
+
+layout (location = 0) out vec4 color;
+
+const float PI = 3.14159265359;
+
+// The integral of the clamped cosine over the edge, see Heitz et al. 2016, "Real-Time Polygonal-Light Shading with Linearly Transformed Cosines"
+float integrate_edge(vec3 v1, vec3 v2)
+{
+    float x = dot(v1, v2);
+    float y = abs(x);
+    float a = 0.8543985 + (0.4965155 + 0.0145206 * y) * y;
+    float b = 3.4175940 + (4.1616724 + y) * y;
+    float v = a / b;
+    float theta_sintheta = x > 0.0 ? v : 0.5 * inversesqrt(max(1.0 - x * x, 1e-7)) - v;
+    return cross(v1, v2).z * theta_sintheta;
+}
+
+// The integral of the clamped cosine distribution over the light polygon, where the polygon is transformed into the space of the distribution
+// and clipped to the upper hemisphere
+float integrate_polygon(mat3 transform, vec3 position)
+{
+    vec3 clipped[MAX_AREA_LIGHT_VERTICES + 1];
+    int count = 0;
+    vec3 previous = transform * (areaLightVertices[areaLightVertexCount - 1] - position);
+    for(int i = 0; i < areaLightVertexCount; i++)
+    {
+        vec3 current = transform * (areaLightVertices[i] - position);
+        if((current.z > 0.0) != (previous.z > 0.0))
+        {
+            clipped[count] = mix(previous, current, previous.z / (previous.z - current.z));
+            count++;
+        }
+        if(current.z > 0.0)
+        {
+            clipped[count] = current;
+            count++;
+        }
+        previous = current;
+    }
+    if(count < 3)
+    {
+        return 0.0;
+    }
+
+    float sum = 0.0;
+    vec3 first = normalize(clipped[0]);
+    vec3 a = first;
+    for(int i = 1; i < count; i++)
+    {
+        vec3 b = normalize(clipped[i]);
+        sum += integrate_edge(a, b);
+        a = b;
+    }
+    sum += integrate_edge(a, first);
+    return abs(sum) / (2.0 * PI);
+}
+
+void main()
+{
+    Surface surface = get_surface();
+    // The light is only emitted from the front side
+    if(dot(surface.position - areaLightVertices[0], areaLightNormal) <= 0.0)
+    {
+        color = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    vec3 normal = surface.normal;
+    vec3 view_direction = normalize(eyePosition - surface.position);
+    float cos_theta = clamp(dot(normal, view_direction), 0.0, 1.0);
+    vec3 tangent = view_direction - normal * dot(view_direction, normal);
+    tangent = length(tangent) > 1e-4 ? normalize(tangent) : normalize(cross(normal, abs(normal.x) < 0.9 ? vec3(1.0, 0.0, 0.0) : vec3(0.0, 1.0, 0.0)));
+    mat3 world_to_tangent = transpose(mat3(tangent, cross(normal, tangent), normal));
+
+    float roughness = sqrt(2.0 / (surface.specular_power + 2.0));
+    vec2 lut_uv = vec2(roughness, sqrt(1.0 - cos_theta)) * (LTC_LUT_SIZE - 1.0) / LTC_LUT_SIZE + 0.5 / LTC_LUT_SIZE;
+    vec4 t = texture(ltcMatrix, lut_uv);
+    mat3 inverse_matrix = mat3(vec3(t.x, 0.0, t.w), vec3(0.0, t.z, 0.0), vec3(t.y, 0.0, 1.0));
+
+    float diffuse = integrate_polygon(world_to_tangent, surface.position);
+    float specular = integrate_polygon(inverse_matrix * world_to_tangent, surface.position) * texture(ltcMagnitude, lut_uv).r;
+
+    vec3 light = areaLightColor * areaLightIntensity;
+    color = vec4(surface.color * light * (surface.diffuse_intensity * diffuse + surface.specular_intensity * specular), 1.0);
+}
@@ -0,0 +1,50 @@
+uniform sampler2D lightData;
+uniform sampler2D clusterData;
+uniform sampler2D lightIndices;
+uniform mat4 view;
+uniform float zNear;
+uniform float zFar;
+uniform int logarithmicSlicing;
+
+layout (location = 0) out vec4 color;
+
+int depth_slice(float depth)
+{
+    float s = logarithmicSlicing == 1 ? log(max(depth, zNear) / zNear) / log(zFar / zNear) : (depth - zNear) / (zFar - zNear);
+    return clamp(int(s * float(CLUSTER_COUNT_Z)), 0, CLUSTER_COUNT_Z - 1);
+}
+
+void main()
+{
+    Surface surface = get_surface();
+    float depth = -(view * vec4(surface.position, 1.0)).z;
+    ivec2 tile = clamp(ivec2(uv * vec2(CLUSTER_COUNT_X, CLUSTER_COUNT_Y)), ivec2(0), ivec2(CLUSTER_COUNT_X - 1, CLUSTER_COUNT_Y - 1));
+    vec4 cluster = texelFetch(clusterData, ivec2(tile.x + tile.y * CLUSTER_COUNT_X, depth_slice(depth)), 0);
+    int offset = int(cluster.x);
+    int count = int(cluster.y);
+
+    vec3 result = vec3(0.0);
+    for(int i = 0; i < count; i++)
+    {
+        int index = offset + i;
+        int light = int(texelFetch(lightIndices, ivec2(index % INDEX_TEXTURE_WIDTH, index / INDEX_TEXTURE_WIDTH), 0).r);
+        vec4 position_type = texelFetch(lightData, ivec2(0, light), 0);
+        vec4 color_intensity = texelFetch(lightData, ivec2(1, light), 0);
+        vec4 attenuation_radius = texelFetch(lightData, ivec2(2, light), 0);
+        if(distance(position_type.xyz, surface.position) > attenuation_radius.w)
+        {
+            continue;
+        }
+        BaseLight base = BaseLight(color_intensity.rgb, color_intensity.a);
+        Attenuation attenuation = Attenuation(attenuation_radius.x, attenuation_radius.y, attenuation_radius.z, 0.0);
+        if(position_type.w < 0.5)
+        {
+            result += calculate_point_light(PointLight(base, attenuation, position_type.xyz, 0.0), surface);
+        }
+        else {
+            vec4 direction_cutoff = texelFetch(lightData, ivec2(3, light), 0);
+            result += calculate_spot_light(SpotLight(base, attenuation, position_type.xyz, direction_cutoff.w, direction_cutoff.xyz, 0.0, mat4(0.0)), surface);
+        }
+    }
+    color = vec4(result, 1.0);
+}
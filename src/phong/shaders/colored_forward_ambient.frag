@@ -4,7 +4,13 @@ uniform float diffuse_intensity;
 uniform float specular_intensity;
 uniform float specular_power;
 
+#if defined(USE_HEMISPHERE)
+uniform HemisphereLight hemisphereLight;
+#elif defined(USE_SH)
+uniform SHLight shLight;
+#elif !defined(USE_ENVIRONMENT)
 uniform BaseLight ambientLight;
+#endif
 
 in vec3 nor;
 in vec3 pos;
@@ -16,5 +22,17 @@ void main()
 {
 	vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
 	Surface surface = Surface(pos, normal, color.rgb, diffuse_intensity, specular_intensity, specular_power);
-    out_color = vec4(calculate_ambient_light(ambientLight, surface), color.a);
+#if defined(USE_ENVIRONMENT)
+    vec3 ambient = calculate_environment_light(surface);
+#elif defined(USE_HEMISPHERE)
+    vec3 ambient = calculate_hemisphere_light(hemisphereLight, surface);
+#elif defined(USE_SH)
+    vec3 ambient = calculate_sh_light(shLight, surface);
+#else
+    vec3 ambient = calculate_ambient_light(ambientLight, surface);
+#endif
+#ifdef USE_LIGHTS
+    ambient += calculate_lights(surface);
+#endif
+    out_color = vec4(ambient, color.a);
 }
\ No newline at end of file
@@ -4,7 +4,13 @@ uniform float diffuse_intensity;
 uniform float specular_intensity;
 uniform float specular_power;
 
+#if defined(USE_HEMISPHERE)
+uniform HemisphereLight hemisphereLight;
+#elif defined(USE_SH)
+uniform SHLight shLight;
+#elif !defined(USE_ENVIRONMENT)
 uniform BaseLight ambientLight;
+#endif
 
 layout (std140) uniform DirectionalLightUniform
 {
@@ -21,5 +27,14 @@ void main()
 {
 	vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
 	Surface surface = Surface(pos, normal, color.rgb, diffuse_intensity, specular_intensity, specular_power);
-    out_color = vec4(calculate_ambient_light(ambientLight, surface) + calculate_directional_light(light, surface), color.a);
+#if defined(USE_ENVIRONMENT)
+    vec3 ambient = calculate_environment_light(surface);
+#elif defined(USE_HEMISPHERE)
+    vec3 ambient = calculate_hemisphere_light(hemisphereLight, surface);
+#elif defined(USE_SH)
+    vec3 ambient = calculate_sh_light(shLight, surface);
+#else
+    vec3 ambient = calculate_ambient_light(ambientLight, surface);
+#endif
+    out_color = vec4(ambient + calculate_directional_light(light, surface), color.a);
 }
\ No newline at end of file
@@ -19,6 +19,12 @@ in vec3 position;
 out vec3 pos;
 out vec3 nor;
 
+#ifdef GBUFFER_MOTION_LAYER
+uniform mat4 previousViewProjection;
+out vec4 currentClipPosition;
+out vec4 previousClipPosition;
+#endif
+
 mat3 rotationMatrix(vec3 source_dir, vec3 target_dir)
 {
     vec3 axis = normalize(cross(source_dir, target_dir));
@@ -38,4 +44,8 @@ void main()
     mat3 normalMatrix = transpose(inverse(l2w));
     nor = normalize(normalMatrix * vec3(0.0, position.y, position.z));
     gl_Position = camera.viewProjection * modelMatrix * vec4(pos, 1.0);
+#ifdef GBUFFER_MOTION_LAYER
+    currentClipPosition = gl_Position;
+    previousClipPosition = previousViewProjection * modelMatrix * vec4(pos, 1.0);
+#endif
 }
\ No newline at end of file
@@ -13,6 +13,17 @@ layout (location = 0) out vec4 color;
 uniform float zNear;
 uniform float zFar;
 
+// The G-buffer is read at the position of the fragment, see deferred_light_shared.frag
+vec4 read_gbuffer(int layer)
+{
+    return texelFetch(gbuffer, ivec3(gl_FragCoord.xy, layer), 0);
+}
+
+float read_depth()
+{
+    return texelFetch(depthMap, ivec3(gl_FragCoord.xy, 0), 0).x;
+}
+
 float linear_depth(float z)
 {
     float n = 0.1; // camera z near
@@ -26,48 +37,70 @@ vec3 WorldPosFromDepth(float depth, vec2 uv) {
     return position.xyz / position.w;
 }
 
+// The output is encoded to sRGB before it is written to the screen, so values are decoded here to show them unchanged
+vec3 linear_from_srgb(vec3 rgb)
+{
+    vec3 a = vec3(0.055);
+    return mix(rgb / 12.92, pow((rgb + a) / (1.0 + a), vec3(2.4)), step(vec3(0.04045), rgb));
+}
+
 void main()
 {
     if(type == 0) // Position
     {
-        float depth = texture(depthMap, vec3(uv, 0)).x;
+        float depth = read_depth();
         vec3 pos = WorldPosFromDepth(depth, uv);
         color = vec4(pos, 1.);
     }
     else if(type == 1) // Normal
     {
-        color = vec4(texture(gbuffer, vec3(uv, 1)).xyz, 1.);
+        color = vec4(0.5 * decode_normal(read_gbuffer(1)) + 0.5, 1.);
     }
     else if(type == 2) // Color
     {
-        color = vec4(texture(gbuffer, vec3(uv, 0)).xyz, 1.);
+        color = vec4(decode_color(read_gbuffer(0)), 1.);
+        return;
     }
     else if(type == 3) // Depth
     {
-        float depth = linear_depth(texture(depthMap, vec3(uv, 0)).x);
+        float depth = linear_depth(read_depth());
         color = vec4(depth, depth, depth, 1.);
     }
     else if(type == 4) // Diffuse
     {
-        float val = texture(gbuffer, vec3(uv, 0)).w;
+        float val = decode_diffuse_intensity(read_gbuffer(0));
         color = vec4(val, val, val, 1.);
     }
     else if(type == 5) // Specular
     {
-        float nw = texture(gbuffer, vec3(uv, 1)).w;
-        int t = int(floor(nw*255.0));
-        float val = float(t & 15) / 15.0;
+        float val = decode_specular_intensity(read_gbuffer(1));
         color = vec4(val, val, val, 1.);
     }
     else if(type == 6) // Specular power
     {
-        float nw = texture(gbuffer, vec3(uv, 1)).w;
-        int t = int(floor(nw*255.0));
-        float val = 2.0 * float((t & 240) >> 4);
-        val /= 30.0;
+        float val = decode_specular_power(read_gbuffer(1)) / MAX_SPECULAR_POWER;
         color = vec4(val, val, val, 1.);
     }
+#ifdef GBUFFER_EMISSIVE_LAYER
+    else if(type == 7) // Emissive
+    {
+        color = vec4(decode_emissive(read_gbuffer(GBUFFER_EMISSIVE_LAYER)), 1.);
+    }
+#endif
+#ifdef GBUFFER_MATERIAL_LAYER
+    else if(type == 8) // Metalness, roughness and material id
+    {
+        color = vec4(read_gbuffer(GBUFFER_MATERIAL_LAYER).xyz, 1.);
+    }
+#endif
+#ifdef GBUFFER_MOTION_LAYER
+    else if(type == 9) // Motion
+    {
+        color = vec4(0.5 * decode_motion(read_gbuffer(GBUFFER_MOTION_LAYER)) + 0.5, 0., 1.);
+    }
+#endif
     else {
         color = vec4(0., 0., 0., 0.);
     }
-}
\ No newline at end of file
+    color.rgb = linear_from_srgb(clamp(color.rgb, 0.0, 1.0));
+}
@@ -0,0 +1,8 @@
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    get_surface();
+    color = vec4(get_emissive(), 1.0);
+}
@@ -3,7 +3,19 @@ uniform sampler2DArray gbuffer;
 uniform sampler2DArray depthMap;
 uniform mat4 viewProjectionInverse;
 
+#ifdef LIGHT_VOLUME
+in vec4 clipPosition;
+vec2 uv;
+#else
 in vec2 uv;
+#endif
+
+// The G-buffer covers the whole render target, also when several cameras render to different viewports of it,
+// so it is read at the position of the fragment, while the uv coordinates are relative to the viewport of the camera
+vec4 read_gbuffer(int layer)
+{
+    return texelFetch(gbuffer, ivec3(gl_FragCoord.xy, layer), 0);
+}
 
 vec3 WorldPosFromDepth(float depth, vec2 uv) {
     vec4 clipSpacePosition = vec4(uv * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
@@ -13,21 +25,46 @@ vec3 WorldPosFromDepth(float depth, vec2 uv) {
 
 Surface get_surface()
 {
-    float depth = texture(depthMap, vec3(uv,0)).r;
-    if(depth > 0.99999)
+#ifdef LIGHT_VOLUME
+    uv = 0.5 * clipPosition.xy / clipPosition.w + 0.5;
+#endif
+    float depth = texelFetch(depthMap, ivec3(gl_FragCoord.xy, 0), 0).r;
+    // Nothing is written where the depth is still the clear value at the far plane, which is 0 when the depth is reversed
+    if(depth >= 1.0 || depth <= 0.0)
     {
         discard;
     }
-   	vec4 c = texture(gbuffer, vec3(uv, 0));
-    vec3 surface_color = c.rgb;
+    vec4 c = read_gbuffer(0);
+    vec4 n = read_gbuffer(1);
     vec3 position = WorldPosFromDepth(depth, uv);
-    vec4 n = texture(gbuffer, vec3(uv, 1));
-    vec3 normal = normalize(n.xyz*2.0 - 1.0);
-    float diffuse_intensity = c.w;
-    int t = int(floor(n.w*255.0));
-    float specular_intensity = float(t & 15) / 15.0;
-    float specular_power = 2.0 * float((t & 240) >> 4);
     gl_FragDepth = depth;
 
-    return Surface(position, normal, surface_color, diffuse_intensity, specular_intensity, specular_power);
-}
\ No newline at end of file
+    return Surface(position, decode_normal(n), decode_color(c), decode_diffuse_intensity(c), decode_specular_intensity(n), decode_specular_power(n));
+}
+
+// The functions below read the optional layers of the G-buffer and must be called after get_surface
+#ifdef GBUFFER_EMISSIVE_LAYER
+vec3 get_emissive()
+{
+    return decode_emissive(read_gbuffer(GBUFFER_EMISSIVE_LAYER));
+}
+#endif
+
+#ifdef GBUFFER_MATERIAL_LAYER
+vec2 get_metalness_roughness()
+{
+    return read_gbuffer(GBUFFER_MATERIAL_LAYER).xy;
+}
+
+int get_material_id()
+{
+    return decode_material_id(read_gbuffer(GBUFFER_MATERIAL_LAYER));
+}
+#endif
+
+#ifdef GBUFFER_MOTION_LAYER
+vec2 get_motion()
+{
+    return decode_motion(read_gbuffer(GBUFFER_MOTION_LAYER));
+}
+#endif
@@ -1,11 +1,35 @@
-
 layout (location = 0) out vec4 out_color;
 layout (location = 1) out vec4 out_normal;
 
+#ifdef GBUFFER_EMISSIVE_LAYER
+uniform vec3 emissive;
+layout (location = GBUFFER_EMISSIVE_LAYER) out vec4 out_emissive;
+#endif
+
+#ifdef GBUFFER_MATERIAL_LAYER
+uniform float metalness;
+uniform float roughness;
+uniform int materialId;
+layout (location = GBUFFER_MATERIAL_LAYER) out vec4 out_material;
+#endif
+
+#ifdef GBUFFER_MOTION_LAYER
+in vec4 currentClipPosition;
+in vec4 previousClipPosition;
+layout (location = GBUFFER_MOTION_LAYER) out vec4 out_motion;
+#endif
+
 void write(vec3 normal, vec3 color, float diffuse_intensity, float specular_intensity, float specular_power)
 {
-    out_color = vec4(color, diffuse_intensity);
-	int intensity = int(floor(specular_intensity * 15.0));
-	int power = int(floor(clamp(specular_power, 0.0, 30.0)*0.5));
-    out_normal = vec4(0.5 * normal + 0.5, float(power << 4 | intensity)/255.0);
-}
\ No newline at end of file
+    out_color = encode_color(color, diffuse_intensity);
+    out_normal = encode_normal(normal, specular_intensity, specular_power);
+#ifdef GBUFFER_EMISSIVE_LAYER
+    out_emissive = encode_emissive(emissive);
+#endif
+#ifdef GBUFFER_MATERIAL_LAYER
+    out_material = encode_material(metalness, roughness, materialId);
+#endif
+#ifdef GBUFFER_MOTION_LAYER
+    out_motion = encode_motion(0.5 * (currentClipPosition.xy / currentClipPosition.w - previousClipPosition.xy / previousClipPosition.w));
+#endif
+}
@@ -0,0 +1,8 @@
+in vec3 emissiveColor;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    color = vec4(emissiveColor, 1.0);
+}
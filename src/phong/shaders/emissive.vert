@@ -0,0 +1,12 @@
+uniform mat4 viewProjection;
+
+in vec3 position;
+in vec3 color;
+
+out vec3 emissiveColor;
+
+void main()
+{
+    emissiveColor = color;
+    gl_Position = viewProjection * vec4(position, 1.0);
+}
@@ -0,0 +1,18 @@
+uniform samplerCube irradianceMap;
+uniform samplerCube prefilteredMap;
+uniform float environmentIntensity;
+uniform float prefilteredMaxLod;
+
+// Diffuse lighting from the irradiance map and specular lighting from the prefiltered environment map,
+// where the roughness used to look up the prefiltered mip level is derived from the specular power
+vec3 calculate_environment_light(Surface surface)
+{
+    vec3 diffuse = surface.color * texture(irradianceMap, surface.normal).rgb;
+
+    vec3 view_direction = normalize(eyePosition - surface.position);
+    vec3 reflect_direction = reflect(-view_direction, surface.normal);
+    float roughness = sqrt(2.0 / (surface.specular_power + 2.0));
+    vec3 specular = surface.specular_intensity * textureLod(prefilteredMap, reflect_direction, roughness * prefilteredMaxLod).rgb;
+
+    return (diffuse + specular) * environmentIntensity;
+}
@@ -0,0 +1,129 @@
+// Encoding and decoding of the G-buffer layers, which depends on the defines given by the G-buffer layout
+
+#ifdef GBUFFER_OCTAHEDRAL_NORMALS
+const float MAX_SPECULAR_POWER = 255.0;
+#else
+const float MAX_SPECULAR_POWER = 30.0;
+#endif
+
+// Maps a unit vector to the square [-1, 1]^2 by projecting it onto an octahedron and unfolding the lower half,
+// see Cigolle et al. 2014, "A Survey of Efficient Representations for Independent Unit Vectors"
+vec2 octahedral_encode(vec3 n)
+{
+    n /= abs(n.x) + abs(n.y) + abs(n.z);
+    vec2 signs = vec2(n.x >= 0.0 ? 1.0 : -1.0, n.y >= 0.0 ? 1.0 : -1.0);
+    return n.z >= 0.0 ? n.xy : (1.0 - abs(n.yx)) * signs;
+}
+
+vec3 octahedral_decode(vec2 e)
+{
+    vec3 n = vec3(e, 1.0 - abs(e.x) - abs(e.y));
+    float t = max(-n.z, 0.0);
+    n.x += n.x >= 0.0 ? -t : t;
+    n.y += n.y >= 0.0 ? -t : t;
+    return normalize(n);
+}
+
+vec4 encode_color(vec3 color, float diffuse_intensity)
+{
+#ifdef GBUFFER_HIGH_PRECISION
+    return vec4(color, diffuse_intensity);
+#else
+    // The color is in linear space, so the square root is stored to keep the precision of dark colors in the 8 bit buffer
+    return vec4(sqrt(color), diffuse_intensity);
+#endif
+}
+
+vec3 decode_color(vec4 layer)
+{
+#ifdef GBUFFER_HIGH_PRECISION
+    return layer.rgb;
+#else
+    return layer.rgb * layer.rgb;
+#endif
+}
+
+float decode_diffuse_intensity(vec4 layer)
+{
+    return layer.w;
+}
+
+vec4 encode_normal(vec3 normal, float specular_intensity, float specular_power)
+{
+#ifdef GBUFFER_OCTAHEDRAL_NORMALS
+    return vec4(0.5 * octahedral_encode(normal) + 0.5, specular_intensity, clamp(specular_power, 0.0, MAX_SPECULAR_POWER) / MAX_SPECULAR_POWER);
+#else
+    // The specular intensity and power are packed into 4 bits each
+    int intensity = int(floor(specular_intensity * 15.0));
+    int power = int(floor(clamp(specular_power, 0.0, MAX_SPECULAR_POWER) * 0.5));
+    return vec4(0.5 * normal + 0.5, float(power << 4 | intensity) / 255.0);
+#endif
+}
+
+vec3 decode_normal(vec4 layer)
+{
+#ifdef GBUFFER_OCTAHEDRAL_NORMALS
+    return octahedral_decode(2.0 * layer.xy - 1.0);
+#else
+    return normalize(2.0 * layer.xyz - 1.0);
+#endif
+}
+
+float decode_specular_intensity(vec4 layer)
+{
+#ifdef GBUFFER_OCTAHEDRAL_NORMALS
+    return layer.z;
+#else
+    int t = int(round(layer.w * 255.0));
+    return float(t & 15) / 15.0;
+#endif
+}
+
+float decode_specular_power(vec4 layer)
+{
+#ifdef GBUFFER_OCTAHEDRAL_NORMALS
+    return layer.w * MAX_SPECULAR_POWER;
+#else
+    int t = int(round(layer.w * 255.0));
+    return 2.0 * float((t & 240) >> 4);
+#endif
+}
+
+vec4 encode_emissive(vec3 emissive)
+{
+    return encode_color(emissive, 1.0);
+}
+
+vec3 decode_emissive(vec4 layer)
+{
+    return decode_color(layer);
+}
+
+vec4 encode_material(float metalness, float roughness, int material_id)
+{
+    return vec4(metalness, roughness, float(material_id) / 255.0, 1.0);
+}
+
+int decode_material_id(vec4 layer)
+{
+    return int(round(layer.z * 255.0));
+}
+
+// The motion is given in uv coordinates, that is, the change in the uv coordinates of a point since the previous frame
+vec4 encode_motion(vec2 motion)
+{
+#ifdef GBUFFER_HIGH_PRECISION
+    return vec4(motion, 0.0, 1.0);
+#else
+    return vec4(clamp(0.5 * motion + 0.5, 0.0, 1.0), 0.0, 1.0);
+#endif
+}
+
+vec2 decode_motion(vec4 layer)
+{
+#ifdef GBUFFER_HIGH_PRECISION
+    return layer.xy;
+#else
+    return 2.0 * layer.xy - 1.0;
+#endif
+}
@@ -7,6 +7,15 @@ in vec2 cs;
 
 layout (location = 0) out vec4 out_color;
 layout (location = 1) out vec4 out_normal;
+#ifdef GBUFFER_EMISSIVE_LAYER
+layout (location = GBUFFER_EMISSIVE_LAYER) out vec4 out_emissive;
+#endif
+#ifdef GBUFFER_MATERIAL_LAYER
+layout (location = GBUFFER_MATERIAL_LAYER) out vec4 out_material;
+#endif
+#ifdef GBUFFER_MOTION_LAYER
+layout (location = GBUFFER_MOTION_LAYER) out vec4 out_motion;
+#endif
 
 void main()
 {
@@ -28,8 +37,20 @@ void main()
 
     vec4 normal0 = texture(tex, vec3(uv, float(no_views) + index0));
     vec4 normal1 = texture(tex, vec3(uv, float(no_views) + index1));
-    out_normal = mix(normal0, normal1, frac);
-    out_normal.xyz = 2.0 * out_normal.xyz - 1.0;
-    out_normal.xyz = 0.5 + 0.5 * normalize(vec3(cs.x * out_normal.x + cs.y * out_normal.z, out_normal.y, -cs.y * out_normal.x + cs.x * out_normal.z));
+    vec3 normal = normalize(mix(decode_normal(normal0), decode_normal(normal1), frac));
+    normal = normalize(vec3(cs.x * normal.x + cs.y * normal.z, normal.y, -cs.y * normal.x + cs.x * normal.z));
+    out_normal = encode_normal(normal, mix(decode_specular_intensity(normal0), decode_specular_intensity(normal1), frac),
+                               mix(decode_specular_power(normal0), decode_specular_power(normal1), frac));
+
+    // The optional layers are not stored for the imposters
+#ifdef GBUFFER_EMISSIVE_LAYER
+    out_emissive = encode_emissive(vec3(0.0));
+#endif
+#ifdef GBUFFER_MATERIAL_LAYER
+    out_material = encode_material(0.0, 1.0, 0);
+#endif
+#ifdef GBUFFER_MOTION_LAYER
+    out_motion = encode_motion(vec2(0.0));
+#endif
     // Maybe update depth as well?
 }
@@ -8,6 +8,8 @@ layout (std140) uniform Camera
     float padding;
 } camera;
 
+uniform vec3 translation;
+
 in vec3 center;
 in float theta;
 
@@ -30,5 +32,5 @@ void main()
     float angle = mod((s > 0.0 ? acos(c) : 2.0 * 3.1415926 - acos(c)) + theta, 2.0 * 3.1415926);
     t = angle / (2.0 * 3.1415926);
     cs = vec2(cos(theta), sin(theta));
-    gl_Position = camera.viewProjection * vec4(rot * position.xyz + center, 1.);
+    gl_Position = camera.viewProjection * vec4(rot * position.xyz + center + translation, 1.);
 }
@@ -18,6 +18,20 @@ struct BaseLight
     float intensity;
 };
 
+struct HemisphereLight
+{
+    vec3 skyColor;
+    vec3 groundColor;
+    vec3 up;
+    float intensity;
+};
+
+struct SHLight
+{
+    vec3 coefficients[9];
+    float intensity;
+};
+
 struct Attenuation
 {
     float constant;
@@ -92,7 +106,7 @@ vec3 calculate_attenuated_light(BaseLight light, Attenuation attenuation, vec3 l
     return color / max(1.0, att);
 }
 
-float is_visible(vec4 shadow_coord, vec2 offset)
+float is_visible(sampler2D shadowMap, vec4 shadow_coord, vec2 offset)
 {
     vec2 uv = (shadow_coord.xy + offset)/shadow_coord.w;
     float true_distance = (shadow_coord.z - 0.005)/shadow_coord.w;
@@ -100,7 +114,7 @@ float is_visible(vec4 shadow_coord, vec2 offset)
     return uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0 || shadow_cast_distance > true_distance ? 1.0 : 0.0;
 }
 
-float calculate_shadow(mat4 shadowMVP, vec3 position)
+float calculate_shadow(sampler2D shadowMap, mat4 shadowMVP, vec3 position)
 {
     if(shadowMVP[3][3] < 0.1) // Shadow disabled
     {
@@ -116,7 +130,7 @@ float calculate_shadow(mat4 shadowMVP, vec3 position)
                                  );
     for (int i=0;i<4;i++)
     {
-        visibility += is_visible(shadow_coord, poissonDisk[i] * 0.001f);
+        visibility += is_visible(shadowMap, shadow_coord, poissonDisk[i] * 0.001f);
     }
     return visibility * 0.25;
 }
@@ -126,21 +140,50 @@ vec3 calculate_ambient_light(BaseLight ambientLight, Surface surface)
     return surface.color * ambientLight.color * ambientLight.intensity;
 }
 
-vec3 calculate_directional_light(DirectionalLight directionalLight, Surface surface)
+vec3 calculate_hemisphere_light(HemisphereLight hemisphereLight, Surface surface)
+{
+    float t = 0.5 * dot(surface.normal, hemisphereLight.up) + 0.5;
+    return surface.color * mix(hemisphereLight.groundColor, hemisphereLight.skyColor, t) * hemisphereLight.intensity;
+}
+
+// The irradiance in the normal direction divided by pi, see Ramamoorthi and Hanrahan 2001, "An Efficient Representation for Irradiance Environment Maps"
+vec3 calculate_sh_light(SHLight shLight, Surface surface)
+{
+    const float c1 = 0.429043;
+    const float c2 = 0.511664;
+    const float c3 = 0.743125;
+    const float c4 = 0.886227;
+    const float c5 = 0.247708;
+    vec3 n = surface.normal;
+    vec3 irradiance = c1 * shLight.coefficients[8] * (n.x * n.x - n.y * n.y)
+        + c3 * shLight.coefficients[6] * n.z * n.z
+        + c4 * shLight.coefficients[0]
+        - c5 * shLight.coefficients[6]
+        + 2.0 * c1 * (shLight.coefficients[4] * n.x * n.y + shLight.coefficients[7] * n.x * n.z + shLight.coefficients[5] * n.y * n.z)
+        + 2.0 * c2 * (shLight.coefficients[3] * n.x + shLight.coefficients[1] * n.y + shLight.coefficients[2] * n.z);
+    return surface.color * max(irradiance, vec3(0.0)) * shLight.intensity / 3.14159265359;
+}
+
+vec3 calculate_directional_light(DirectionalLight directionalLight, sampler2D shadow_map, Surface surface)
 {
     vec3 light = calculate_light(directionalLight.base, directionalLight.direction, surface);
     if(directionalLight.shadowEnabled > 0.5) {
-        light *= calculate_shadow(directionalLight.shadowMVP, surface.position);
+        light *= calculate_shadow(shadow_map, directionalLight.shadowMVP, surface.position);
     }
     return surface.color * light;
 }
 
+vec3 calculate_directional_light(DirectionalLight directionalLight, Surface surface)
+{
+    return calculate_directional_light(directionalLight, shadowMap, surface);
+}
+
 vec3 calculate_point_light(PointLight pointLight, Surface surface)
 {
     return surface.color * calculate_attenuated_light(pointLight.base, pointLight.attenuation, pointLight.position, surface);
 }
 
-vec3 calculate_spot_light(SpotLight spotLight, Surface surface)
+vec3 calculate_spot_light(SpotLight spotLight, sampler2D shadow_map, Surface surface)
 {
     vec3 light_direction = normalize(surface.position - spotLight.position);
     float angle = acos(dot(light_direction, normalize(spotLight.direction)));
@@ -150,8 +193,13 @@ vec3 calculate_spot_light(SpotLight spotLight, Surface surface)
     if (angle < cutoff) {
         light = calculate_attenuated_light(spotLight.base, spotLight.attenuation, spotLight.position, surface) * (1.0 - smoothstep(0.75 * cutoff, cutoff, angle));
         if(spotLight.shadowEnabled > 0.5) {
-            light *= calculate_shadow(spotLight.shadowMVP, surface.position);
+            light *= calculate_shadow(shadow_map, spotLight.shadowMVP, surface.position);
         }
     }
     return surface.color * light;
+}
+
+vec3 calculate_spot_light(SpotLight spotLight, Surface surface)
+{
+    return calculate_spot_light(spotLight, shadowMap, surface);
 }
\ No newline at end of file
@@ -0,0 +1,17 @@
+uniform mat4 viewProjection;
+uniform mat4 modelMatrix;
+uniform float farClipDepth;
+
+in vec3 position;
+
+out vec4 clipPosition;
+
+void main()
+{
+    vec4 p = viewProjection * modelMatrix * vec4(position, 1.0);
+    // Move the parts of the volume behind the far plane onto the far plane, so that the back faces are always rendered
+    // The far plane is at z = w, or at z = 0 or z = -w when the depth is reversed
+    p.z = farClipDepth > 0.0 ? min(p.z, p.w) : max(p.z, farClipDepth * p.w);
+    clipPosition = p;
+    gl_Position = p;
+}
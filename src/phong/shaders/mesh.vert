@@ -18,6 +18,12 @@ out vec3 pos;
 out vec3 nor;
 out vec2 uvs;
 
+#ifdef GBUFFER_MOTION_LAYER
+uniform mat4 previousViewProjection;
+out vec4 currentClipPosition;
+out vec4 previousClipPosition;
+#endif
+
 void main()
 {
     vec4 worldPosition = modelMatrix * vec4(position, 1.);
@@ -25,4 +31,8 @@ void main()
     pos = worldPosition.xyz;
     uvs = uv_coordinates;
     gl_Position = camera.viewProjection * worldPosition;
+#ifdef GBUFFER_MOTION_LAYER
+    currentClipPosition = gl_Position;
+    previousClipPosition = previousViewProjection * worldPosition;
+#endif
 }
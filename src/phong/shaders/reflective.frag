@@ -0,0 +1,48 @@
+uniform vec4 color;
+uniform float reflectivity;
+uniform float distortion;
+uniform sampler2D reflectionMap;
+uniform bool use_normal_map;
+uniform sampler2D normalMap;
+uniform float normalMapScale;
+uniform vec2 normalMapOffset;
+
+uniform BaseLight ambientLight;
+
+layout (std140) uniform Camera
+{
+    mat4 viewProjection;
+    mat4 view;
+    mat4 projection;
+    vec3 position;
+    float padding;
+} camera;
+
+in vec3 nor;
+in vec3 pos;
+in vec2 uvs;
+
+layout (location = 0) out vec4 out_color;
+
+void main()
+{
+	vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+    vec2 offset = vec2(0.0);
+    if(use_normal_map) {
+        // The surface is assumed to be horizontal, so the normal map is mapped onto the xz plane
+        vec3 n = texture(normalMap, pos.xz * normalMapScale + normalMapOffset).xyz * 2.0 - 1.0;
+        normal = normalize(vec3(n.x, n.z, n.y));
+        offset = distortion * n.xy;
+    }
+
+    vec4 clip_position = camera.viewProjection * vec4(pos, 1.0);
+    vec2 screen_uv = clamp(0.5 * clip_position.xy / clip_position.w + 0.5 + offset, 0.0, 1.0);
+    vec3 reflection = texture(reflectionMap, screen_uv).rgb;
+
+    // Schlick's approximation of the Fresnel term
+    vec3 view_direction = normalize(eyePosition - pos);
+    float fresnel = reflectivity + (1.0 - reflectivity) * pow(1.0 - max(dot(normal, view_direction), 0.0), 5.0);
+
+    vec3 surface_color = calculate_ambient_light(ambientLight, Surface(pos, normal, color.rgb, 0.0, 0.0, 0.0));
+    out_color = vec4(mix(surface_color, reflection, fresnel), mix(color.a, 1.0, fresnel));
+}
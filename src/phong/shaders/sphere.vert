@@ -18,9 +18,19 @@ in vec3 position;
 out vec3 pos;
 out vec3 nor;
 
+#ifdef GBUFFER_MOTION_LAYER
+uniform mat4 previousViewProjection;
+out vec4 currentClipPosition;
+out vec4 previousClipPosition;
+#endif
+
 void main()
 {
     pos = scale * position + translation;
     nor = normalize(position);
     gl_Position = camera.viewProjection * modelMatrix * vec4(pos, 1.0);
+#ifdef GBUFFER_MOTION_LAYER
+    currentClipPosition = gl_Position;
+    previousClipPosition = previousViewProjection * modelMatrix * vec4(pos, 1.0);
+#endif
 }
\ No newline at end of file
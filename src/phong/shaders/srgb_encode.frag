@@ -0,0 +1,20 @@
+uniform sampler2D colorMap;
+uniform sampler2D depthMap;
+
+in vec2 uv;
+
+layout (location = 0) out vec4 color;
+
+// The color map is in the sRGB format, so the colors are already converted to linear space when sampled
+vec3 srgb_from_linear(vec3 rgb)
+{
+    vec3 a = vec3(0.055);
+    return mix(12.92 * rgb, (1.0 + a) * pow(rgb, vec3(1.0 / 2.4)) - a, step(vec3(0.0031308), rgb));
+}
+
+void main()
+{
+    vec4 c = texture(colorMap, uv);
+    color = vec4(srgb_from_linear(c.rgb), c.a);
+    gl_FragDepth = texture(depthMap, uv).r;
+}
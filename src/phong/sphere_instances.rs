@@ -8,18 +8,21 @@ pub struct SphereInstances {
     ball_vertex_buffer: VertexBuffer,
     no_vertices: u32,
     pub material: PhongMaterial,
-    pub ball_radius: f32
+    pub ball_radius: f32,
+    ///
+    /// The origin of the sphere positions in world space, which the transformation given when rendering is relative to, see [PhongForwardMesh::origin].
+    ///
+    pub origin: Vec3d
 }
 
 impl SphereInstances
 {
-    pub(crate) fn new(gl: &Gl, positions: &[f32], ball_radius: f32, material: &PhongMaterial) -> Result<Self, Error>
+    pub(crate) fn new(gl: &Gl, gbuffer_layout: &GBufferLayout, positions: &[f32], ball_radius: f32, material: &PhongMaterial) -> Result<Self, Error>
     {
-        let program = Program::from_source(&gl,
-                                                    include_str!("shaders/sphere.vert"),
-                                                    &format!("{}\n{}",
-                                                             include_str!("shaders/deferred_objects_shared.frag"),
-                                                             include_str!("shaders/colored_deferred.frag")))?;
+        let program = Program::from_shader_source(&gl, &ShaderSource::from_segments(vec![gbuffer_layout.shader_defines(), shader_segment!("shaders/sphere.vert")]),
+                                                    &ShaderSource::from_segments([gbuffer_layout.shader_segments(), vec![
+                                                                          shader_segment!("shaders/deferred_objects_shared.frag"),
+                                                                          shader_segment!("shaders/colored_deferred.frag")]].concat()))?;
 
         let x = 0.525731112119133606;
         let z = 0.850650808352039932;
@@ -40,7 +43,7 @@ impl SphereInstances
         let instance_buffer = VertexBuffer::new_with_dynamic_f32(gl, positions)?;
 
         Ok(SphereInstances { program, instance_buffer, ball_index_buffer, ball_vertex_buffer, no_vertices: positions.len() as u32/3,
-            material: material.clone(), ball_radius })
+            material: material.clone(), ball_radius, origin: vec3d(0.0, 0.0, 0.0) })
     }
 
     pub fn update_positions(&mut self, positions: &[f32])
@@ -53,12 +56,13 @@ impl SphereInstances
         self.program.add_uniform_float("diffuse_intensity", &self.material.diffuse_intensity)?;
         self.program.add_uniform_float("specular_intensity", &self.material.specular_intensity)?;
         self.program.add_uniform_float("specular_power", &self.material.specular_power)?;
+        use_optional_layer_uniforms(&self.program, &self.material, camera)?;
 
         let color = if let ColorSource::Color(c) = self.material.color_source {c} else {vec4(1.0, 0.0, 0.0, 1.0)};
         self.program.add_uniform_vec4("color", &color)?;
 
         self.program.add_uniform_float("scale", &self.ball_radius)?;
-        self.program.add_uniform_mat4("modelMatrix", &transformation)?;
+        self.program.add_uniform_mat4("modelMatrix", &camera.render_space_transformation(&self.origin, transformation))?;
         self.program.use_uniform_block(camera.matrix_buffer(), "Camera");
 
         self.program.use_attribute_vec3_float_divisor(&self.instance_buffer, "translation", 1)?;